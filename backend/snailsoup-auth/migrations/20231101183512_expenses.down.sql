@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS expense_tags;
+DROP TABLE IF EXISTS expenses;
+DROP TABLE IF EXISTS user_tags;
+DROP TABLE IF EXISTS user_categories;
\ No newline at end of file
@@ -0,0 +1,30 @@
+CREATE TABLE IF NOT EXISTS user_categories (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES app_users(id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS user_tags (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES app_users(id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS expenses (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES app_users(id) ON DELETE CASCADE,
+    -- Deleting a category keeps its expenses, uncategorized
+    category_id UUID REFERENCES user_categories(id) ON DELETE SET NULL,
+    description VARCHAR(255) NOT NULL,
+    expense_date DATE NOT NULL,
+    cost NUMERIC(12, 2) NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS expense_tags (
+    id UUID PRIMARY KEY,
+    user_tag_id UUID NOT NULL REFERENCES user_tags(id) ON DELETE CASCADE,
+    expense_id UUID NOT NULL REFERENCES expenses(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS expenses_user_id_idx ON expenses(user_id, expense_date);
+CREATE INDEX IF NOT EXISTS expense_tags_expense_id_idx ON expense_tags(expense_id);
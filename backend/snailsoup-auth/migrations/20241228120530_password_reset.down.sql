@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS password_reset_tokens;
+ALTER TABLE app_users DROP COLUMN IF EXISTS email;
\ No newline at end of file
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS login_failures (
+    -- 'user' counters are keyed by the user id, 'unknown_login' by the normalized login of a
+    -- missing account and 'ip' by the client address
+    kind VARCHAR(20) NOT NULL,
+    subject VARCHAR(255) NOT NULL,
+    failed_attempts INT NOT NULL,
+    last_failed_at TIMESTAMPTZ NOT NULL,
+    locked_until TIMESTAMPTZ,
+    PRIMARY KEY (kind, subject)
+);
+
+CREATE INDEX IF NOT EXISTS login_failures_last_failed_at_idx ON login_failures(last_failed_at);
@@ -0,0 +1,4 @@
+ALTER TABLE app_users DROP CONSTRAINT IF EXISTS app_users_account_role_fkey;
+ALTER TABLE app_users ADD CONSTRAINT role_check CHECK(account_role IN ('Admin', 'User'));
+DROP TABLE IF EXISTS role_permissions;
+DROP TABLE IF EXISTS roles;
@@ -0,0 +1,20 @@
+CREATE TABLE IF NOT EXISTS roles (
+    name VARCHAR(20) PRIMARY KEY
+);
+
+INSERT INTO roles (name) VALUES ('Admin'), ('User') ON CONFLICT DO NOTHING;
+
+CREATE TABLE IF NOT EXISTS role_permissions (
+    role VARCHAR(20) NOT NULL REFERENCES roles(name) ON DELETE CASCADE,
+    permission VARCHAR(64) NOT NULL,
+    PRIMARY KEY (role, permission)
+);
+
+INSERT INTO role_permissions (role, permission) VALUES
+    ('Admin', 'users:read'),
+    ('Admin', 'users:write'),
+    ('Admin', 'expenses:read')
+ON CONFLICT DO NOTHING;
+
+ALTER TABLE app_users DROP CONSTRAINT IF EXISTS role_check;
+ALTER TABLE app_users ADD CONSTRAINT app_users_account_role_fkey FOREIGN KEY (account_role) REFERENCES roles(name);
@@ -0,0 +1,3 @@
+DELETE FROM role_permissions WHERE permission = 'users:delete';
+
+ALTER TABLE app_users DROP COLUMN IF EXISTS disabled_at;
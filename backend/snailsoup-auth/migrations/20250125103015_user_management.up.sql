@@ -0,0 +1,3 @@
+ALTER TABLE app_users ADD COLUMN IF NOT EXISTS disabled_at TIMESTAMPTZ;
+
+INSERT INTO role_permissions (role, permission) VALUES ('Admin', 'users:delete') ON CONFLICT DO NOTHING;
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS app_users_created_at_idx;
+
+ALTER TABLE app_users DROP COLUMN IF EXISTS created_at;
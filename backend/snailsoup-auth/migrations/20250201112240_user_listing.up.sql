@@ -0,0 +1,3 @@
+ALTER TABLE app_users ADD COLUMN IF NOT EXISTS created_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+CREATE INDEX IF NOT EXISTS app_users_created_at_idx ON app_users(created_at, id);
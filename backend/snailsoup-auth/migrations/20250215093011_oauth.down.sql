@@ -0,0 +1,4 @@
+DELETE FROM role_permissions WHERE permission IN ('clients:read', 'clients:write');
+
+DROP TABLE IF EXISTS oauth_authorization_codes;
+DROP TABLE IF EXISTS oauth_clients;
@@ -0,0 +1,34 @@
+CREATE TABLE IF NOT EXISTS oauth_clients (
+    id UUID PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    -- Public clients (single page and mobile apps) have no secret and rely on PKCE
+    secret_hash VARCHAR(64),
+    redirect_uris TEXT[] NOT NULL,
+    grant_types TEXT[] NOT NULL,
+    scopes TEXT[] NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS oauth_authorization_codes (
+    code_hash VARCHAR(64) PRIMARY KEY,
+    client_id UUID NOT NULL REFERENCES oauth_clients(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES app_users(id) ON DELETE CASCADE,
+    redirect_uri TEXT NOT NULL,
+    -- The token request must repeat a redirect_uri that was sent to /authorize
+    redirect_uri_sent BOOLEAN NOT NULL,
+    scopes TEXT[] NOT NULL,
+    nonce TEXT,
+    code_challenge VARCHAR(128) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ,
+    -- Access token issued for the code, revoked when the code is used again
+    access_token_jti UUID
+);
+
+CREATE INDEX IF NOT EXISTS oauth_authorization_codes_expires_at_idx ON oauth_authorization_codes(expires_at);
+
+INSERT INTO role_permissions (role, permission) VALUES
+    ('Admin', 'clients:read'),
+    ('Admin', 'clients:write')
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS email_verification_tokens;
+DROP INDEX IF EXISTS app_users_email_lower_idx;
+ALTER TABLE app_users DROP COLUMN IF EXISTS email_verified_at;
@@ -0,0 +1,3 @@
+DELETE FROM role_permissions WHERE permission IN ('invites:read', 'invites:write');
+DROP TABLE IF EXISTS invite_codes;
+ALTER TABLE app_users DROP COLUMN IF EXISTS pending_approval;
@@ -0,0 +1,16 @@
+ALTER TABLE app_users ADD COLUMN IF NOT EXISTS pending_approval BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE IF NOT EXISTS invite_codes (
+    id UUID PRIMARY KEY,
+    code_hash VARCHAR(64) NOT NULL UNIQUE,
+    max_uses INTEGER NOT NULL,
+    uses INTEGER NOT NULL DEFAULT 0,
+    created_by UUID REFERENCES app_users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    expires_at TIMESTAMPTZ
+);
+
+INSERT INTO role_permissions (role, permission) VALUES
+    ('Admin', 'invites:read'),
+    ('Admin', 'invites:write')
+ON CONFLICT DO NOTHING;
@@ -0,0 +1 @@
+DROP INDEX IF EXISTS app_users_username_lower_idx;
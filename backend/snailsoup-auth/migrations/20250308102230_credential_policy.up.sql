@@ -0,0 +1,23 @@
+-- Usernames are unique ignoring case. Accounts that only differ in case from an older one are
+-- renamed first by appending the start of their id, each rename is reported as a notice.
+DO $$
+DECLARE
+    duplicate RECORD;
+BEGIN
+    FOR duplicate IN
+        SELECT id, username FROM (
+            SELECT id, username, row_number() OVER (
+                PARTITION BY lower(username) ORDER BY created_at, id
+            ) AS position
+            FROM app_users
+        ) AS ranked
+        WHERE position > 1
+    LOOP
+        UPDATE app_users SET username = duplicate.username || '-' || left(duplicate.id::text, 8)
+        WHERE id = duplicate.id;
+        RAISE NOTICE 'Renamed user % from % to %', duplicate.id, duplicate.username,
+            duplicate.username || '-' || left(duplicate.id::text, 8);
+    END LOOP;
+END $$;
+
+CREATE UNIQUE INDEX IF NOT EXISTS app_users_username_lower_idx ON app_users (lower(username));
@@ -0,0 +1,58 @@
+-- No foreign keys so that events outlive the accounts they mention
+CREATE TABLE IF NOT EXISTS audit_events (
+    id UUID PRIMARY KEY,
+    occurred_at TIMESTAMPTZ NOT NULL,
+    actor_id UUID,
+    actor_name VARCHAR(255),
+    action VARCHAR(64) NOT NULL,
+    target_id UUID,
+    ip VARCHAR(45),
+    user_agent VARCHAR(512),
+    outcome VARCHAR(16) NOT NULL,
+    detail TEXT
+);
+
+CREATE INDEX IF NOT EXISTS audit_events_occurred_at_idx ON audit_events(occurred_at, id);
+CREATE INDEX IF NOT EXISTS audit_events_actor_id_idx ON audit_events(actor_id, occurred_at);
+CREATE INDEX IF NOT EXISTS audit_events_target_id_idx ON audit_events(target_id, occurred_at);
+CREATE INDEX IF NOT EXISTS audit_events_action_idx ON audit_events(action, occurred_at);
+
+-- Deleting an account strips the personal data from the events about it. Inside a transaction
+-- that set `snailsoup.pseudonymize_user` to the account id, events of that account may have their
+-- actor name, address and user agent cleared and events targeting it their detail; nothing else.
+CREATE OR REPLACE FUNCTION audit_events_append_only() RETURNS trigger AS $$
+DECLARE
+    pseudonymized_user UUID := NULLIF(current_setting('snailsoup.pseudonymize_user', true), '')::UUID;
+    is_actor BOOLEAN;
+BEGIN
+    IF TG_OP = 'UPDATE' AND pseudonymized_user IS NOT NULL THEN
+        is_actor := OLD.actor_id = pseudonymized_user OR (
+            OLD.actor_id IS NULL AND lower(OLD.actor_name) IN (
+                SELECT lower(username) FROM app_users WHERE id = pseudonymized_user
+                UNION
+                SELECT lower(email) FROM app_users WHERE id = pseudonymized_user AND email IS NOT NULL
+            )
+        );
+
+        IF (NEW.id, NEW.occurred_at, NEW.actor_id, NEW.action, NEW.target_id, NEW.outcome)
+                IS NOT DISTINCT FROM
+                (OLD.id, OLD.occurred_at, OLD.actor_id, OLD.action, OLD.target_id, OLD.outcome)
+            AND (NEW.actor_name IS NOT DISTINCT FROM OLD.actor_name OR (NEW.actor_name IS NULL AND is_actor))
+            AND (NEW.ip IS NOT DISTINCT FROM OLD.ip OR (NEW.ip IS NULL AND is_actor))
+            AND (NEW.user_agent IS NOT DISTINCT FROM OLD.user_agent OR (NEW.user_agent IS NULL AND is_actor))
+            AND (NEW.detail IS NOT DISTINCT FROM OLD.detail
+                OR (NEW.detail IS NULL AND OLD.target_id = pseudonymized_user))
+        THEN
+            RETURN NEW;
+        END IF;
+    END IF;
+
+    RAISE EXCEPTION 'audit_events is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS audit_events_append_only ON audit_events;
+CREATE TRIGGER audit_events_append_only BEFORE UPDATE OR DELETE ON audit_events
+    FOR EACH ROW EXECUTE FUNCTION audit_events_append_only();
+
+INSERT INTO role_permissions (role, permission) VALUES ('Admin', 'audit:read') ON CONFLICT DO NOTHING;
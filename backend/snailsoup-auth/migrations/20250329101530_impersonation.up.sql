@@ -0,0 +1 @@
+INSERT INTO role_permissions (role, permission) VALUES ('Admin', 'users:impersonate') ON CONFLICT DO NOTHING;
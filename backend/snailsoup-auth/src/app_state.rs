@@ -4,7 +4,15 @@ use axum::extract::FromRef;
 
 use crate::{
     config::Config,
-    services::{auth::AuthService, user::UserService},
+    services::{
+        account::AccountService, api_token::ApiTokenService, audit::AuditService,
+        auth::AuthService, email_verification::EmailVerificationService, expense::ExpenseService,
+        introspection::IntrospectionService, invite::InviteService,
+        login_throttle::LoginThrottleService, oauth::OAuthService,
+        password_reset::PasswordResetService, permission::PermissionService,
+        registration::RegistrationService, session::SessionService, two_factor::TwoFactorService,
+        user::UserService,
+    },
 };
 
 #[derive(Clone)]
@@ -12,20 +20,20 @@ pub(super) struct AppState {
     pub config: Config,
     pub auth_service: Arc<AuthService>,
     pub user_service: Arc<UserService>,
-}
-
-impl AppState {
-    pub fn new(
-        config: Config,
-        auth_service: Arc<AuthService>,
-        user_service: Arc<UserService>,
-    ) -> AppState {
-        AppState {
-            config,
-            auth_service,
-            user_service,
-        }
-    }
+    pub expense_service: Arc<ExpenseService>,
+    pub password_reset_service: Arc<PasswordResetService>,
+    pub registration_service: Arc<RegistrationService>,
+    pub email_verification_service: Arc<EmailVerificationService>,
+    pub two_factor_service: Arc<TwoFactorService>,
+    pub login_throttle_service: Arc<LoginThrottleService>,
+    pub permission_service: Arc<PermissionService>,
+    pub api_token_service: Arc<ApiTokenService>,
+    pub oauth_service: Arc<OAuthService>,
+    pub introspection_service: Arc<IntrospectionService>,
+    pub invite_service: Arc<InviteService>,
+    pub audit_service: Arc<AuditService>,
+    pub account_service: Arc<AccountService>,
+    pub session_service: Arc<SessionService>,
 }
 
 impl FromRef<AppState> for Config {
@@ -45,3 +53,87 @@ impl FromRef<AppState> for Arc<UserService> {
         app_state.user_service.clone()
     }
 }
+
+impl FromRef<AppState> for Arc<ExpenseService> {
+    fn from_ref(app_state: &AppState) -> Arc<ExpenseService> {
+        app_state.expense_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<EmailVerificationService> {
+    fn from_ref(app_state: &AppState) -> Arc<EmailVerificationService> {
+        app_state.email_verification_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<PasswordResetService> {
+    fn from_ref(app_state: &AppState) -> Arc<PasswordResetService> {
+        app_state.password_reset_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<TwoFactorService> {
+    fn from_ref(app_state: &AppState) -> Arc<TwoFactorService> {
+        app_state.two_factor_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<LoginThrottleService> {
+    fn from_ref(app_state: &AppState) -> Arc<LoginThrottleService> {
+        app_state.login_throttle_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<PermissionService> {
+    fn from_ref(app_state: &AppState) -> Arc<PermissionService> {
+        app_state.permission_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<ApiTokenService> {
+    fn from_ref(app_state: &AppState) -> Arc<ApiTokenService> {
+        app_state.api_token_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<OAuthService> {
+    fn from_ref(app_state: &AppState) -> Arc<OAuthService> {
+        app_state.oauth_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<RegistrationService> {
+    fn from_ref(app_state: &AppState) -> Arc<RegistrationService> {
+        app_state.registration_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<InviteService> {
+    fn from_ref(app_state: &AppState) -> Arc<InviteService> {
+        app_state.invite_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<IntrospectionService> {
+    fn from_ref(app_state: &AppState) -> Arc<IntrospectionService> {
+        app_state.introspection_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<AuditService> {
+    fn from_ref(app_state: &AppState) -> Arc<AuditService> {
+        app_state.audit_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<SessionService> {
+    fn from_ref(app_state: &AppState) -> Arc<SessionService> {
+        app_state.session_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<AccountService> {
+    fn from_ref(app_state: &AppState) -> Arc<AccountService> {
+        app_state.account_service.clone()
+    }
+}
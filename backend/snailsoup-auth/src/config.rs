@@ -1,24 +1,286 @@
-use std::env;
+use std::{env, str::FromStr};
+
+use axum_extra::extract::cookie::SameSite;
+use jsonwebtoken::Algorithm;
+
+/// Who may create an account through `/api/auth/register`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegistrationMode {
+    Open,
+    /// An invite code created by an admin is required
+    Invite,
+    /// Accounts can log in only after an admin approved them
+    Approval,
+}
 
 #[derive(Debug, Clone)]
 pub struct Config {
-    pub jwt_secret: String,
+    pub jwt_algorithm: Algorithm,
+    pub jwt_secret: Option<String>,
+    pub jwt_private_key_path: Option<String>,
+    pub jwt_key_id: Option<String>,
+    /// Retired public keys (kid, path) that are still accepted and published in the JWKS
+    pub jwt_verification_keys: Vec<(String, String)>,
     pub jwt_expires_in: String,
     pub jwt_maxage: u32,
+    pub refresh_token_maxage: u32,
+    /// One of `smtp`, `file` or `stdout`
+    pub mail_transport: String,
+    pub mail_from: String,
+    pub mail_outbox_dir: Option<String>,
+    pub smtp_host: Option<String>,
+    pub smtp_port: Option<u16>,
+    /// One of `starttls`, `tls` or `none`
+    pub smtp_tls: String,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    /// Frontend page the password reset token is appended to
+    pub password_reset_url: String,
+    pub password_reset_token_maxage: u32,
+    /// Frontend page the email verification token is appended to
+    pub email_verification_url: String,
+    pub email_verification_token_maxage: u32,
+    /// Refuse logins until the account's email address is verified, registration then requires an email
+    pub require_email_verification: bool,
+    /// Issuer shown in authenticator apps
+    pub totp_issuer: String,
+    /// Minutes between the password step and the second factor step of a login
+    pub mfa_token_maxage: u32,
+    /// Minutes an impersonation token issued to an admin stays valid
+    pub impersonation_token_maxage: u32,
+    /// Failed logins for one username before it is locked
+    pub login_max_attempts: u32,
+    /// Failed logins from one address before it is blocked
+    pub login_max_attempts_per_ip: u32,
+    pub login_lockout_minutes: u32,
+    /// Delay after the first failed login for a username, doubled with every further failure
+    pub login_delay_seconds: u32,
+    /// Take the client address from `X-Forwarded-For`, enable only behind a reverse proxy
+    pub trust_proxy_headers: bool,
+    /// Answer registrations the same way whether or not the username or email is taken
+    pub conceal_registration_conflicts: bool,
+    pub registration_mode: RegistrationMode,
+    pub username_min_length: u32,
+    pub username_max_length: u32,
+    pub password_min_length: u32,
+    pub password_max_length: u32,
+    /// How many of lowercase letters, uppercase letters, digits and other characters a password needs
+    pub password_min_character_classes: u32,
+    /// File with one known breached password per line, new passwords on the list are refused
+    pub breached_passwords_file: Option<String>,
+    /// Argon2id memory cost in KiB, existing hashes are upgraded on the next login when changed
+    pub argon2_memory_kib: u32,
+    pub argon2_iterations: u32,
+    pub argon2_parallelism: u32,
+    /// Public base URL of this server, used as `iss` of OAuth tokens and in the discovery document
+    pub oauth_issuer: String,
+    /// Frontend page asking the signed in user to approve an OAuth client, gets the authorization request as query
+    pub oauth_consent_url: String,
+    /// Cookie holding the access token in session cookie mode, also read by the forward-auth endpoint
+    pub access_token_cookie: String,
+    /// Login sets HttpOnly session cookies and cookie authenticated requests need a CSRF token
+    pub session_cookies: bool,
+    /// Send session cookies over https only, disable for local development over http
+    pub cookie_secure: bool,
+    pub cookie_same_site: SameSite,
 }
 
 impl Config {
     pub fn init() -> Config {
-        let jwt_secret = env::var("JWT_SECRET").expect("JWT_SECRET must be set");
+        let jwt_algorithm = env::var("JWT_ALGORITHM").unwrap_or("HS256".to_owned());
+        let jwt_algorithm = match Algorithm::from_str(&jwt_algorithm) {
+            Err(_) => panic!("JWT_ALGORITHM must be a valid JWT algorithm name"),
+            Ok(val) => val,
+        };
+        let jwt_secret = env::var("JWT_SECRET").ok();
+        let jwt_private_key_path = env::var("JWT_PRIVATE_KEY_PATH").ok();
+        let jwt_key_id = env::var("JWT_KEY_ID").ok();
+        match jwt_algorithm {
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => {
+                if jwt_secret.is_none() {
+                    panic!("JWT_SECRET must be set");
+                }
+            }
+            _ => {
+                if jwt_private_key_path.is_none() || jwt_key_id.is_none() {
+                    panic!("JWT_PRIVATE_KEY_PATH and JWT_KEY_ID must be set for asymmetric JWT_ALGORITHM");
+                }
+            }
+        }
+        let jwt_verification_keys = env::var("JWT_VERIFICATION_KEYS")
+            .map(|keys| {
+                keys.split(',')
+                    .filter(|key| !key.trim().is_empty())
+                    .map(|key| match key.split_once('=') {
+                        Some((kid, path)) => (kid.trim().to_owned(), path.trim().to_owned()),
+                        None => panic!("JWT_VERIFICATION_KEYS must be a list of kid=path pairs"),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
         let jwt_expires_in = env::var("JWT_EXPIRED_IN").expect("JWT_EXPIRED_IN must be set");
         let jwt_maxage = env::var("JWT_MAXAGE").expect("JWT_MAXAGE must be set");
+        let refresh_token_maxage =
+            env::var("REFRESH_TOKEN_MAXAGE").expect("REFRESH_TOKEN_MAXAGE must be set");
+        let mail_transport = env::var("MAIL_TRANSPORT").unwrap_or("stdout".to_owned());
+        let mail_from = env::var("MAIL_FROM").unwrap_or("snailsoup@localhost".to_owned());
+        let smtp_port = env::var("SMTP_PORT")
+            .ok()
+            .map(|port| match port.parse::<u16>() {
+                Err(_) => panic!("SMTP_PORT must be a port number"),
+                Ok(val) => val,
+            });
+        let password_reset_url = env::var("PASSWORD_RESET_URL")
+            .unwrap_or("http://localhost:3000/reset-password".to_owned());
+        let password_reset_token_maxage =
+            env::var("PASSWORD_RESET_TOKEN_MAXAGE").unwrap_or("30".to_owned());
+        let email_verification_url = env::var("EMAIL_VERIFICATION_URL")
+            .unwrap_or("http://localhost:3000/verify-email".to_owned());
+        let email_verification_token_maxage =
+            env::var("EMAIL_VERIFICATION_TOKEN_MAXAGE").unwrap_or("1440".to_owned());
+        let mfa_token_maxage = env::var("MFA_TOKEN_MAXAGE").unwrap_or("5".to_owned());
+        let impersonation_token_maxage =
+            env::var("IMPERSONATION_TOKEN_MAXAGE").unwrap_or("15".to_owned());
+        let login_max_attempts = env::var("LOGIN_MAX_ATTEMPTS").unwrap_or("5".to_owned());
+        let login_max_attempts_per_ip =
+            env::var("LOGIN_MAX_ATTEMPTS_PER_IP").unwrap_or("50".to_owned());
+        let login_lockout_minutes = env::var("LOGIN_LOCKOUT_MINUTES").unwrap_or("15".to_owned());
+        let login_delay_seconds = env::var("LOGIN_DELAY_SECONDS").unwrap_or("1".to_owned());
+        let username_min_length = env::var("USERNAME_MIN_LENGTH").unwrap_or("3".to_owned());
+        let username_max_length = env::var("USERNAME_MAX_LENGTH").unwrap_or("32".to_owned());
+        let password_min_length = env::var("PASSWORD_MIN_LENGTH").unwrap_or("8".to_owned());
+        let password_max_length = env::var("PASSWORD_MAX_LENGTH").unwrap_or("128".to_owned());
+        let argon2_memory_kib = env::var("ARGON2_MEMORY_KIB").unwrap_or("19456".to_owned());
+        let argon2_iterations = env::var("ARGON2_ITERATIONS").unwrap_or("2".to_owned());
+        let argon2_parallelism = env::var("ARGON2_PARALLELISM").unwrap_or("1".to_owned());
+        let password_min_character_classes =
+            env::var("PASSWORD_MIN_CHARACTER_CLASSES").unwrap_or("1".to_owned());
         Config {
+            jwt_algorithm,
             jwt_secret,
+            jwt_private_key_path,
+            jwt_key_id,
+            jwt_verification_keys,
             jwt_expires_in,
             jwt_maxage: match jwt_maxage.parse::<u32>() {
                 Err(_) => panic!("JWT_MAXAGE must be an integer value"),
                 Ok(val) => val,
             },
+            refresh_token_maxage: match refresh_token_maxage.parse::<u32>() {
+                Err(_) => panic!("REFRESH_TOKEN_MAXAGE must be an integer value"),
+                Ok(val) => val,
+            },
+            mail_transport,
+            mail_from,
+            mail_outbox_dir: env::var("MAIL_OUTBOX_DIR").ok(),
+            smtp_host: env::var("SMTP_HOST").ok(),
+            smtp_port,
+            smtp_tls: env::var("SMTP_TLS").unwrap_or("starttls".to_owned()),
+            smtp_username: env::var("SMTP_USERNAME").ok(),
+            smtp_password: env::var("SMTP_PASSWORD").ok(),
+            password_reset_url,
+            password_reset_token_maxage: match password_reset_token_maxage.parse::<u32>() {
+                Err(_) => panic!("PASSWORD_RESET_TOKEN_MAXAGE must be an integer value"),
+                Ok(val) => val,
+            },
+            email_verification_url,
+            email_verification_token_maxage: match email_verification_token_maxage.parse::<u32>() {
+                Err(_) => panic!("EMAIL_VERIFICATION_TOKEN_MAXAGE must be an integer value"),
+                Ok(val) => val,
+            },
+            require_email_verification: env::var("REQUIRE_EMAIL_VERIFICATION")
+                == Ok("true".to_owned()),
+            totp_issuer: env::var("TOTP_ISSUER").unwrap_or("snail-soup".to_owned()),
+            mfa_token_maxage: match mfa_token_maxage.parse::<u32>() {
+                Err(_) => panic!("MFA_TOKEN_MAXAGE must be an integer value"),
+                Ok(val) => val,
+            },
+            impersonation_token_maxage: match impersonation_token_maxage.parse::<u32>() {
+                Err(_) => panic!("IMPERSONATION_TOKEN_MAXAGE must be an integer value"),
+                Ok(val) => val,
+            },
+            login_max_attempts: match login_max_attempts.parse::<u32>() {
+                Err(_) => panic!("LOGIN_MAX_ATTEMPTS must be an integer value"),
+                Ok(val) => val,
+            },
+            login_max_attempts_per_ip: match login_max_attempts_per_ip.parse::<u32>() {
+                Err(_) => panic!("LOGIN_MAX_ATTEMPTS_PER_IP must be an integer value"),
+                Ok(val) => val,
+            },
+            login_lockout_minutes: match login_lockout_minutes.parse::<u32>() {
+                Err(_) => panic!("LOGIN_LOCKOUT_MINUTES must be an integer value"),
+                Ok(val) => val,
+            },
+            login_delay_seconds: match login_delay_seconds.parse::<u32>() {
+                Err(_) => panic!("LOGIN_DELAY_SECONDS must be an integer value"),
+                Ok(val) => val,
+            },
+            trust_proxy_headers: env::var("TRUST_PROXY_HEADERS") == Ok("true".to_owned()),
+            conceal_registration_conflicts: env::var("CONCEAL_REGISTRATION_CONFLICTS")
+                == Ok("true".to_owned()),
+            registration_mode: match env::var("REGISTRATION_MODE")
+                .unwrap_or("open".to_owned())
+                .as_str()
+            {
+                "open" => RegistrationMode::Open,
+                "invite" => RegistrationMode::Invite,
+                "approval" => RegistrationMode::Approval,
+                _ => panic!("REGISTRATION_MODE must be open, invite or approval"),
+            },
+            username_min_length: match username_min_length.parse::<u32>() {
+                Err(_) => panic!("USERNAME_MIN_LENGTH must be an integer value"),
+                Ok(val) => val,
+            },
+            username_max_length: match username_max_length.parse::<u32>() {
+                // Length of the app_users.username column
+                Ok(val) if val <= 255 => val,
+                _ => panic!("USERNAME_MAX_LENGTH must be an integer value of at most 255"),
+            },
+            password_min_length: match password_min_length.parse::<u32>() {
+                Err(_) => panic!("PASSWORD_MIN_LENGTH must be an integer value"),
+                Ok(val) => val,
+            },
+            password_max_length: match password_max_length.parse::<u32>() {
+                Err(_) => panic!("PASSWORD_MAX_LENGTH must be an integer value"),
+                Ok(val) => val,
+            },
+            password_min_character_classes: match password_min_character_classes.parse::<u32>() {
+                Ok(val) if val <= 4 => val,
+                _ => panic!(
+                    "PASSWORD_MIN_CHARACTER_CLASSES must be an integer value between 0 and 4"
+                ),
+            },
+            breached_passwords_file: env::var("BREACHED_PASSWORDS_FILE").ok(),
+            argon2_memory_kib: match argon2_memory_kib.parse::<u32>() {
+                Err(_) => panic!("ARGON2_MEMORY_KIB must be an integer value"),
+                Ok(val) => val,
+            },
+            argon2_iterations: match argon2_iterations.parse::<u32>() {
+                Err(_) => panic!("ARGON2_ITERATIONS must be an integer value"),
+                Ok(val) => val,
+            },
+            argon2_parallelism: match argon2_parallelism.parse::<u32>() {
+                Err(_) => panic!("ARGON2_PARALLELISM must be an integer value"),
+                Ok(val) => val,
+            },
+            oauth_issuer: env::var("OAUTH_ISSUER")
+                .unwrap_or("http://localhost:3000".to_owned())
+                .trim_end_matches('/')
+                .to_owned(),
+            oauth_consent_url: env::var("OAUTH_CONSENT_URL")
+                .unwrap_or("http://localhost:3000/oauth/consent".to_owned()),
+            access_token_cookie: env::var("ACCESS_TOKEN_COOKIE")
+                .unwrap_or("snailsoup_token".to_owned()),
+            session_cookies: env::var("SESSION_COOKIES") == Ok("true".to_owned()),
+            cookie_secure: env::var("COOKIE_SECURE") != Ok("false".to_owned()),
+            cookie_same_site: match env::var("COOKIE_SAME_SITE")
+                .unwrap_or("Strict".to_owned())
+                .as_str()
+            {
+                "Strict" => SameSite::Strict,
+                "Lax" => SameSite::Lax,
+                _ => panic!("COOKIE_SAME_SITE must be Strict or Lax"),
+            },
         }
     }
 }
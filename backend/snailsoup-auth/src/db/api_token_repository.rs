@@ -0,0 +1,107 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{db::schema::ApiTokenSchema, domain::api_token::ApiToken};
+
+pub struct ApiTokenRepository {
+    pool: Pool<Postgres>,
+}
+
+impl ApiTokenRepository {
+    pub fn new(pool: Pool<Postgres>) -> ApiTokenRepository {
+        ApiTokenRepository { pool }
+    }
+
+    pub async fn get_by_hash(&self, token_hash: &str) -> Result<Option<ApiToken>, sqlx::Error> {
+        let token = sqlx::query_as!(
+            ApiTokenSchema,
+            "
+            SELECT *
+            FROM api_tokens
+            WHERE token_hash = $1
+            ",
+            token_hash
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|e| e.into());
+
+        Ok(token)
+    }
+
+    pub async fn get_all_for_user(&self, user_id: Uuid) -> Result<Vec<ApiToken>, sqlx::Error> {
+        let tokens = sqlx::query_as!(
+            ApiTokenSchema,
+            "
+            SELECT *
+            FROM api_tokens
+            WHERE user_id = $1
+            ORDER BY created_at
+            ",
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|e| e.into())
+        .collect();
+
+        Ok(tokens)
+    }
+
+    pub async fn insert(&self, token: ApiToken) -> Result<Uuid, sqlx::Error> {
+        let id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO api_tokens (id, user_id, name, token_hash, scopes, created_at, expires_at, last_used_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING id
+            "#,
+            token.id,
+            token.user_id,
+            token.name,
+            token.token_hash,
+            &token.scopes,
+            token.created_at,
+            token.expires_at,
+            token.last_used_at
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    pub async fn mark_used(&self, id: Uuid, used_at: DateTime<Utc>) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE api_tokens SET last_used_at = $1 WHERE id = $2",
+            used_at,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete(&self, id: Uuid, user_id: Uuid) -> Result<Option<Uuid>, sqlx::Error> {
+        let id = sqlx::query_scalar!(
+            r#"
+            DELETE FROM api_tokens WHERE id = $1 AND user_id = $2 RETURNING id
+            "#,
+            id,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    pub async fn delete_all_for_user(&self, user_id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM api_tokens WHERE user_id = $1", user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,78 @@
+use sqlx::{Pool, Postgres, QueryBuilder};
+
+use crate::{
+    db::schema::AuditEventSchema, domain::audit_event::AuditEvent, utils::audit_query::AuditQuery,
+};
+
+pub struct AuditRepository {
+    pool: Pool<Postgres>,
+}
+
+impl AuditRepository {
+    pub fn new(pool: Pool<Postgres>) -> AuditRepository {
+        AuditRepository { pool }
+    }
+
+    pub async fn insert(&self, event: AuditEvent) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO audit_events (id, occurred_at, actor_id, actor_name, action, target_id, ip, user_agent, outcome, detail)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+            event.id,
+            event.occurred_at,
+            event.actor_id,
+            event.actor_name,
+            event.action,
+            event.target_id,
+            event.ip,
+            event.user_agent,
+            event.outcome,
+            event.detail
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Newest events first
+    pub async fn search(&self, query: &AuditQuery) -> Result<Vec<AuditEvent>, sqlx::Error> {
+        let mut builder = QueryBuilder::new("SELECT * FROM audit_events WHERE TRUE");
+
+        if let Some(user_id) = query.user_id {
+            builder
+                .push(" AND (actor_id = ")
+                .push_bind(user_id)
+                .push(" OR target_id = ")
+                .push_bind(user_id)
+                .push(")");
+        }
+
+        if let Some(action) = &query.action {
+            builder.push(" AND action = ").push_bind(action.clone());
+        }
+
+        if let Some(from) = query.from {
+            builder.push(" AND occurred_at >= ").push_bind(from);
+        }
+
+        if let Some(to) = query.to {
+            builder.push(" AND occurred_at < ").push_bind(to);
+        }
+
+        builder
+            .push(" ORDER BY occurred_at DESC, id DESC LIMIT ")
+            .push_bind(query.limit);
+
+        let events = builder
+            .build_query_as::<AuditEventSchema>()
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|e| e.into())
+            .collect();
+
+        Ok(events)
+    }
+}
@@ -0,0 +1,94 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    db::schema::EmailVerificationTokenSchema, domain::email_verification::EmailVerificationToken,
+};
+
+pub struct EmailVerificationRepository {
+    pool: Pool<Postgres>,
+}
+
+impl EmailVerificationRepository {
+    pub fn new(pool: Pool<Postgres>) -> EmailVerificationRepository {
+        EmailVerificationRepository { pool }
+    }
+
+    pub async fn get_by_hash(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<EmailVerificationToken>, sqlx::Error> {
+        let token = sqlx::query_as!(
+            EmailVerificationTokenSchema,
+            "
+            SELECT *
+            FROM email_verification_tokens
+            WHERE token_hash = $1
+            ",
+            token_hash
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|e| e.into());
+
+        Ok(token)
+    }
+
+    /// Stores a new token, discarding every token previously issued to the user.
+    pub async fn replace_for_user(
+        &self,
+        token: EmailVerificationToken,
+    ) -> Result<Uuid, sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        sqlx::query!(
+            "DELETE FROM email_verification_tokens WHERE user_id = $1",
+            token.user_id
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        let id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO email_verification_tokens (id, user_id, email, token_hash, created_at, expires_at, used_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id
+            "#,
+            token.id,
+            token.user_id,
+            token.email,
+            token.token_hash,
+            token.created_at,
+            token.expires_at,
+            token.used_at
+        )
+        .fetch_one(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(id)
+    }
+
+    /// Marks the token as used unless it was already used.
+    /// Returns `None` when another request has consumed the token first.
+    pub async fn mark_used(
+        &self,
+        id: Uuid,
+        used_at: DateTime<Utc>,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        let id = sqlx::query_scalar!(
+            r#"
+            UPDATE email_verification_tokens SET used_at = $1
+            WHERE id = $2 AND used_at IS NULL
+            RETURNING id
+            "#,
+            used_at,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+}
@@ -1,4 +1,5 @@
 use sqlx::{Pool, Postgres, QueryBuilder};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 use crate::{
@@ -13,7 +14,7 @@ pub struct ExpenseRepository {
 
 impl ExpenseRepository {
     pub fn new(pool: Pool<Postgres>) -> ExpenseRepository {
-        ExpenseRepository { pool: pool }
+        ExpenseRepository { pool }
     }
 
     pub async fn get_expense(&self, expense_id: Uuid) -> Result<Option<FullExpense>, sqlx::Error> {
@@ -153,6 +154,34 @@ impl ExpenseRepository {
         Ok(added_expense)
     }
 
+    /// Ids of the tags of every expense of the user, keyed by expense id
+    pub async fn get_expense_tag_ids_by_user_id(
+        &self,
+        user_id: Uuid,
+    ) -> Result<HashMap<Uuid, Vec<Uuid>>, sqlx::Error> {
+        let rows = sqlx::query!(
+            "
+            SELECT expense_tags.expense_id, expense_tags.user_tag_id
+            FROM expense_tags
+            JOIN expenses ON expenses.id = expense_tags.expense_id
+            WHERE expenses.user_id = $1
+            ",
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut tag_ids: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for row in rows {
+            tag_ids
+                .entry(row.expense_id)
+                .or_default()
+                .push(row.user_tag_id);
+        }
+
+        Ok(tag_ids)
+    }
+
     pub async fn get_all_tags_by_user_id(&self, user_id: Uuid) -> Result<Vec<Tag>, sqlx::Error> {
         let tags = sqlx::query_as!(
             TagSchema,
@@ -314,28 +343,14 @@ impl ExpenseRepository {
         Ok(id)
     }
 
-    pub async fn delete_category_force(
-        &self,
-        category_id: Uuid,
-    ) -> Result<Option<Uuid>, sqlx::Error> {
-        let mut transaction = self.pool.begin().await?;
-        sqlx::query!(
-            "UPDATE expenses SET category_id = NULL WHERE category_id = $1",
+    pub async fn is_category_in_use(&self, category_id: Uuid) -> Result<bool, sqlx::Error> {
+        let in_use = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM expenses WHERE category_id = $1) AS "in_use!""#,
             category_id
         )
-        .execute(&mut *transaction)
-        .await?;
-        let id = sqlx::query_scalar!(
-            r#"
-            DELETE FROM user_categories WHERE id = $1 RETURNING id
-            "#,
-            category_id
-        )
-        .fetch_optional(&mut *transaction)
+        .fetch_one(&self.pool)
         .await?;
 
-        transaction.commit().await?;
-
-        Ok(id)
+        Ok(in_use)
     }
 }
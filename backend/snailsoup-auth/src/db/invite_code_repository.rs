@@ -0,0 +1,100 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{db::schema::InviteCodeSchema, domain::invite_code::InviteCode};
+
+pub struct InviteCodeRepository {
+    pool: Pool<Postgres>,
+}
+
+impl InviteCodeRepository {
+    pub fn new(pool: Pool<Postgres>) -> InviteCodeRepository {
+        InviteCodeRepository { pool }
+    }
+
+    pub async fn get_all(&self) -> Result<Vec<InviteCode>, sqlx::Error> {
+        let codes = sqlx::query_as!(
+            InviteCodeSchema,
+            "
+            SELECT *
+            FROM invite_codes
+            ORDER BY created_at
+            "
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|e| e.into())
+        .collect();
+
+        Ok(codes)
+    }
+
+    pub async fn insert(&self, code: InviteCode) -> Result<Uuid, sqlx::Error> {
+        let id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO invite_codes (id, code_hash, max_uses, uses, created_by, created_at, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id
+            "#,
+            code.id,
+            code.code_hash,
+            code.max_uses,
+            code.uses,
+            code.created_by,
+            code.created_at,
+            code.expires_at
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    /// Counts one use of the code in a single statement so that concurrent registrations
+    /// cannot exceed `max_uses`. Returns `None` for unknown, expired and used up codes.
+    pub async fn redeem(
+        &self,
+        code_hash: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        let id = sqlx::query_scalar!(
+            r#"
+            UPDATE invite_codes SET uses = uses + 1
+            WHERE code_hash = $1 AND uses < max_uses AND (expires_at IS NULL OR expires_at > $2)
+            RETURNING id
+            "#,
+            code_hash,
+            now
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    /// Gives back a use taken by `redeem` when the registration failed afterwards
+    pub async fn release(&self, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE invite_codes SET uses = uses - 1 WHERE id = $1 AND uses > 0",
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete(&self, id: Uuid) -> Result<Option<Uuid>, sqlx::Error> {
+        let id = sqlx::query_scalar!(
+            r#"
+            DELETE FROM invite_codes WHERE id = $1 RETURNING id
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+}
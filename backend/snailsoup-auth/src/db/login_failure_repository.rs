@@ -0,0 +1,119 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+
+use crate::{db::schema::LoginFailureSchema, domain::login_failure::LoginFailure};
+
+pub struct LoginFailureRepository {
+    pool: Pool<Postgres>,
+}
+
+impl LoginFailureRepository {
+    pub fn new(pool: Pool<Postgres>) -> LoginFailureRepository {
+        LoginFailureRepository { pool }
+    }
+
+    pub async fn get(
+        &self,
+        kind: &str,
+        subject: &str,
+    ) -> Result<Option<LoginFailure>, sqlx::Error> {
+        let failure = sqlx::query_as!(
+            LoginFailureSchema,
+            "
+            SELECT *
+            FROM login_failures
+            WHERE kind = $1 AND subject = $2
+            ",
+            kind,
+            subject
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|e| e.into());
+
+        Ok(failure)
+    }
+
+    /// Counts one more failed attempt. Counting starts over when the previous failure happened
+    /// before `window_start`.
+    pub async fn record(
+        &self,
+        kind: &str,
+        subject: &str,
+        failed_at: DateTime<Utc>,
+        window_start: DateTime<Utc>,
+    ) -> Result<LoginFailure, sqlx::Error> {
+        let failure = sqlx::query_as!(
+            LoginFailureSchema,
+            r#"
+            INSERT INTO login_failures (kind, subject, failed_attempts, last_failed_at, locked_until)
+            VALUES ($1, $2, 1, $3, NULL)
+            ON CONFLICT (kind, subject) DO UPDATE
+            SET failed_attempts = CASE
+                    WHEN login_failures.last_failed_at < $4 THEN 1
+                    ELSE login_failures.failed_attempts + 1
+                END,
+                locked_until = CASE
+                    WHEN login_failures.last_failed_at < $4 THEN NULL
+                    ELSE login_failures.locked_until
+                END,
+                last_failed_at = $3
+            RETURNING *
+            "#,
+            kind,
+            subject,
+            failed_at,
+            window_start
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .into();
+
+        Ok(failure)
+    }
+
+    pub async fn lock(
+        &self,
+        kind: &str,
+        subject: &str,
+        locked_until: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE login_failures SET locked_until = $1 WHERE kind = $2 AND subject = $3",
+            locked_until,
+            kind,
+            subject
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn clear(&self, kind: &str, subject: &str) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "DELETE FROM login_failures WHERE kind = $1 AND subject = $2",
+            kind,
+            subject
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Drops counters that would start over anyway so the table does not grow without bounds.
+    pub async fn delete_stale(&self, before: DateTime<Utc>) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            DELETE FROM login_failures
+            WHERE last_failed_at < $1 AND (locked_until IS NULL OR locked_until < $1)
+            "#,
+            before
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
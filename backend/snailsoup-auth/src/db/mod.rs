@@ -1,3 +1,29 @@
+mod api_token_repository;
+mod audit_repository;
+mod email_verification_repository;
+mod expense_repository;
+mod invite_code_repository;
+mod login_failure_repository;
+mod oauth_repository;
+mod password_reset_repository;
+mod permission_repository;
+mod refresh_token_repository;
+mod revocation_repository;
 mod schema;
+mod session_repository;
+mod two_factor_repository;
 mod user_repository;
+pub use api_token_repository::ApiTokenRepository;
+pub use audit_repository::AuditRepository;
+pub use email_verification_repository::EmailVerificationRepository;
+pub use expense_repository::ExpenseRepository;
+pub use invite_code_repository::InviteCodeRepository;
+pub use login_failure_repository::LoginFailureRepository;
+pub use oauth_repository::OAuthRepository;
+pub use password_reset_repository::PasswordResetRepository;
+pub use permission_repository::PermissionRepository;
+pub use refresh_token_repository::RefreshTokenRepository;
+pub use revocation_repository::RevocationRepository;
+pub use session_repository::SessionRepository;
+pub use two_factor_repository::TwoFactorRepository;
 pub use user_repository::AppUserRepository;
@@ -0,0 +1,176 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    db::schema::{AuthorizationCodeSchema, OAuthClientSchema},
+    domain::oauth::{AuthorizationCode, OAuthClient},
+};
+
+pub struct OAuthRepository {
+    pool: Pool<Postgres>,
+}
+
+impl OAuthRepository {
+    pub fn new(pool: Pool<Postgres>) -> OAuthRepository {
+        OAuthRepository { pool }
+    }
+
+    pub async fn get_client(&self, id: Uuid) -> Result<Option<OAuthClient>, sqlx::Error> {
+        let client = sqlx::query_as!(
+            OAuthClientSchema,
+            "
+            SELECT *
+            FROM oauth_clients
+            WHERE id = $1
+            ",
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|e| e.into());
+
+        Ok(client)
+    }
+
+    pub async fn get_all_clients(&self) -> Result<Vec<OAuthClient>, sqlx::Error> {
+        let clients = sqlx::query_as!(
+            OAuthClientSchema,
+            "
+            SELECT *
+            FROM oauth_clients
+            ORDER BY created_at
+            "
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|e| e.into())
+        .collect();
+
+        Ok(clients)
+    }
+
+    pub async fn insert_client(&self, client: OAuthClient) -> Result<Uuid, sqlx::Error> {
+        let id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO oauth_clients (id, name, secret_hash, redirect_uris, grant_types, scopes, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id
+            "#,
+            client.id,
+            client.name,
+            client.secret_hash,
+            &client.redirect_uris,
+            &client.grant_types,
+            &client.scopes,
+            client.created_at
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    /// Authorization codes issued to the client are removed with it.
+    pub async fn delete_client(&self, id: Uuid) -> Result<Option<Uuid>, sqlx::Error> {
+        let id = sqlx::query_scalar!(
+            r#"
+            DELETE FROM oauth_clients WHERE id = $1 RETURNING id
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    pub async fn insert_code(&self, code: AuthorizationCode) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO oauth_authorization_codes
+                (code_hash, client_id, user_id, redirect_uri, redirect_uri_sent, scopes, nonce, code_challenge, created_at, expires_at, used_at, access_token_jti)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            "#,
+            code.code_hash,
+            code.client_id,
+            code.user_id,
+            code.redirect_uri,
+            code.redirect_uri_sent,
+            &code.scopes,
+            code.nonce,
+            code.code_challenge,
+            code.created_at,
+            code.expires_at,
+            code.used_at,
+            code.access_token_jti
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Marks the code as used by the access token `access_token_jti` and returns it, or `None`
+    /// when it does not exist or was used before.
+    pub async fn use_code(
+        &self,
+        code_hash: &str,
+        used_at: DateTime<Utc>,
+        access_token_jti: Uuid,
+    ) -> Result<Option<AuthorizationCode>, sqlx::Error> {
+        let code = sqlx::query_as!(
+            AuthorizationCodeSchema,
+            r#"
+            UPDATE oauth_authorization_codes SET used_at = $1, access_token_jti = $3
+            WHERE code_hash = $2 AND used_at IS NULL
+            RETURNING *
+            "#,
+            used_at,
+            code_hash,
+            access_token_jti
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|e| e.into());
+
+        Ok(code)
+    }
+
+    pub async fn get_code(
+        &self,
+        code_hash: &str,
+    ) -> Result<Option<AuthorizationCode>, sqlx::Error> {
+        let code = sqlx::query_as!(
+            AuthorizationCodeSchema,
+            "SELECT * FROM oauth_authorization_codes WHERE code_hash = $1",
+            code_hash
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|e| e.into());
+
+        Ok(code)
+    }
+
+    /// Deletes unused codes past `now` and used ones older than `used_before`. Used codes are
+    /// kept while their access token is valid so that a replay can still revoke it.
+    pub async fn delete_expired_codes(
+        &self,
+        now: DateTime<Utc>,
+        used_before: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            DELETE FROM oauth_authorization_codes
+            WHERE expires_at < $1 AND (used_at IS NULL OR used_at < $2)
+            "#,
+            now,
+            used_before
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+use sqlx::{Pool, Postgres};
+
+pub struct PermissionRepository {
+    pool: Pool<Postgres>,
+}
+
+impl PermissionRepository {
+    pub fn new(pool: Pool<Postgres>) -> PermissionRepository {
+        PermissionRepository { pool }
+    }
+
+    pub async fn has_permission(&self, role: &str, permission: &str) -> Result<bool, sqlx::Error> {
+        let granted = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM role_permissions WHERE role = $1 AND permission = $2
+            ) AS "granted!"
+            "#,
+            role,
+            permission
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(granted)
+    }
+
+    /// Whether `role` is granted every permission of `other`.
+    pub async fn role_includes(&self, role: &str, other: &str) -> Result<bool, sqlx::Error> {
+        let includes = sqlx::query_scalar!(
+            r#"
+            SELECT NOT EXISTS(
+                SELECT permission FROM role_permissions WHERE role = $2
+                EXCEPT
+                SELECT permission FROM role_permissions WHERE role = $1
+            ) AS "includes!"
+            "#,
+            role,
+            other
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(includes)
+    }
+
+    pub async fn role_exists(&self, role: &str) -> Result<bool, sqlx::Error> {
+        let exists = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS(SELECT 1 FROM roles WHERE name = $1) AS "exists!"
+            "#,
+            role
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(exists)
+    }
+}
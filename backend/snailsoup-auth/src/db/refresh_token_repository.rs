@@ -0,0 +1,156 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{db::schema::RefreshTokenSchema, domain::refresh_token::RefreshToken};
+
+pub struct RefreshTokenRepository {
+    pool: Pool<Postgres>,
+}
+
+impl RefreshTokenRepository {
+    pub fn new(pool: Pool<Postgres>) -> RefreshTokenRepository {
+        RefreshTokenRepository { pool }
+    }
+
+    pub async fn get_by_hash(&self, token_hash: &str) -> Result<Option<RefreshToken>, sqlx::Error> {
+        let token = sqlx::query_as!(
+            RefreshTokenSchema,
+            "
+            SELECT *
+            FROM refresh_tokens
+            WHERE token_hash = $1
+            ",
+            token_hash
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|e| e.into());
+
+        Ok(token)
+    }
+
+    pub async fn insert(&self, token: RefreshToken) -> Result<Uuid, sqlx::Error> {
+        let id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO refresh_tokens (id, user_id, family_id, token_hash, created_at, expires_at, used_at, revoked_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING id
+            "#,
+            token.id,
+            token.user_id,
+            token.family_id,
+            token.token_hash,
+            token.created_at,
+            token.expires_at,
+            token.used_at,
+            token.revoked_at
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    /// Marks the token as used unless it was already used or revoked.
+    /// Returns `None` when another request has consumed the token first.
+    pub async fn mark_used(
+        &self,
+        id: Uuid,
+        used_at: DateTime<Utc>,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        let id = sqlx::query_scalar!(
+            r#"
+            UPDATE refresh_tokens SET used_at = $1
+            WHERE id = $2 AND used_at IS NULL AND revoked_at IS NULL
+            RETURNING id
+            "#,
+            used_at,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    pub async fn revoke_family(
+        &self,
+        family_id: Uuid,
+        revoked_at: DateTime<Utc>,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE refresh_tokens SET revoked_at = $1
+            WHERE family_id = $2 AND revoked_at IS NULL
+            "#,
+            revoked_at,
+            family_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    pub async fn revoke_family_by_token_hash(
+        &self,
+        token_hash: &str,
+        user_id: Uuid,
+        revoked_at: DateTime<Utc>,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE refresh_tokens SET revoked_at = $1
+            WHERE family_id IN (SELECT family_id FROM refresh_tokens WHERE token_hash = $2 AND user_id = $3)
+            AND revoked_at IS NULL
+            "#,
+            revoked_at,
+            token_hash,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    pub async fn revoke_all_for_user(
+        &self,
+        user_id: Uuid,
+        revoked_at: DateTime<Utc>,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE refresh_tokens SET revoked_at = $1
+            WHERE user_id = $2 AND revoked_at IS NULL
+            "#,
+            revoked_at,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    pub async fn revoke_all_for_user_except_family(
+        &self,
+        user_id: Uuid,
+        family_id: Uuid,
+        revoked_at: DateTime<Utc>,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE refresh_tokens SET revoked_at = $1
+            WHERE user_id = $2 AND family_id <> $3 AND revoked_at IS NULL
+            "#,
+            revoked_at,
+            user_id,
+            family_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
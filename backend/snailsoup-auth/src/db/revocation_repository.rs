@@ -0,0 +1,94 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+pub struct RevocationRepository {
+    pool: Pool<Postgres>,
+}
+
+impl RevocationRepository {
+    pub fn new(pool: Pool<Postgres>) -> RevocationRepository {
+        RevocationRepository { pool }
+    }
+
+    pub async fn revoke_token(
+        &self,
+        jti: Uuid,
+        user_id: Uuid,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        // Revoked tokens are only relevant until they expire on their own
+        sqlx::query!("DELETE FROM revoked_tokens WHERE expires_at < NOW()")
+            .execute(&mut *transaction)
+            .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO revoked_tokens (jti, user_id, expires_at) VALUES ($1, $2, $3)
+            ON CONFLICT (jti) DO NOTHING
+            "#,
+            jti,
+            user_id,
+            expires_at
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn is_token_revoked(&self, jti: Uuid) -> Result<bool, sqlx::Error> {
+        let revoked = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS(SELECT 1 FROM revoked_tokens WHERE jti = $1) AS "revoked!"
+            "#,
+            jti
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(revoked)
+    }
+
+    /// Invalidates every token of the user issued before `revoked_before`.
+    pub async fn revoke_all_for_user(
+        &self,
+        user_id: Uuid,
+        revoked_before: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO user_token_revocations (user_id, revoked_before) VALUES ($1, $2)
+            ON CONFLICT (user_id) DO UPDATE SET revoked_before = EXCLUDED.revoked_before
+            "#,
+            user_id,
+            revoked_before
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_revoked_before(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+        let revoked_before = sqlx::query_scalar!(
+            "
+            SELECT revoked_before
+            FROM user_token_revocations
+            WHERE user_id = $1
+            ",
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(revoked_before)
+    }
+}
@@ -1,21 +1,383 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
 use uuid::Uuid;
 
-use crate::domain::app_user::AppUser;
+use crate::domain::{
+    api_token::ApiToken,
+    app_user::{AppUser, Role},
+    audit_event::AuditEvent,
+    email_verification::EmailVerificationToken,
+    expense::{Category, CategoryData, Expense, ExpenseData, Tag, TagData},
+    invite_code::InviteCode,
+    login_failure::LoginFailure,
+    oauth::{AuthorizationCode, OAuthClient},
+    password_reset::PasswordResetToken,
+    refresh_token::RefreshToken,
+    session::Session,
+    two_factor::TotpCredential,
+};
 
+#[derive(sqlx::FromRow)]
 pub struct AppUserSchema {
     pub id: Uuid,
     pub username: String,
     pub password_hash: String,
     pub account_role: String,
+    pub email: Option<String>,
+    pub disabled_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    pub pending_approval: bool,
 }
 
-impl Into<AppUser> for AppUserSchema {
-    fn into(self) -> AppUser {
+impl From<AppUserSchema> for AppUser {
+    fn from(value: AppUserSchema) -> Self {
         AppUser {
-            id: self.id,
-            username: self.username,
-            password_hash: self.password_hash,
-            account_role: self.account_role,
+            id: value.id,
+            username: value.username,
+            password_hash: value.password_hash,
+            // Guaranteed to exist in `roles` by the foreign key
+            account_role: Role::new(value.account_role),
+            email: value.email,
+            email_verified_at: value.email_verified_at,
+            disabled_at: value.disabled_at,
+            pending_approval: value.pending_approval,
+            created_at: value.created_at,
+        }
+    }
+}
+
+pub struct ExpenseSchema {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub category_id: Option<Uuid>,
+    pub description: String,
+    pub expense_date: NaiveDate,
+    pub cost: Decimal,
+}
+
+impl From<ExpenseSchema> for Expense {
+    fn from(value: ExpenseSchema) -> Self {
+        Expense {
+            id: value.id,
+            data: ExpenseData {
+                user_id: value.user_id,
+                category_id: value.category_id,
+                description: value.description,
+                expense_date: value.expense_date,
+                cost: value.cost,
+            },
+        }
+    }
+}
+
+pub struct TagSchema {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+}
+
+impl From<TagSchema> for Tag {
+    fn from(value: TagSchema) -> Self {
+        Tag {
+            id: value.id,
+            data: TagData {
+                user_id: value.user_id,
+                name: value.name,
+            },
+        }
+    }
+}
+
+pub struct CategorySchema {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+}
+
+impl From<CategorySchema> for Category {
+    fn from(value: CategorySchema) -> Self {
+        Category {
+            id: value.id,
+            data: CategoryData {
+                user_id: value.user_id,
+                name: value.name,
+            },
+        }
+    }
+}
+
+pub struct RefreshTokenSchema {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub family_id: Uuid,
+    pub token_hash: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl From<RefreshTokenSchema> for RefreshToken {
+    fn from(value: RefreshTokenSchema) -> Self {
+        RefreshToken {
+            id: value.id,
+            user_id: value.user_id,
+            family_id: value.family_id,
+            token_hash: value.token_hash,
+            created_at: value.created_at,
+            expires_at: value.expires_at,
+            used_at: value.used_at,
+            revoked_at: value.revoked_at,
+        }
+    }
+}
+
+pub struct SessionSchema {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub device_name: String,
+    pub ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl From<SessionSchema> for Session {
+    fn from(value: SessionSchema) -> Self {
+        Session {
+            id: value.id,
+            user_id: value.user_id,
+            device_name: value.device_name,
+            ip: value.ip,
+            created_at: value.created_at,
+            last_seen_at: value.last_seen_at,
+            revoked_at: value.revoked_at,
+        }
+    }
+}
+
+pub struct PasswordResetTokenSchema {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub token_hash: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+}
+
+impl From<PasswordResetTokenSchema> for PasswordResetToken {
+    fn from(value: PasswordResetTokenSchema) -> Self {
+        PasswordResetToken {
+            id: value.id,
+            user_id: value.user_id,
+            token_hash: value.token_hash,
+            created_at: value.created_at,
+            expires_at: value.expires_at,
+            used_at: value.used_at,
+        }
+    }
+}
+
+pub struct EmailVerificationTokenSchema {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub email: String,
+    pub token_hash: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+}
+
+impl From<EmailVerificationTokenSchema> for EmailVerificationToken {
+    fn from(value: EmailVerificationTokenSchema) -> Self {
+        EmailVerificationToken {
+            id: value.id,
+            user_id: value.user_id,
+            email: value.email,
+            token_hash: value.token_hash,
+            created_at: value.created_at,
+            expires_at: value.expires_at,
+            used_at: value.used_at,
+        }
+    }
+}
+
+pub struct TotpCredentialSchema {
+    pub user_id: Uuid,
+    pub secret: String,
+    pub created_at: DateTime<Utc>,
+    pub confirmed_at: Option<DateTime<Utc>>,
+    pub last_used_step: Option<i64>,
+}
+
+impl From<TotpCredentialSchema> for TotpCredential {
+    fn from(value: TotpCredentialSchema) -> Self {
+        TotpCredential {
+            user_id: value.user_id,
+            secret: value.secret,
+            created_at: value.created_at,
+            confirmed_at: value.confirmed_at,
+            last_used_step: value.last_used_step,
+        }
+    }
+}
+
+pub struct LoginFailureSchema {
+    pub kind: String,
+    pub subject: String,
+    pub failed_attempts: i32,
+    pub last_failed_at: DateTime<Utc>,
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+impl From<LoginFailureSchema> for LoginFailure {
+    fn from(value: LoginFailureSchema) -> Self {
+        LoginFailure {
+            kind: value.kind,
+            subject: value.subject,
+            failed_attempts: value.failed_attempts,
+            last_failed_at: value.last_failed_at,
+            locked_until: value.locked_until,
+        }
+    }
+}
+
+pub struct ApiTokenSchema {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub token_hash: String,
+    pub scopes: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl From<ApiTokenSchema> for ApiToken {
+    fn from(value: ApiTokenSchema) -> Self {
+        ApiToken {
+            id: value.id,
+            user_id: value.user_id,
+            name: value.name,
+            token_hash: value.token_hash,
+            scopes: value.scopes,
+            created_at: value.created_at,
+            expires_at: value.expires_at,
+            last_used_at: value.last_used_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+pub struct AuditEventSchema {
+    pub id: Uuid,
+    pub occurred_at: DateTime<Utc>,
+    pub actor_id: Option<Uuid>,
+    pub actor_name: Option<String>,
+    pub action: String,
+    pub target_id: Option<Uuid>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub outcome: String,
+    pub detail: Option<String>,
+}
+
+impl From<AuditEventSchema> for AuditEvent {
+    fn from(value: AuditEventSchema) -> Self {
+        AuditEvent {
+            id: value.id,
+            occurred_at: value.occurred_at,
+            actor_id: value.actor_id,
+            actor_name: value.actor_name,
+            action: value.action,
+            target_id: value.target_id,
+            ip: value.ip,
+            user_agent: value.user_agent,
+            outcome: value.outcome,
+            detail: value.detail,
+        }
+    }
+}
+
+pub struct InviteCodeSchema {
+    pub id: Uuid,
+    pub code_hash: String,
+    pub max_uses: i32,
+    pub uses: i32,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl From<InviteCodeSchema> for InviteCode {
+    fn from(value: InviteCodeSchema) -> Self {
+        InviteCode {
+            id: value.id,
+            code_hash: value.code_hash,
+            max_uses: value.max_uses,
+            uses: value.uses,
+            created_by: value.created_by,
+            created_at: value.created_at,
+            expires_at: value.expires_at,
+        }
+    }
+}
+
+pub struct OAuthClientSchema {
+    pub id: Uuid,
+    pub name: String,
+    pub secret_hash: Option<String>,
+    pub redirect_uris: Vec<String>,
+    pub grant_types: Vec<String>,
+    pub scopes: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<OAuthClientSchema> for OAuthClient {
+    fn from(value: OAuthClientSchema) -> Self {
+        OAuthClient {
+            id: value.id,
+            name: value.name,
+            secret_hash: value.secret_hash,
+            redirect_uris: value.redirect_uris,
+            grant_types: value.grant_types,
+            scopes: value.scopes,
+            created_at: value.created_at,
+        }
+    }
+}
+
+pub struct AuthorizationCodeSchema {
+    pub code_hash: String,
+    pub client_id: Uuid,
+    pub user_id: Uuid,
+    pub redirect_uri: String,
+    pub redirect_uri_sent: bool,
+    pub scopes: Vec<String>,
+    pub nonce: Option<String>,
+    pub code_challenge: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+    pub access_token_jti: Option<Uuid>,
+}
+
+impl From<AuthorizationCodeSchema> for AuthorizationCode {
+    fn from(value: AuthorizationCodeSchema) -> Self {
+        AuthorizationCode {
+            code_hash: value.code_hash,
+            client_id: value.client_id,
+            user_id: value.user_id,
+            redirect_uri: value.redirect_uri,
+            redirect_uri_sent: value.redirect_uri_sent,
+            scopes: value.scopes,
+            nonce: value.nonce,
+            code_challenge: value.code_challenge,
+            created_at: value.created_at,
+            expires_at: value.expires_at,
+            used_at: value.used_at,
+            access_token_jti: value.access_token_jti,
         }
     }
 }
@@ -0,0 +1,161 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{db::schema::SessionSchema, domain::session::Session};
+
+pub struct SessionRepository {
+    pool: Pool<Postgres>,
+}
+
+impl SessionRepository {
+    pub fn new(pool: Pool<Postgres>) -> SessionRepository {
+        SessionRepository { pool }
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<Option<Session>, sqlx::Error> {
+        let session = sqlx::query_as!(
+            SessionSchema,
+            "
+            SELECT *
+            FROM sessions
+            WHERE id = $1
+            ",
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|e| e.into());
+
+        Ok(session)
+    }
+
+    /// Sessions that are not revoked and were used after `seen_after`, most recently used first
+    pub async fn get_active_for_user(
+        &self,
+        user_id: Uuid,
+        seen_after: DateTime<Utc>,
+    ) -> Result<Vec<Session>, sqlx::Error> {
+        let sessions = sqlx::query_as!(
+            SessionSchema,
+            "
+            SELECT *
+            FROM sessions
+            WHERE user_id = $1 AND revoked_at IS NULL AND last_seen_at > $2
+            ORDER BY last_seen_at DESC
+            ",
+            user_id,
+            seen_after
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|e| e.into())
+        .collect();
+
+        Ok(sessions)
+    }
+
+    pub async fn insert(&self, session: Session) -> Result<Uuid, sqlx::Error> {
+        let id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO sessions (id, user_id, device_name, ip, created_at, last_seen_at, revoked_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id
+            "#,
+            session.id,
+            session.user_id,
+            session.device_name,
+            session.ip,
+            session.created_at,
+            session.last_seen_at,
+            session.revoked_at
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    /// Only writes when the session was last seen before `stale_before`, so that busy clients
+    /// do not update the row on every request.
+    pub async fn mark_seen(
+        &self,
+        id: Uuid,
+        seen_at: DateTime<Utc>,
+        stale_before: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE sessions SET last_seen_at = $1 WHERE id = $2 AND last_seen_at < $3",
+            seen_at,
+            id,
+            stale_before
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Returns `None` when the user has no such session or it is already revoked.
+    pub async fn revoke(
+        &self,
+        id: Uuid,
+        user_id: Uuid,
+        revoked_at: DateTime<Utc>,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        let id = sqlx::query_scalar!(
+            r#"
+            UPDATE sessions SET revoked_at = $1
+            WHERE id = $2 AND user_id = $3 AND revoked_at IS NULL
+            RETURNING id
+            "#,
+            revoked_at,
+            id,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    pub async fn revoke_all_for_user(
+        &self,
+        user_id: Uuid,
+        revoked_at: DateTime<Utc>,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE sessions SET revoked_at = $1
+            WHERE user_id = $2 AND revoked_at IS NULL
+            "#,
+            revoked_at,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    pub async fn revoke_all_for_user_except(
+        &self,
+        user_id: Uuid,
+        except_id: Uuid,
+        revoked_at: DateTime<Utc>,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE sessions SET revoked_at = $1
+            WHERE user_id = $2 AND id <> $3 AND revoked_at IS NULL
+            "#,
+            revoked_at,
+            user_id,
+            except_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,180 @@
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{db::schema::TotpCredentialSchema, domain::two_factor::TotpCredential};
+
+pub struct TwoFactorRepository {
+    pool: Pool<Postgres>,
+}
+
+impl TwoFactorRepository {
+    pub fn new(pool: Pool<Postgres>) -> TwoFactorRepository {
+        TwoFactorRepository { pool }
+    }
+
+    pub async fn get(&self, user_id: Uuid) -> Result<Option<TotpCredential>, sqlx::Error> {
+        let credential = sqlx::query_as!(
+            TotpCredentialSchema,
+            "
+            SELECT *
+            FROM totp_credentials
+            WHERE user_id = $1
+            ",
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|e| e.into());
+
+        Ok(credential)
+    }
+
+    /// Stores a new unconfirmed secret, replacing a previous unconfirmed one.
+    /// Returns `None` when the user already has a confirmed credential.
+    pub async fn upsert_unconfirmed(
+        &self,
+        credential: TotpCredential,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        let user_id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO totp_credentials (user_id, secret, created_at, confirmed_at, last_used_step)
+            VALUES ($1, $2, $3, NULL, NULL)
+            ON CONFLICT (user_id) DO UPDATE
+            SET secret = EXCLUDED.secret, created_at = EXCLUDED.created_at, last_used_step = NULL
+            WHERE totp_credentials.confirmed_at IS NULL
+            RETURNING user_id
+            "#,
+            credential.user_id,
+            credential.secret,
+            credential.created_at
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(user_id)
+    }
+
+    /// Marks the credential as confirmed and stores its first set of recovery codes.
+    pub async fn confirm(
+        &self,
+        user_id: Uuid,
+        confirmed_at: DateTime<Utc>,
+        code_hashes: &[String],
+    ) -> Result<(), sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        sqlx::query!(
+            "UPDATE totp_credentials SET confirmed_at = $1 WHERE user_id = $2",
+            confirmed_at,
+            user_id
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        replace_recovery_codes(&mut transaction, user_id, code_hashes).await?;
+
+        transaction.commit().await
+    }
+
+    /// Records the time step of an accepted code unless the same or a later step was used before.
+    /// Returns `None` when the code was already used.
+    pub async fn use_step(&self, user_id: Uuid, step: i64) -> Result<Option<Uuid>, sqlx::Error> {
+        let user_id = sqlx::query_scalar!(
+            r#"
+            UPDATE totp_credentials SET last_used_step = $1
+            WHERE user_id = $2 AND (last_used_step IS NULL OR last_used_step < $1)
+            RETURNING user_id
+            "#,
+            step,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(user_id)
+    }
+
+    /// Consumes an unused recovery code. Returns `None` when no such code exists.
+    pub async fn use_recovery_code(
+        &self,
+        user_id: Uuid,
+        code_hash: &str,
+        used_at: DateTime<Utc>,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        let id = sqlx::query_scalar!(
+            r#"
+            UPDATE recovery_codes SET used_at = $1
+            WHERE id = (
+                SELECT id FROM recovery_codes
+                WHERE user_id = $2 AND code_hash = $3 AND used_at IS NULL
+                LIMIT 1
+            ) AND used_at IS NULL
+            RETURNING id
+            "#,
+            used_at,
+            user_id,
+            code_hash
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    pub async fn replace_recovery_codes(
+        &self,
+        user_id: Uuid,
+        code_hashes: &[String],
+    ) -> Result<(), sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        replace_recovery_codes(&mut transaction, user_id, code_hashes).await?;
+
+        transaction.commit().await
+    }
+
+    /// Removes the credential together with its recovery codes.
+    /// Returns `None` when the user had no credential.
+    pub async fn delete(&self, user_id: Uuid) -> Result<Option<Uuid>, sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        sqlx::query!("DELETE FROM recovery_codes WHERE user_id = $1", user_id)
+            .execute(&mut *transaction)
+            .await?;
+
+        let deleted = sqlx::query_scalar!(
+            "DELETE FROM totp_credentials WHERE user_id = $1 RETURNING user_id",
+            user_id
+        )
+        .fetch_optional(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(deleted)
+    }
+}
+
+async fn replace_recovery_codes(
+    connection: &mut PgConnection,
+    user_id: Uuid,
+    code_hashes: &[String],
+) -> Result<(), sqlx::Error> {
+    sqlx::query!("DELETE FROM recovery_codes WHERE user_id = $1", user_id)
+        .execute(&mut *connection)
+        .await?;
+
+    for code_hash in code_hashes {
+        sqlx::query!(
+            "INSERT INTO recovery_codes (id, user_id, code_hash, used_at) VALUES ($1, $2, $3, NULL)",
+            Uuid::new_v4(),
+            user_id,
+            code_hash
+        )
+        .execute(&mut *connection)
+        .await?;
+    }
+
+    Ok(())
+}
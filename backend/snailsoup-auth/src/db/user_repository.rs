@@ -1,7 +1,16 @@
-use sqlx::{Pool, Postgres};
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres, QueryBuilder};
 use uuid::Uuid;
 
-use crate::{db::schema::AppUserSchema, domain::app_user::AppUser};
+use crate::{
+    db::schema::AppUserSchema,
+    domain::{app_user::AppUser, audit_event::AuditAction},
+    utils::user_query::{SortOrder, UserCursor, UserFilter, UserQuery, UserSort},
+};
+
+/// Audit actions whose detail is the username the account was given
+const USERNAME_DETAIL_ACTIONS: [AuditAction; 2] =
+    [AuditAction::UserCreate, AuditAction::UserRename];
 
 #[derive(Clone)]
 pub struct AppUserRepository {
@@ -10,7 +19,7 @@ pub struct AppUserRepository {
 
 impl AppUserRepository {
     pub fn new(pool: Pool<Postgres>) -> AppUserRepository {
-        AppUserRepository { pool: pool }
+        AppUserRepository { pool }
     }
 
     pub async fn get(&self, id: Uuid) -> Result<Option<AppUser>, sqlx::Error> {
@@ -34,14 +43,19 @@ impl AppUserRepository {
         let created_user = sqlx::query_as!(
             AppUserSchema,
             "
-        INSERT INTO app_users(id, username, password_hash, account_role) 
-        VALUES ($1, $2, $3, $4)
-        RETURNING id, username, password_hash, account_role
+        INSERT INTO app_users(id, username, password_hash, account_role, email, disabled_at, created_at, email_verified_at, pending_approval) 
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING id, username, password_hash, account_role, email, disabled_at, created_at, email_verified_at, pending_approval
         ",
             user.id,
             user.username,
             user.password_hash,
-            user.account_role
+            user.account_role.as_str(),
+            user.email,
+            user.disabled_at,
+            user.created_at,
+            user.email_verified_at,
+            user.pending_approval
         )
         .fetch_one(&self.pool)
         .await?
@@ -56,7 +70,7 @@ impl AppUserRepository {
             "
             SELECT *
             FROM app_users 
-            WHERE username = $1
+            WHERE lower(username) = lower($1)
             ",
             username
         )
@@ -67,20 +81,299 @@ impl AppUserRepository {
         Ok(user)
     }
 
-    pub async fn get_all(&self) -> Result<Vec<AppUser>, sqlx::Error> {
-        let users = sqlx::query_as!(
+    pub async fn get_by_email(&self, email: &str) -> Result<Option<AppUser>, sqlx::Error> {
+        let user = sqlx::query_as!(
             AppUserSchema,
             "
             SELECT *
-            FROM app_users
+            FROM app_users 
+            WHERE lower(email) = lower($1)
+            ",
+            email
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|e| e.into());
+
+        Ok(user)
+    }
+
+    /// Finds the user by username or, ignoring case, by email address.
+    /// A username takes precedence over another account's identical email address.
+    pub async fn get_by_login(&self, login: &str) -> Result<Option<AppUser>, sqlx::Error> {
+        let user = sqlx::query_as!(
+            AppUserSchema,
             "
+            SELECT *
+            FROM app_users
+            WHERE lower(username) = lower($1) OR lower(email) = lower($1)
+            ORDER BY lower(username) = lower($1) DESC
+            LIMIT 1
+            ",
+            login
         )
-        .fetch_all(&self.pool)
+        .fetch_optional(&self.pool)
         .await?
-        .into_iter()
-        .map(|e| e.into())
-        .collect();
+        .map(|e| e.into());
+
+        Ok(user)
+    }
+
+    /// Marks the address as verified, unless the account's email has changed since.
+    pub async fn set_email_verified(
+        &self,
+        id: Uuid,
+        email: &str,
+        verified_at: DateTime<Utc>,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        let id = sqlx::query_scalar!(
+            r#"
+            UPDATE app_users SET email_verified_at = $1
+            WHERE id = $2 AND email = $3
+            RETURNING id
+            "#,
+            verified_at,
+            id,
+            email
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    pub async fn get_page(&self, query: &UserQuery) -> Result<Vec<AppUser>, sqlx::Error> {
+        let mut builder = QueryBuilder::new("SELECT * FROM app_users WHERE TRUE");
+        push_filter(&mut builder, &query.filter);
+
+        let (comparison, direction) = match query.order {
+            SortOrder::Ascending => (" > ", " ASC"),
+            SortOrder::Descending => (" < ", " DESC"),
+        };
+
+        match &query.after {
+            Some(UserCursor::Username(username, id)) => {
+                builder
+                    .push(" AND (username, id)")
+                    .push(comparison)
+                    .push("(")
+                    .push_bind(username.clone())
+                    .push(", ")
+                    .push_bind(*id)
+                    .push(")");
+            }
+            Some(UserCursor::CreatedAt(created_at, id)) => {
+                builder
+                    .push(" AND (created_at, id)")
+                    .push(comparison)
+                    .push("(")
+                    .push_bind(*created_at)
+                    .push(", ")
+                    .push_bind(*id)
+                    .push(")");
+            }
+            None => {}
+        }
+
+        let column = match query.sort {
+            UserSort::Username => "username",
+            UserSort::CreatedAt => "created_at",
+        };
+
+        builder
+            .push(" ORDER BY ")
+            .push(column)
+            .push(direction)
+            .push(", id")
+            .push(direction)
+            .push(" LIMIT ")
+            .push_bind(query.limit);
+
+        let users = builder
+            .build_query_as::<AppUserSchema>()
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|e| e.into())
+            .collect();
 
         Ok(users)
     }
+
+    pub async fn count(&self, filter: &UserFilter) -> Result<i64, sqlx::Error> {
+        let mut builder = QueryBuilder::new("SELECT COUNT(*) FROM app_users WHERE TRUE");
+        push_filter(&mut builder, filter);
+
+        builder
+            .build_query_scalar::<i64>()
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    pub async fn update_password(
+        &self,
+        id: Uuid,
+        password_hash: &str,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        let id = sqlx::query_scalar!(
+            r#"
+            UPDATE app_users SET password_hash = $1 WHERE id = $2 RETURNING id
+            "#,
+            password_hash,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    pub async fn update_role(&self, id: Uuid, role: &str) -> Result<Option<Uuid>, sqlx::Error> {
+        let id = sqlx::query_scalar!(
+            r#"
+            UPDATE app_users SET account_role = $1 WHERE id = $2 RETURNING id
+            "#,
+            role,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    pub async fn update_username(
+        &self,
+        id: Uuid,
+        username: &str,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        let id = sqlx::query_scalar!(
+            r#"
+            UPDATE app_users SET username = $1 WHERE id = $2 RETURNING id
+            "#,
+            username,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    pub async fn approve(&self, id: Uuid) -> Result<Option<Uuid>, sqlx::Error> {
+        let id = sqlx::query_scalar!(
+            r#"
+            UPDATE app_users SET pending_approval = FALSE WHERE id = $1 RETURNING id
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    pub async fn set_disabled_at(
+        &self,
+        id: Uuid,
+        disabled_at: Option<DateTime<Utc>>,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        let id = sqlx::query_scalar!(
+            r#"
+            UPDATE app_users SET disabled_at = $1 WHERE id = $2 RETURNING id
+            "#,
+            disabled_at,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    /// Data owned by the user is removed through `ON DELETE CASCADE` foreign keys, failed
+    /// logins recorded for the account in the same transaction. Audit events keep the id but
+    /// lose the name, address and user agent of the account and usernames given in details.
+    pub async fn delete(&self, id: Uuid) -> Result<Option<Uuid>, sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        // Lets the append-only trigger of audit_events accept the updates below for this user
+        sqlx::query_scalar!(
+            "SELECT set_config('snailsoup.pseudonymize_user', $1, true)",
+            id.to_string()
+        )
+        .fetch_one(&mut *transaction)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE audit_events SET actor_name = NULL, ip = NULL, user_agent = NULL
+            WHERE actor_id = $1 OR (actor_id IS NULL AND lower(actor_name) IN (
+                SELECT lower(username) FROM app_users WHERE id = $1
+                UNION
+                SELECT lower(email) FROM app_users WHERE id = $1 AND email IS NOT NULL
+            ))
+            "#,
+            id
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE audit_events SET detail = NULL
+            WHERE target_id = $1 AND action = ANY($2) AND detail IS NOT NULL
+            "#,
+            id,
+            &USERNAME_DETAIL_ACTIONS.map(|action| action.as_str().to_owned())
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM login_failures WHERE kind = 'user' AND subject = $1
+            "#,
+            id.to_string()
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        let id = sqlx::query_scalar!(
+            r#"
+            DELETE FROM app_users WHERE id = $1 RETURNING id
+            "#,
+            id
+        )
+        .fetch_optional(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(id)
+    }
+}
+
+fn push_filter(builder: &mut QueryBuilder<'_, Postgres>, filter: &UserFilter) {
+    if let Some(role) = &filter.role {
+        builder
+            .push(" AND account_role = ")
+            .push_bind(role.as_str().to_owned());
+    }
+
+    if let Some(username) = &filter.username_contains {
+        let escaped = username
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        builder
+            .push(" AND username ILIKE ")
+            .push_bind(format!("%{}%", escaped));
+    }
+
+    if let Some(pending_approval) = filter.pending_approval {
+        builder
+            .push(" AND pending_approval = ")
+            .push_bind(pending_approval);
+    }
 }
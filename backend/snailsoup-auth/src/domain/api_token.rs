@@ -0,0 +1,39 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// Prefix telling API tokens apart from JWTs in the `Authorization` header
+pub const API_TOKEN_PREFIX: &str = "sst_";
+
+/// Scopes an API token can be limited to. Admin scopes are only effective
+/// when the owner's role grants the permission of the same name.
+pub const API_TOKEN_SCOPES: [&str; 11] = [
+    "profile:read",
+    "expenses:read",
+    "expenses:write",
+    "users:read",
+    "users:write",
+    "users:delete",
+    "clients:read",
+    "clients:write",
+    "invites:read",
+    "invites:write",
+    "audit:read",
+];
+
+#[derive(Clone)]
+pub struct ApiToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub token_hash: String,
+    pub scopes: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl ApiToken {
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope)
+    }
+}
@@ -1,3 +1,5 @@
+use chrono::{DateTime, Utc};
+use std::fmt;
 use uuid::Uuid;
 
 #[derive(Clone)]
@@ -5,5 +7,43 @@ pub struct AppUser {
     pub id: Uuid,
     pub username: String,
     pub password_hash: String,
-    pub account_role: String,
+    pub account_role: Role,
+    pub email: Option<String>,
+    /// Set once the user has confirmed `email`, cleared when it changes
+    pub email_verified_at: Option<DateTime<Utc>>,
+    /// Disabled accounts cannot log in and their tokens are rejected
+    pub disabled_at: Option<DateTime<Utc>>,
+    /// Registered while `REGISTRATION_MODE` is `approval` and not yet approved by an admin
+    pub pending_approval: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Name of an account role, one of the rows of the `roles` table. What a role may do is stored in
+/// `role_permissions`, so adding a role takes a migration but no code changes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Role(String);
+
+impl Role {
+    /// Wraps a role name known to exist in `roles`, e.g. because it was read from the database.
+    /// Names coming from requests are looked up with `PermissionService::find_role` instead.
+    pub fn new(name: impl Into<String>) -> Role {
+        Role(name.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Role of self-registered accounts
+impl Default for Role {
+    fn default() -> Self {
+        Role::new("User")
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
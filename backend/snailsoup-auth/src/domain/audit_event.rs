@@ -0,0 +1,88 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// Recorded authentication or administration event. Events are never changed or deleted.
+#[derive(Clone)]
+pub struct AuditEvent {
+    pub id: Uuid,
+    pub occurred_at: DateTime<Utc>,
+    /// Authenticated user, or the account a login or registration was for
+    pub actor_id: Option<Uuid>,
+    /// Username of the actor, or the name given at login and registration
+    pub actor_name: Option<String>,
+    pub action: String,
+    /// Account an admin action was applied to
+    pub target_id: Option<Uuid>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub outcome: String,
+    pub detail: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuditAction {
+    Login,
+    LoginMfa,
+    Register,
+    TokenRejected,
+    PasswordChange,
+    PasswordReset,
+    SessionRevoke,
+    AccountExport,
+    AccountDelete,
+    UserRead,
+    UserCreate,
+    UserRoleChange,
+    UserRename,
+    UserApprove,
+    UserDisable,
+    UserEnable,
+    UserDelete,
+    UserPasswordReset,
+    UserTotpRemove,
+    UserUnlock,
+    UserImpersonate,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Login => "auth.login",
+            AuditAction::LoginMfa => "auth.login_mfa",
+            AuditAction::Register => "auth.register",
+            AuditAction::TokenRejected => "auth.token_rejected",
+            AuditAction::PasswordChange => "auth.password_change",
+            AuditAction::PasswordReset => "auth.password_reset",
+            AuditAction::SessionRevoke => "auth.session_revoke",
+            AuditAction::AccountExport => "auth.account_export",
+            AuditAction::AccountDelete => "auth.account_delete",
+            AuditAction::UserRead => "admin.user_read",
+            AuditAction::UserCreate => "admin.user_create",
+            AuditAction::UserRoleChange => "admin.user_role_change",
+            AuditAction::UserRename => "admin.user_rename",
+            AuditAction::UserApprove => "admin.user_approve",
+            AuditAction::UserDisable => "admin.user_disable",
+            AuditAction::UserEnable => "admin.user_enable",
+            AuditAction::UserDelete => "admin.user_delete",
+            AuditAction::UserPasswordReset => "admin.user_password_reset",
+            AuditAction::UserTotpRemove => "admin.user_totp_remove",
+            AuditAction::UserUnlock => "admin.user_unlock",
+            AuditAction::UserImpersonate => "admin.user_impersonate",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuditOutcome {
+    Success,
+    Failure,
+}
+
+impl AuditOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditOutcome::Success => "success",
+            AuditOutcome::Failure => "failure",
+        }
+    }
+}
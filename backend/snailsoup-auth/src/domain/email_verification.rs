@@ -0,0 +1,15 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// One-time token proving that the user received mail at `email`.
+/// It stops working when the account's address changes in the meantime.
+#[derive(Clone)]
+pub struct EmailVerificationToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub email: String,
+    pub token_hash: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,54 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct ExpenseData {
+    pub user_id: Uuid,
+    pub category_id: Option<Uuid>,
+    pub description: String,
+    pub expense_date: NaiveDate,
+    pub cost: Decimal,
+}
+
+#[derive(Clone)]
+pub struct Expense {
+    pub id: Uuid,
+    pub data: ExpenseData,
+}
+
+#[derive(Clone)]
+pub struct FullExpenseData {
+    pub expense: ExpenseData,
+    pub tags_ids: Vec<Uuid>,
+}
+
+#[derive(Clone)]
+pub struct FullExpense {
+    pub id: Uuid,
+    pub data: FullExpenseData,
+}
+
+#[derive(Clone)]
+pub struct TagData {
+    pub user_id: Uuid,
+    pub name: String,
+}
+
+#[derive(Clone)]
+pub struct Tag {
+    pub id: Uuid,
+    pub data: TagData,
+}
+
+#[derive(Clone)]
+pub struct CategoryData {
+    pub user_id: Uuid,
+    pub name: String,
+}
+
+#[derive(Clone)]
+pub struct Category {
+    pub id: Uuid,
+    pub data: CategoryData,
+}
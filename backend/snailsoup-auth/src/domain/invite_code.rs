@@ -0,0 +1,15 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// Code allowing `max_uses` registrations while `REGISTRATION_MODE` is `invite`.
+#[derive(Clone)]
+pub struct InviteCode {
+    pub id: Uuid,
+    pub code_hash: String,
+    pub max_uses: i32,
+    pub uses: i32,
+    /// Admin who created the code, cleared when that account is deleted
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,12 @@
+use chrono::{DateTime, Utc};
+
+/// Failed login attempts counted for one username or one client address.
+#[derive(Clone)]
+pub struct LoginFailure {
+    /// `username` or `ip`
+    pub kind: String,
+    pub subject: String,
+    pub failed_attempts: i32,
+    pub last_failed_at: DateTime<Utc>,
+    pub locked_until: Option<DateTime<Utc>>,
+}
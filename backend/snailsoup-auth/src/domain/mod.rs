@@ -1 +1,12 @@
+pub mod api_token;
 pub mod app_user;
+pub mod audit_event;
+pub mod email_verification;
+pub mod expense;
+pub mod invite_code;
+pub mod login_failure;
+pub mod oauth;
+pub mod password_reset;
+pub mod refresh_token;
+pub mod session;
+pub mod two_factor;
@@ -0,0 +1,50 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+pub const GRANT_AUTHORIZATION_CODE: &str = "authorization_code";
+pub const GRANT_CLIENT_CREDENTIALS: &str = "client_credentials";
+
+/// Scope requesting an ID token, see OpenID Connect Core 1.0
+pub const OPENID_SCOPE: &str = "openid";
+
+#[derive(Clone)]
+pub struct OAuthClient {
+    pub id: Uuid,
+    pub name: String,
+    pub secret_hash: Option<String>,
+    pub redirect_uris: Vec<String>,
+    pub grant_types: Vec<String>,
+    /// Scopes the client may request
+    pub scopes: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl OAuthClient {
+    pub fn is_confidential(&self) -> bool {
+        self.secret_hash.is_some()
+    }
+
+    pub fn allows_grant(&self, grant_type: &str) -> bool {
+        self.grant_types.iter().any(|g| g == grant_type)
+    }
+}
+
+#[derive(Clone)]
+pub struct AuthorizationCode {
+    pub code_hash: String,
+    pub client_id: Uuid,
+    pub user_id: Uuid,
+    pub redirect_uri: String,
+    /// Whether `redirect_uri` was part of the authorization request rather than the client's
+    /// only registered URI, the token request must then repeat it
+    pub redirect_uri_sent: bool,
+    pub scopes: Vec<String>,
+    pub nonce: Option<String>,
+    /// S256 PKCE challenge the code verifier is checked against
+    pub code_challenge: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+    /// Access token issued for the code, revoked if the code is presented again
+    pub access_token_jti: Option<Uuid>,
+}
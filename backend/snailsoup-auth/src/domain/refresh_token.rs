@@ -0,0 +1,15 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct RefreshToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// Every token obtained by rotating a login's refresh token shares its family id
+    pub family_id: Uuid,
+    pub token_hash: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,17 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// A login on one device. The refresh tokens of the login share its id as family id and its
+/// access tokens carry it in the `sid` claim.
+#[derive(Clone)]
+pub struct Session {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// Browser and operating system taken from the User-Agent header
+    pub device_name: String,
+    /// Client address at login
+    pub ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,14 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct TotpCredential {
+    pub user_id: Uuid,
+    /// Base32 encoded shared secret
+    pub secret: String,
+    pub created_at: DateTime<Utc>,
+    /// Set once the user proved the authenticator app works; unconfirmed credentials are ignored at login
+    pub confirmed_at: Option<DateTime<Utc>>,
+    /// Last accepted time step, codes from it or earlier steps are rejected
+    pub last_used_step: Option<i64>,
+}
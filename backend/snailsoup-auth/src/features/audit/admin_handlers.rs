@@ -0,0 +1,61 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use std::sync::Arc;
+
+use crate::{
+    features::response::HttpError,
+    services::audit::{AuditError, AuditService},
+    utils::audit_query::AuditQuery,
+};
+
+const DEFAULT_LIMIT: i64 = 100;
+const MAX_LIMIT: i64 = 1000;
+
+use super::api::{AuditEventQuery, AuditEventResponse};
+
+#[utoipa::path(
+    get,
+    path = "/api/admin/audit-events",
+    tag = "Audit - Admin",
+    responses(
+        (status = StatusCode::OK, description = "list audit events successfully, newest first", body = [AuditEventResponse]),
+        (status = StatusCode::BAD_REQUEST, description = "`from` is not before `to`")
+    ),
+    params(AuditEventQuery),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn audit_events(
+    Query(query): Query<AuditEventQuery>,
+    State(service): State<Arc<AuditService>>,
+) -> Result<impl IntoResponse, HttpError> {
+    if let (Some(from), Some(to)) = (query.from, query.to) {
+        if from >= to {
+            return Err(HttpError::from("from must be before to"));
+        }
+    }
+
+    service
+        .search(AuditQuery {
+            user_id: query.user_id,
+            action: query.action,
+            from: query.from,
+            to: query.to,
+            limit: query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT),
+        })
+        .await
+        .map_err(|e| match e {
+            AuditError::InternalError => HttpError::from(StatusCode::INTERNAL_SERVER_ERROR),
+        })
+        .map(|events| {
+            Json(
+                events
+                    .into_iter()
+                    .map(AuditEventResponse::from_event)
+                    .collect::<Vec<_>>(),
+            )
+        })
+}
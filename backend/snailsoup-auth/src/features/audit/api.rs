@@ -0,0 +1,82 @@
+use axum::{middleware::from_fn_with_state, routing::get, Router};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState, domain::audit_event::AuditEvent,
+    features::auth::middleware::require_permission,
+};
+
+use super::admin_handlers::audit_events;
+
+pub fn get_admin_routes(app_state: AppState) -> Router {
+    Router::new()
+        .route("/api/admin/audit-events", get(audit_events))
+        .route_layer(from_fn_with_state(
+            app_state.clone(),
+            require_permission("audit:read"),
+        ))
+        .with_state(app_state)
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct AuditEventQuery {
+    /// Only events where this user is the actor or the target
+    pub user_id: Option<Uuid>,
+    /// Only events with this action, e.g. `auth.login`
+    pub action: Option<String>,
+    /// Only events at or after this time
+    pub from: Option<DateTime<Utc>>,
+    /// Only events before this time
+    pub to: Option<DateTime<Utc>>,
+    /// Number of events, 100 by default and at most 1000
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AuditEventResponse {
+    #[schema()]
+    pub id: Uuid,
+    #[schema()]
+    pub occurred_at: DateTime<Utc>,
+    /// Authenticated user, or the account a login or registration was for
+    #[schema()]
+    pub actor_id: Option<Uuid>,
+    /// Username of the actor, or the name given at login and registration
+    #[schema()]
+    pub actor_name: Option<String>,
+    #[schema(example = "auth.login")]
+    pub action: String,
+    /// Account an admin action was applied to
+    #[schema()]
+    pub target_id: Option<Uuid>,
+    #[schema()]
+    pub ip: Option<String>,
+    #[schema()]
+    pub user_agent: Option<String>,
+    /// `success` or `failure`
+    #[schema(example = "failure")]
+    pub outcome: String,
+    /// Reason of a failure or the new value of a change
+    #[schema(example = "incorrect_password")]
+    pub detail: Option<String>,
+}
+
+impl AuditEventResponse {
+    pub fn from_event(event: AuditEvent) -> AuditEventResponse {
+        AuditEventResponse {
+            id: event.id,
+            occurred_at: event.occurred_at,
+            actor_id: event.actor_id,
+            actor_name: event.actor_name,
+            action: event.action,
+            target_id: event.target_id,
+            ip: event.ip,
+            user_agent: event.user_agent,
+            outcome: event.outcome,
+            detail: event.detail,
+        }
+    }
+}
@@ -1,30 +1,199 @@
-use axum::{routing::post, Router};
-use serde::Deserialize;
-use utoipa::ToSchema;
+use axum::{
+    middleware::from_fn,
+    routing::{get, post},
+    Router,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
 
-use crate::app_state::AppState;
+use crate::{
+    app_state::AppState,
+    config::Config,
+    features::auth::middleware::{require_access_token, require_session},
+    services::auth::AuthTokens,
+};
 
-use super::handlers::{login, register};
+use super::handlers::{
+    confirm_password_reset, forgot_password, jwks, login, login_mfa, logout, logout_everywhere,
+    refresh, register, resend_verification, verify, verify_email,
+};
 
 pub fn get_public_routes(app_state: AppState) -> Router {
     Router::new()
         .route("/api/auth/login", post(login))
+        .route("/api/auth/login/mfa", post(login_mfa))
         .route("/api/auth/register", post(register))
+        .route("/api/auth/refresh", post(refresh))
+        .route("/api/auth/forgot-password", post(forgot_password))
+        .route("/api/auth/reset-password", post(confirm_password_reset))
+        .route("/api/auth/verify-email", post(verify_email))
+        .route("/api/auth/resend-verification", post(resend_verification))
+        .route("/.well-known/jwks.json", get(jwks))
+        .route("/api/auth/verify", get(verify))
         .with_state(app_state)
 }
 
+pub fn get_private_routes(app_state: AppState) -> Router {
+    // Impersonation tokens may log out, which only revokes the token itself
+    Router::new()
+        .route("/api/auth/logout-everywhere", post(logout_everywhere))
+        .route_layer(from_fn(require_session))
+        .route("/api/auth/logout", post(logout))
+        .route_layer(from_fn(require_access_token))
+        .with_state(app_state)
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct VerifyQuery {
+    /// Only let users through whose role has every permission of this role, e.g. `User` also
+    /// lets `Admin` through
+    pub role: Option<String>,
+    /// Only let users through that hold this permission, e.g. `expenses:read`. API tokens need
+    /// it as scope as well
+    pub permission: Option<String>,
+}
+
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct LoginRequest {
+    /// Username or email address
     #[schema()]
     pub username: String,
     #[schema()]
     pub password: String,
 }
 
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct MfaLoginRequest {
+    /// Token returned by the password step
+    #[schema()]
+    pub mfa_token: String,
+    /// Code from the authenticator app or an unused recovery code
+    #[schema()]
+    pub code: String,
+}
+
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct RegisterRequest {
     #[schema()]
     pub username: String,
     #[schema()]
     pub password: String,
+    /// Address used to recover the account
+    #[schema()]
+    pub email: Option<String>,
+    /// Required when REGISTRATION_MODE is `invite`
+    #[schema()]
+    pub invite_code: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RefreshRequest {
+    /// May be omitted in session cookie mode, the refresh token cookie is used then
+    #[schema()]
+    pub refresh_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ForgotPasswordRequest {
+    /// Username or email address
+    #[schema()]
+    pub username: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct VerifyEmailRequest {
+    /// Token received in the verification email
+    #[schema()]
+    pub token: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ResendVerificationRequest {
+    /// Username or email address
+    #[schema()]
+    pub username: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ConfirmPasswordResetRequest {
+    /// Token received in the password reset email
+    #[schema()]
+    pub token: String,
+    #[schema()]
+    pub new_password: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct LogoutRequest {
+    /// Refresh token issued together with the access token, revoked along with it
+    #[schema()]
+    pub refresh_token: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LoginResponse {
+    #[schema()]
+    pub access_token: String,
+    #[schema()]
+    pub refresh_token: String,
+}
+
+impl LoginResponse {
+    pub fn from_tokens(tokens: AuthTokens) -> LoginResponse {
+        LoginResponse {
+            access_token: tokens.access_token,
+            refresh_token: tokens.refresh_token,
+        }
+    }
+}
+
+/// Body of login, MFA login and refresh with `SESSION_COOKIES`, where the tokens are only
+/// set as HttpOnly cookies
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SessionResponse {
+    #[schema()]
+    pub user_id: Uuid,
+    #[schema()]
+    pub access_token_expires_at: DateTime<Utc>,
+    #[schema()]
+    pub refresh_token_expires_at: DateTime<Utc>,
+}
+
+impl SessionResponse {
+    pub fn from_tokens(tokens: &AuthTokens, config: &Config) -> SessionResponse {
+        let now = Utc::now();
+        SessionResponse {
+            user_id: tokens.user_id,
+            access_token_expires_at: now + Duration::minutes(config.jwt_maxage.into()),
+            refresh_token_expires_at: now + Duration::minutes(config.refresh_token_maxage.into()),
+        }
+    }
+}
+
+/// Tokens in the body, or only the session details when `SESSION_COOKIES` is enabled
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(untagged)]
+pub enum AuthResponse {
+    Tokens(LoginResponse),
+    Session(SessionResponse),
+}
+
+impl AuthResponse {
+    pub fn from_tokens(tokens: AuthTokens, config: &Config) -> AuthResponse {
+        if config.session_cookies {
+            AuthResponse::Session(SessionResponse::from_tokens(&tokens, config))
+        } else {
+            AuthResponse::Tokens(LoginResponse::from_tokens(tokens))
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MfaRequiredResponse {
+    /// Short-lived token to send to `/api/auth/login/mfa` together with a code
+    #[schema()]
+    pub mfa_token: String,
 }
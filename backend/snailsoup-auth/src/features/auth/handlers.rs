@@ -1,12 +1,46 @@
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use axum::{
+    extract::{Query, Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode},
+    response::IntoResponse,
+    Extension, Json,
+};
+use axum_extra::extract::CookieJar;
 use std::sync::Arc;
 
 use crate::{
-    features::response::HttpError,
-    services::auth::{AuthService, LoginError, RegisterError},
+    config::Config,
+    domain::api_token::API_TOKEN_SCOPES,
+    domain::app_user::{AppUser, Role},
+    domain::audit_event::{AuditAction, AuditOutcome},
+    features::{
+        auth::{
+            middleware::{process_token, Credentials, ErrorResponse},
+            session_cookie::{
+                clear_session_cookies, csrf_token_valid, refresh_token_cookie, set_session_cookies,
+            },
+        },
+        client_ip::ClientIp,
+        response::{HttpError, ValidationErrorResponse},
+    },
+    services::{
+        api_token::ApiTokenService,
+        audit::{AuditRecord, AuditService, RequestOrigin},
+        auth::{
+            AuthService, AuthTokens, LoginError, LoginOutcome, LogoutError, MfaLoginError,
+            RefreshError, RegisterError, TokenClaims,
+        },
+        email_verification::{EmailVerificationService, VerificationError},
+        password_reset::{PasswordResetService, ResetError},
+        permission::{PermissionError, PermissionService},
+        registration::RegistrationService,
+    },
 };
 
-use super::api::{LoginRequest, RegisterRequest};
+use super::api::{
+    AuthResponse, ConfirmPasswordResetRequest, ForgotPasswordRequest, LoginRequest, LogoutRequest,
+    MfaLoginRequest, MfaRequiredResponse, RefreshRequest, RegisterRequest,
+    ResendVerificationRequest, VerifyEmailRequest, VerifyQuery,
+};
 
 #[utoipa::path(
     post,
@@ -14,25 +48,224 @@ use super::api::{LoginRequest, RegisterRequest};
     tag = "Auth",
     request_body = LoginRequest,
     responses(
-        (status = OK, body=Uuid),
+        (status = OK, description = "Access and refresh token, or with SESSION_COOKIES only the user id and expiry times while the tokens are set as HttpOnly cookies", body=AuthResponse),
+        (status = ACCEPTED, description = "Password is correct, a second factor is required", body=MfaRequiredResponse),
         (status = UNAUTHORIZED, description = "User with provided username and password does not exist"),
+        (status = FORBIDDEN, description = "Account is disabled or awaiting approval, or its email address is not verified while REQUIRE_EMAIL_VERIFICATION is set"),
+        (status = TOO_MANY_REQUESTS, description = "Too many failed attempts, retry after the time in the Retry-After header"),
+        (status = LOCKED, description = "Account is temporarily locked after too many failed attempts"),
     )
 )]
 pub(super) async fn login(
+    ClientIp(ip): ClientIp,
+    origin: RequestOrigin,
+    jar: CookieJar,
     State(service): State<Arc<AuthService>>,
+    State(audit_service): State<Arc<AuditService>>,
+    State(config): State<Config>,
     Json(body): Json<LoginRequest>,
 ) -> Result<impl IntoResponse, HttpError> {
-    service
-        .login(body.username.as_str(), body.password.as_str())
-        .await
+    let result = service
+        .login(
+            body.username.as_str(),
+            body.password.as_str(),
+            ip,
+            origin.user_agent.as_deref(),
+        )
+        .await;
+
+    let record = match &result {
+        Ok(LoginOutcome::Authenticated(tokens)) => {
+            AuditRecord::new(AuditAction::Login, AuditOutcome::Success).actor_id(tokens.user_id)
+        }
+        Ok(LoginOutcome::MfaRequired { user_id, .. }) => {
+            AuditRecord::new(AuditAction::Login, AuditOutcome::Success)
+                .actor_id(*user_id)
+                .detail("mfa_required")
+        }
+        Err(e) => AuditRecord::new(AuditAction::Login, AuditOutcome::Failure)
+            .detail(login_failure_reason(e)),
+    };
+    audit_service
+        .record(&origin, record.actor_name(&body.username))
+        .await;
+
+    result
         .map_err(|e| match e {
             LoginError::IncorrectUser => HttpError::from(StatusCode::UNAUTHORIZED),
             LoginError::IncorrectPassword => HttpError::from(StatusCode::UNAUTHORIZED),
+            LoginError::TooManyAttempts(retry_after) => {
+                HttpError::from((StatusCode::TOO_MANY_REQUESTS, "Too many login attempts"))
+                    .retry_after(retry_after)
+            }
+            LoginError::AccountLocked(retry_after) => {
+                HttpError::from((StatusCode::LOCKED, "Account is temporarily locked"))
+                    .retry_after(retry_after)
+            }
+            LoginError::AccountDisabled => {
+                HttpError::from((StatusCode::FORBIDDEN, "Account is disabled"))
+            }
+            LoginError::EmailNotVerified => {
+                HttpError::from((StatusCode::FORBIDDEN, "Email address is not verified"))
+            }
+            LoginError::PendingApproval => {
+                HttpError::from((StatusCode::FORBIDDEN, "Account is awaiting approval"))
+            }
             LoginError::InternalError => HttpError::from(StatusCode::INTERNAL_SERVER_ERROR),
             LoginError::UnexpectedError => HttpError::from(StatusCode::INTERNAL_SERVER_ERROR),
             LoginError::InternalPasswordError => HttpError::from(StatusCode::INTERNAL_SERVER_ERROR),
         })
-        .map(|token| Json(token))
+        .map(|outcome| match outcome {
+            LoginOutcome::Authenticated(tokens) => {
+                (StatusCode::OK, token_response(jar, &config, tokens)).into_response()
+            }
+            LoginOutcome::MfaRequired { mfa_token, .. } => (
+                StatusCode::ACCEPTED,
+                Json(MfaRequiredResponse { mfa_token }),
+            )
+                .into_response(),
+        })
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/auth/login/mfa",
+    tag = "Auth",
+    request_body = MfaLoginRequest,
+    responses(
+        (status = OK, description = "Access and refresh token, or with SESSION_COOKIES only the user id and expiry times while the tokens are set as HttpOnly cookies", body=AuthResponse),
+        (status = UNAUTHORIZED, description = "Token from the password step is invalid or expired"),
+        (status = FORBIDDEN, description = "Code is invalid or was already used, or the account is disabled"),
+        (status = TOO_MANY_REQUESTS, description = "Too many failed attempts, retry after the time in the Retry-After header"),
+        (status = LOCKED, description = "Account is temporarily locked after too many failed attempts"),
+    )
+)]
+pub(super) async fn login_mfa(
+    ClientIp(ip): ClientIp,
+    origin: RequestOrigin,
+    jar: CookieJar,
+    State(service): State<Arc<AuthService>>,
+    State(audit_service): State<Arc<AuditService>>,
+    State(config): State<Config>,
+    Json(body): Json<MfaLoginRequest>,
+) -> Result<impl IntoResponse, HttpError> {
+    let result = service
+        .login_mfa(
+            body.mfa_token.as_str(),
+            body.code.as_str(),
+            ip,
+            origin.user_agent.as_deref(),
+        )
+        .await;
+
+    let record = match &result {
+        Ok(tokens) => {
+            AuditRecord::new(AuditAction::LoginMfa, AuditOutcome::Success).actor_id(tokens.user_id)
+        }
+        Err(e) => AuditRecord::new(AuditAction::LoginMfa, AuditOutcome::Failure)
+            .detail(mfa_failure_reason(e)),
+    };
+    audit_service.record(&origin, record).await;
+
+    result
+        .map_err(|e| match e {
+            MfaLoginError::InvalidToken => {
+                HttpError::from((StatusCode::UNAUTHORIZED, "Invalid mfa token"))
+            }
+            MfaLoginError::ExpiredToken => {
+                HttpError::from((StatusCode::UNAUTHORIZED, "Expired mfa token"))
+            }
+            MfaLoginError::InvalidCode => HttpError::from((StatusCode::FORBIDDEN, "Invalid code")),
+            MfaLoginError::TooManyAttempts(retry_after) => {
+                HttpError::from((StatusCode::TOO_MANY_REQUESTS, "Too many login attempts"))
+                    .retry_after(retry_after)
+            }
+            MfaLoginError::AccountLocked(retry_after) => {
+                HttpError::from((StatusCode::LOCKED, "Account is temporarily locked"))
+                    .retry_after(retry_after)
+            }
+            MfaLoginError::UserDoesNotExist => {
+                HttpError::from((StatusCode::UNAUTHORIZED, "User does not exist"))
+            }
+            MfaLoginError::AccountDisabled => {
+                HttpError::from((StatusCode::FORBIDDEN, "Account is disabled"))
+            }
+            MfaLoginError::InternalError => HttpError::from(StatusCode::INTERNAL_SERVER_ERROR),
+        })
+        .map(|tokens| token_response(jar, &config, tokens))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/auth/refresh",
+    tag = "Auth",
+    request_body = RefreshRequest,
+    responses(
+        (status = OK, description = "Access and refresh token, or with SESSION_COOKIES only the user id and expiry times while the tokens are set as HttpOnly cookies", body=AuthResponse),
+        (status = UNAUTHORIZED, description = "Refresh token is invalid, expired or was already used"),
+        (status = FORBIDDEN, description = "Account is disabled"),
+    )
+)]
+pub(super) async fn refresh(
+    method: Method,
+    headers: HeaderMap,
+    jar: CookieJar,
+    State(service): State<Arc<AuthService>>,
+    State(config): State<Config>,
+    Json(body): Json<RefreshRequest>,
+) -> Result<impl IntoResponse, HttpError> {
+    let refresh_token = match (body.refresh_token, refresh_token_cookie(&jar, &config)) {
+        (Some(refresh_token), _) => refresh_token,
+        (None, Some(_)) if !csrf_token_valid(&method, &headers) => {
+            return Err(HttpError::from((
+                StatusCode::FORBIDDEN,
+                "Invalid CSRF token",
+            )))
+        }
+        (None, Some(refresh_token)) => refresh_token,
+        (None, None) => {
+            return Err(HttpError::from((
+                StatusCode::UNAUTHORIZED,
+                "Missing refresh token",
+            )))
+        }
+    };
+
+    service
+        .refresh(refresh_token.as_str())
+        .await
+        .map_err(|e| match e {
+            RefreshError::InvalidToken => {
+                HttpError::from((StatusCode::UNAUTHORIZED, "Invalid refresh token"))
+            }
+            RefreshError::ExpiredToken => {
+                HttpError::from((StatusCode::UNAUTHORIZED, "Expired refresh token"))
+            }
+            RefreshError::ReusedToken => {
+                HttpError::from((StatusCode::UNAUTHORIZED, "Refresh token was already used"))
+            }
+            RefreshError::UserDoesNotExist => {
+                HttpError::from((StatusCode::UNAUTHORIZED, "User does not exist"))
+            }
+            RefreshError::AccountDisabled => {
+                HttpError::from((StatusCode::FORBIDDEN, "Account is disabled"))
+            }
+            RefreshError::InternalError => HttpError::from(StatusCode::INTERNAL_SERVER_ERROR),
+        })
+        .map(|tokens| token_response(jar, &config, tokens))
+}
+
+/// With `SESSION_COOKIES` the tokens only travel in HttpOnly cookies and stay out of the body,
+/// so that scripts on the page cannot read them.
+fn token_response(
+    jar: CookieJar,
+    config: &Config,
+    tokens: AuthTokens,
+) -> (CookieJar, Json<AuthResponse>) {
+    (
+        set_session_cookies(jar, config, &tokens),
+        Json(AuthResponse::from_tokens(tokens, config)),
+    )
 }
 
 #[utoipa::path(
@@ -42,21 +275,461 @@ pub(super) async fn login(
     request_body = RegisterRequest,
     responses(
         (status = CREATED, body=Uuid),
-        (status = BAD_REQUEST)
+        (status = ACCEPTED, description = "Account created but awaiting approval when REGISTRATION_MODE is `approval`; also returned without a body instead of CREATED and BAD_REQUEST when CONCEAL_REGISTRATION_CONFLICTS is set"),
+        (status = BAD_REQUEST, description = "Username or email already used, email or invite code missing, or invite code invalid, expired or used up; a ValidationErrorResponse when the username or password does not meet the policy", body = ValidationErrorResponse)
     )
 )]
 pub(super) async fn register(
-    State(service): State<Arc<AuthService>>,
+    origin: RequestOrigin,
+    State(service): State<Arc<RegistrationService>>,
+    State(verification_service): State<Arc<EmailVerificationService>>,
+    State(audit_service): State<Arc<AuditService>>,
+    State(config): State<Config>,
     Json(body): Json<RegisterRequest>,
 ) -> Result<impl IntoResponse, HttpError> {
+    let registered = service
+        .register(
+            body.username.as_str(),
+            body.password.as_str(),
+            body.email.as_deref(),
+            body.invite_code.as_deref(),
+        )
+        .await;
+
+    let record = match &registered {
+        Ok(user) => AuditRecord::new(AuditAction::Register, AuditOutcome::Success).actor(user),
+        Err(e) => AuditRecord::new(AuditAction::Register, AuditOutcome::Failure)
+            .actor_name(&body.username)
+            .detail(register_failure_reason(e)),
+    };
+    audit_service.record(&origin, record).await;
+
+    // Mails go out off the request path so the response time does not give away whether an
+    // account was created
+    match &registered {
+        Ok(user) => {
+            let user = user.clone();
+            tokio::spawn(async move {
+                if verification_service.send_verification(&user).await.is_err() {
+                    println!("Cannot create verification token for {}", user.username);
+                }
+            });
+        }
+        Err(e @ RegisterError::UsernameInUse) | Err(e @ RegisterError::EmailInUse)
+            if config.conceal_registration_conflicts =>
+        {
+            let username = body.username.clone();
+            let email = match e {
+                RegisterError::EmailInUse => body.email.clone(),
+                _ => None,
+            };
+            tokio::spawn(async move {
+                if verification_service
+                    .notify_registration_attempt(&username, email.as_deref())
+                    .await
+                    .is_err()
+                {
+                    println!("Cannot notify {} of a registration attempt", username);
+                }
+            });
+        }
+        Err(_) => {}
+    }
+
+    if config.conceal_registration_conflicts {
+        return match registered {
+            Ok(_) | Err(RegisterError::UsernameInUse) | Err(RegisterError::EmailInUse) => {
+                Ok(StatusCode::ACCEPTED.into_response())
+            }
+            Err(e) => Err(map_register_error(e)),
+        };
+    }
+
+    registered.map_err(map_register_error).map(|user| {
+        let status = if user.pending_approval {
+            StatusCode::ACCEPTED
+        } else {
+            StatusCode::CREATED
+        };
+        (status, Json(user.id)).into_response()
+    })
+}
+
+fn login_failure_reason(e: &LoginError) -> &'static str {
+    match e {
+        LoginError::IncorrectUser => "incorrect_user",
+        LoginError::IncorrectPassword => "incorrect_password",
+        LoginError::TooManyAttempts(_) => "too_many_attempts",
+        LoginError::AccountLocked(_) => "account_locked",
+        LoginError::AccountDisabled => "account_disabled",
+        LoginError::EmailNotVerified => "email_not_verified",
+        LoginError::PendingApproval => "pending_approval",
+        LoginError::InternalError
+        | LoginError::UnexpectedError
+        | LoginError::InternalPasswordError => "internal_error",
+    }
+}
+
+fn mfa_failure_reason(e: &MfaLoginError) -> &'static str {
+    match e {
+        MfaLoginError::InvalidToken => "invalid_token",
+        MfaLoginError::ExpiredToken => "expired_token",
+        MfaLoginError::InvalidCode => "invalid_code",
+        MfaLoginError::TooManyAttempts(_) => "too_many_attempts",
+        MfaLoginError::AccountLocked(_) => "account_locked",
+        MfaLoginError::UserDoesNotExist => "user_does_not_exist",
+        MfaLoginError::AccountDisabled => "account_disabled",
+        MfaLoginError::InternalError => "internal_error",
+    }
+}
+
+fn register_failure_reason(e: &RegisterError) -> &'static str {
+    match e {
+        RegisterError::UsernameInUse => "username_in_use",
+        RegisterError::EmailInUse => "email_in_use",
+        RegisterError::EmailRequired => "email_required",
+        RegisterError::PolicyViolation(_) => "policy_violation",
+        RegisterError::InviteCodeRequired => "invite_code_required",
+        RegisterError::InvalidInviteCode => "invalid_invite_code",
+        RegisterError::InternalError => "internal_error",
+    }
+}
+
+fn map_register_error(e: RegisterError) -> HttpError {
+    match e {
+        RegisterError::UsernameInUse => {
+            HttpError::from((StatusCode::BAD_REQUEST, "Username is already used"))
+        }
+        RegisterError::EmailInUse => {
+            HttpError::from((StatusCode::BAD_REQUEST, "Email is already used"))
+        }
+        RegisterError::EmailRequired => HttpError::from("Email is required"),
+        RegisterError::PolicyViolation(violations) => HttpError::from(violations),
+        RegisterError::InviteCodeRequired => HttpError::from("Invite code is required"),
+        RegisterError::InvalidInviteCode => HttpError::from("Invalid invite code"),
+        RegisterError::InternalError => HttpError::from(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/.well-known/jwks.json",
+    tag = "Auth",
+    responses(
+        (status = OK, description = "Public keys used to sign access tokens (RFC 7517 JWK Set)", body = Object),
+    )
+)]
+pub(super) async fn jwks(State(service): State<Arc<AuthService>>) -> impl IntoResponse {
+    Json(service.jwks().clone())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/auth/verify",
+    tag = "Auth",
+    params(VerifyQuery),
+    responses(
+        (status = OK, description = "Token is valid, the user is described by the X-User-Id, X-User-Name and X-User-Role headers, impersonation tokens add X-Impersonator-Id"),
+        (status = BAD_REQUEST, description = "Unknown role or permission"),
+        (status = UNAUTHORIZED, description = "Missing, invalid, expired or revoked token"),
+        (status = FORBIDDEN, description = "Account is disabled, its role does not include the required role or it lacks the required permission"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn verify(
+    origin: RequestOrigin,
+    State(auth_service): State<Arc<AuthService>>,
+    State(api_token_service): State<Arc<ApiTokenService>>,
+    State(audit_service): State<Arc<AuditService>>,
+    State(permission_service): State<Arc<PermissionService>>,
+    State(config): State<Config>,
+    req: Request,
+) -> Result<impl IntoResponse, (StatusCode, Json<ErrorResponse>)> {
+    let error = |status: StatusCode, message: String| (status, Json(ErrorResponse { message }));
+    let internal_error = |_| {
+        error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Internal error".to_string(),
+        )
+    };
+
+    let Query(query) = Query::<VerifyQuery>::try_from_uri(req.uri())
+        .map_err(|e| error(StatusCode::BAD_REQUEST, e.body_text()))?;
+
+    if let Some(permission) = &query.permission {
+        if !API_TOKEN_SCOPES.contains(&permission.as_str()) {
+            let message = format!("Unknown permission {}", permission);
+            return Err(error(StatusCode::BAD_REQUEST, message));
+        }
+    }
+
+    let required_role = match &query.role {
+        Some(name) => Some(
+            permission_service
+                .find_role(name)
+                .await
+                .map_err(internal_error)?
+                .ok_or_else(|| error(StatusCode::BAD_REQUEST, format!("Unknown role {}", name)))?,
+        ),
+        None => None,
+    };
+
+    let (user, credentials) = process_token(
+        req.headers().clone(),
+        config
+            .session_cookies
+            .then_some(config.access_token_cookie.as_str()),
+        auth_service,
+        api_token_service,
+        audit_service,
+        &origin,
+    )
+    .await?;
+
+    let allowed = verify_allows(
+        &permission_service,
+        &user,
+        &credentials,
+        required_role.as_ref(),
+        query.permission.as_deref(),
+    )
+    .await
+    .map_err(internal_error)?;
+    if !allowed {
+        return Err(error(
+            StatusCode::FORBIDDEN,
+            "Insufficient privileges".to_string(),
+        ));
+    }
+
+    let mut user_headers = HeaderMap::new();
+    user_headers.insert(
+        HeaderName::from_static("x-user-id"),
+        HeaderValue::from_str(&user.id.to_string()).expect("uuid is a valid header value"),
+    );
+    if let Ok(username) = HeaderValue::from_bytes(user.username.as_bytes()) {
+        user_headers.insert(HeaderName::from_static("x-user-name"), username);
+    }
+    if let Ok(role) = HeaderValue::from_str(user.account_role.as_str()) {
+        user_headers.insert(HeaderName::from_static("x-user-role"), role);
+    }
+    if let Some(impersonator_id) = match &credentials {
+        Credentials::Session(claims) => claims.impersonator_id(),
+        Credentials::ApiToken(_) => None,
+    } {
+        user_headers.insert(
+            HeaderName::from_static("x-impersonator-id"),
+            HeaderValue::from_str(&impersonator_id.to_string())
+                .expect("uuid is a valid header value"),
+        );
+    }
+
+    Ok((StatusCode::OK, user_headers))
+}
+
+async fn verify_allows(
+    permission_service: &PermissionService,
+    user: &AppUser,
+    credentials: &Credentials,
+    required_role: Option<&Role>,
+    permission: Option<&str>,
+) -> Result<bool, PermissionError> {
+    if let Some(role) = required_role {
+        if !permission_service
+            .role_includes(&user.account_role, role)
+            .await?
+        {
+            return Ok(false);
+        }
+    }
+
+    if let Some(permission) = permission {
+        if let Credentials::ApiToken(api_token) = credentials {
+            if !api_token.has_scope(permission) {
+                return Ok(false);
+            }
+        }
+
+        return permission_service
+            .has_permission(&user.account_role, permission)
+            .await;
+    }
+
+    Ok(true)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/auth/logout",
+    tag = "Auth",
+    request_body(content = Option<LogoutRequest>),
+    responses(
+        (status = NO_CONTENT, description = "Token revoked, together with its session unless it is an impersonation token"),
+        (status = FORBIDDEN, description = "Called with an API token"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn logout(
+    Extension(claims): Extension<TokenClaims>,
+    jar: CookieJar,
+    State(service): State<Arc<AuthService>>,
+    State(config): State<Config>,
+    body: Option<Json<LogoutRequest>>,
+) -> Result<impl IntoResponse, HttpError> {
+    // Cookies of a browser using an impersonation token belong to the admin's own session
+    let impersonating = claims.act.is_some();
+    let refresh_token = body
+        .and_then(|Json(body)| body.refresh_token)
+        .or_else(|| refresh_token_cookie(&jar, &config));
+
     service
-        .register(body.username.as_str(), body.password.as_str())
+        .logout(&claims, refresh_token.as_deref())
         .await
         .map_err(|e| match e {
-            RegisterError::UsernameInUse => {
-                HttpError::from((StatusCode::BAD_REQUEST, "Username is already used"))
-            }
-            RegisterError::InternalError => HttpError::from(StatusCode::INTERNAL_SERVER_ERROR),
+            LogoutError::InvalidToken => HttpError::from(StatusCode::UNAUTHORIZED),
+            LogoutError::InternalError => HttpError::from(StatusCode::INTERNAL_SERVER_ERROR),
+        })
+        .map(|_| {
+            let jar = if impersonating {
+                jar
+            } else {
+                clear_session_cookies(jar, &config)
+            };
+            (StatusCode::NO_CONTENT, jar)
         })
-        .map(|user| (StatusCode::CREATED, Json(user.id)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/auth/logout-everywhere",
+    tag = "Auth",
+    responses(
+        (status = NO_CONTENT, description = "All tokens of the user revoked"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn logout_everywhere(
+    Extension(user): Extension<AppUser>,
+    jar: CookieJar,
+    State(service): State<Arc<AuthService>>,
+    State(config): State<Config>,
+) -> Result<impl IntoResponse, HttpError> {
+    service
+        .logout_everywhere(user.id)
+        .await
+        .map_err(|e| match e {
+            LogoutError::InvalidToken => HttpError::from(StatusCode::UNAUTHORIZED),
+            LogoutError::InternalError => HttpError::from(StatusCode::INTERNAL_SERVER_ERROR),
+        })
+        .map(|_| (StatusCode::NO_CONTENT, clear_session_cookies(jar, &config)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/auth/forgot-password",
+    tag = "Auth",
+    request_body = ForgotPasswordRequest,
+    responses(
+        (status = ACCEPTED, description = "A reset link is sent if the user exists and has an email address"),
+    )
+)]
+pub(super) async fn forgot_password(
+    State(service): State<Arc<PasswordResetService>>,
+    Json(body): Json<ForgotPasswordRequest>,
+) -> Result<impl IntoResponse, HttpError> {
+    service
+        .request_reset(body.username.as_str())
+        .await
+        .map_err(|_| HttpError::from(StatusCode::INTERNAL_SERVER_ERROR))
+        .map(|_| StatusCode::ACCEPTED)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/auth/reset-password",
+    tag = "Auth",
+    request_body = ConfirmPasswordResetRequest,
+    responses(
+        (status = NO_CONTENT, description = "Password changed, existing tokens revoked"),
+        (status = BAD_REQUEST, description = "Reset token is invalid, expired or was already used, or the password does not meet the policy (with a ValidationErrorResponse body)"),
+    )
+)]
+pub(super) async fn confirm_password_reset(
+    origin: RequestOrigin,
+    State(service): State<Arc<PasswordResetService>>,
+    State(audit_service): State<Arc<AuditService>>,
+    Json(body): Json<ConfirmPasswordResetRequest>,
+) -> Result<impl IntoResponse, HttpError> {
+    let result = service
+        .reset(body.token.as_str(), body.new_password.as_str())
+        .await;
+
+    let record = match &result {
+        Ok(user_id) => {
+            AuditRecord::new(AuditAction::PasswordReset, AuditOutcome::Success).actor_id(*user_id)
+        }
+        Err(e) => {
+            AuditRecord::new(AuditAction::PasswordReset, AuditOutcome::Failure).detail(match e {
+                ResetError::InvalidToken => "invalid_token",
+                ResetError::ExpiredToken => "expired_token",
+                ResetError::PolicyViolation(_) => "policy_violation",
+                ResetError::InternalError => "internal_error",
+            })
+        }
+    };
+    audit_service.record(&origin, record).await;
+
+    result
+        .map_err(|e| match e {
+            ResetError::InvalidToken => HttpError::from("Invalid reset token"),
+            ResetError::ExpiredToken => HttpError::from("Expired reset token"),
+            ResetError::PolicyViolation(violations) => HttpError::from(violations),
+            ResetError::InternalError => HttpError::from(StatusCode::INTERNAL_SERVER_ERROR),
+        })
+        .map(|_| StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/auth/verify-email",
+    tag = "Auth",
+    request_body = VerifyEmailRequest,
+    responses(
+        (status = NO_CONTENT, description = "Email address verified"),
+        (status = BAD_REQUEST, description = "Verification token is invalid, expired, was already used or the address has changed since"),
+    )
+)]
+pub(super) async fn verify_email(
+    State(service): State<Arc<EmailVerificationService>>,
+    Json(body): Json<VerifyEmailRequest>,
+) -> Result<impl IntoResponse, HttpError> {
+    service
+        .verify(body.token.as_str())
+        .await
+        .map_err(|e| match e {
+            VerificationError::InvalidToken => HttpError::from("Invalid verification token"),
+            VerificationError::ExpiredToken => HttpError::from("Expired verification token"),
+            VerificationError::InternalError => HttpError::from(StatusCode::INTERNAL_SERVER_ERROR),
+        })
+        .map(|_| StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/auth/resend-verification",
+    tag = "Auth",
+    request_body = ResendVerificationRequest,
+    responses(
+        (status = ACCEPTED, description = "A new link is sent if the account exists and its email address is not verified yet"),
+    )
+)]
+pub(super) async fn resend_verification(
+    State(service): State<Arc<EmailVerificationService>>,
+    Json(body): Json<ResendVerificationRequest>,
+) -> Result<impl IntoResponse, HttpError> {
+    service
+        .resend(body.username.as_str())
+        .await
+        .map_err(|_| HttpError::from(StatusCode::INTERNAL_SERVER_ERROR))
+        .map(|_| StatusCode::ACCEPTED)
 }
@@ -1,7 +1,29 @@
 use axum::http::{header, HeaderMap};
-use std::sync::Arc;
+use axum_extra::extract::CookieJar;
+use std::{future::Future, pin::Pin, sync::Arc};
 
-use crate::{domain::app_user::AppUser, services::auth::AuthService};
+use crate::{
+    config::Config,
+    domain::{
+        api_token::{ApiToken, API_TOKEN_PREFIX},
+        app_user::AppUser,
+        audit_event::{AuditAction, AuditOutcome},
+    },
+    features::auth::session_cookie::csrf_token_valid,
+    services::{
+        api_token::ApiTokenService,
+        audit::{AuditRecord, AuditService, RequestOrigin},
+        auth::{AuthError, AuthService, TokenClaims},
+        permission::PermissionService,
+    },
+};
+
+/// How the request was authenticated. Only one of `TokenClaims` or `ApiToken`
+/// ends up in the request extensions.
+pub(super) enum Credentials {
+    Session(TokenClaims),
+    ApiToken(ApiToken),
+}
 
 #[derive(Debug, serde::Serialize)]
 pub struct ErrorResponse {
@@ -9,88 +31,246 @@ pub struct ErrorResponse {
 }
 
 pub async fn authorize(
-    headers: HeaderMap,
-    axum::extract::State(auth_service): axum::extract::State<Arc<AuthService>>,
-    mut req: axum::http::Request<axum::body::Body>,
-    next: axum::middleware::Next,
-) -> Result<impl axum::response::IntoResponse, (axum::http::StatusCode, axum::Json<ErrorResponse>)>
-{
-    let user = process_token(headers, auth_service).await?;
-    req.extensions_mut().insert(user);
-    Ok(next.run(req).await)
-}
-
-pub async fn authorize_admin(
-    headers: HeaderMap,
+    origin: RequestOrigin,
     axum::extract::State(auth_service): axum::extract::State<Arc<AuthService>>,
+    axum::extract::State(api_token_service): axum::extract::State<Arc<ApiTokenService>>,
+    axum::extract::State(audit_service): axum::extract::State<Arc<AuditService>>,
+    axum::extract::State(config): axum::extract::State<Config>,
     mut req: axum::http::Request<axum::body::Body>,
     next: axum::middleware::Next,
 ) -> Result<impl axum::response::IntoResponse, (axum::http::StatusCode, axum::Json<ErrorResponse>)>
 {
-    let user = process_token(headers, auth_service).await?;
-
-    if user.account_role != "Admin" {
+    let headers = req.headers().clone();
+    let cookie_name = config
+        .session_cookies
+        .then_some(config.access_token_cookie.as_str());
+    let from_cookie = cookie_name.is_some() && !headers.contains_key(header::AUTHORIZATION);
+    if from_cookie && !csrf_token_valid(req.method(), &headers) {
         let json_error = ErrorResponse {
-            message: "Insufficient privileges".to_string(),
+            message: "Invalid CSRF token".to_string(),
         };
         return Err((axum::http::StatusCode::FORBIDDEN, axum::Json(json_error)));
     }
 
+    let (user, credentials) = process_token(
+        headers,
+        cookie_name,
+        auth_service,
+        api_token_service,
+        audit_service,
+        &origin,
+    )
+    .await?;
+    if let Credentials::Session(TokenClaims { act: Some(act), .. }) = &credentials {
+        println!(
+            "Impersonated request {} {} as {} by {}",
+            req.method(),
+            req.uri().path(),
+            user.username,
+            act.sub
+        );
+    }
+
     req.extensions_mut().insert(user);
+    match credentials {
+        Credentials::Session(claims) => {
+            req.extensions_mut().insert(claims);
+        }
+        Credentials::ApiToken(api_token) => {
+            req.extensions_mut().insert(api_token);
+        }
+    }
     Ok(next.run(req).await)
 }
 
-async fn process_token(
+type MiddlewareResult =
+    Result<axum::response::Response, (axum::http::StatusCode, axum::Json<ErrorResponse>)>;
+type MiddlewareFuture = Pin<Box<dyn Future<Output = MiddlewareResult> + Send>>;
+
+/// Middleware rejecting requests whose user's role does not grant `permission`.
+/// Requests made with an API token also need the scope of the same name.
+/// Must be layered inside `authorize`, e.g.
+/// `route_layer(from_fn_with_state(app_state, require_permission("users:read")))`.
+pub fn require_permission(
+    permission: &'static str,
+) -> impl Fn(
+    axum::extract::State<Arc<PermissionService>>,
+    axum::http::Request<axum::body::Body>,
+    axum::middleware::Next,
+) -> MiddlewareFuture
+       + Clone {
+    move |axum::extract::State(permission_service), req, next| {
+        Box::pin(async move {
+            let role = match req.extensions().get::<AppUser>() {
+                Some(user) => user.account_role.clone(),
+                None => {
+                    let json_error = ErrorResponse {
+                        message: "Missing authorization token".to_string(),
+                    };
+                    return Err((axum::http::StatusCode::UNAUTHORIZED, axum::Json(json_error)));
+                }
+            };
+
+            if let Some(api_token) = req.extensions().get::<ApiToken>() {
+                if !api_token.has_scope(permission) {
+                    let json_error = ErrorResponse {
+                        message: format!("API token is missing the {} scope", permission),
+                    };
+                    return Err((axum::http::StatusCode::FORBIDDEN, axum::Json(json_error)));
+                }
+            }
+
+            match permission_service.has_permission(&role, permission).await {
+                Ok(true) => Ok(next.run(req).await),
+                Ok(false) => {
+                    let json_error = ErrorResponse {
+                        message: "Insufficient privileges".to_string(),
+                    };
+                    Err((axum::http::StatusCode::FORBIDDEN, axum::Json(json_error)))
+                }
+                Err(_) => {
+                    let json_error = ErrorResponse {
+                        message: "Internal error".to_string(),
+                    };
+                    Err((
+                        axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                        axum::Json(json_error),
+                    ))
+                }
+            }
+        })
+    }
+}
+
+/// Middleware rejecting requests made with an API token that lacks `scope`.
+/// Requests authenticated with an access token are let through.
+pub fn require_scope(
+    scope: &'static str,
+) -> impl Fn(axum::http::Request<axum::body::Body>, axum::middleware::Next) -> MiddlewareFuture + Clone
+{
+    move |req, next| {
+        Box::pin(async move {
+            if let Some(api_token) = req.extensions().get::<ApiToken>() {
+                if !api_token.has_scope(scope) {
+                    let json_error = ErrorResponse {
+                        message: format!("API token is missing the {} scope", scope),
+                    };
+                    return Err((axum::http::StatusCode::FORBIDDEN, axum::Json(json_error)));
+                }
+            }
+            Ok(next.run(req).await)
+        })
+    }
+}
+
+/// Middleware for account management routes, which API tokens and impersonation tokens
+/// must not reach.
+pub async fn require_session(
+    req: axum::http::Request<axum::body::Body>,
+    next: axum::middleware::Next,
+) -> MiddlewareResult {
+    let message = match req.extensions().get::<TokenClaims>() {
+        None => "Not allowed with an API token",
+        Some(claims) if claims.act.is_some() => "Not allowed while impersonating",
+        Some(_) => return Ok(next.run(req).await),
+    };
+    let json_error = ErrorResponse {
+        message: message.to_string(),
+    };
+    Err((axum::http::StatusCode::FORBIDDEN, axum::Json(json_error)))
+}
+
+/// Middleware for routes that work with the claims of an access token, which API tokens lack.
+/// Unlike `require_session` it lets impersonation tokens through.
+pub async fn require_access_token(
+    req: axum::http::Request<axum::body::Body>,
+    next: axum::middleware::Next,
+) -> MiddlewareResult {
+    if req.extensions().get::<TokenClaims>().is_some() {
+        return Ok(next.run(req).await);
+    }
+    let json_error = ErrorResponse {
+        message: "Not allowed with an API token".to_string(),
+    };
+    Err((axum::http::StatusCode::FORBIDDEN, axum::Json(json_error)))
+}
+
+/// Authenticates the bearer token of the `Authorization` header or, when the header is absent
+/// and `cookie_name` is given, the token stored in that cookie.
+/// Rejected tokens are recorded in the audit log, requests without any token are not.
+pub(super) async fn process_token(
     headers: HeaderMap,
+    cookie_name: Option<&str>,
     auth_service: Arc<AuthService>,
-) -> Result<AppUser, (axum::http::StatusCode, axum::Json<ErrorResponse>)> {
-    if !headers.contains_key(header::AUTHORIZATION) {
+    api_token_service: Arc<ApiTokenService>,
+    audit_service: Arc<AuditService>,
+    origin: &RequestOrigin,
+) -> Result<(AppUser, Credentials), (axum::http::StatusCode, axum::Json<ErrorResponse>)> {
+    let cookie = cookie_name.and_then(|name| {
+        CookieJar::from_headers(&headers)
+            .get(name)
+            .map(|cookie| cookie.value().to_owned())
+    });
+
+    if !headers.contains_key(header::AUTHORIZATION) && cookie.is_none() {
         let json_error = ErrorResponse {
             message: "Missing authorization token".to_string(),
         };
         return Err((axum::http::StatusCode::UNAUTHORIZED, axum::Json(json_error)));
     }
 
-    let token = headers
-        .get(header::AUTHORIZATION)
-        .and_then(|auth_header| auth_header.to_str().ok())
-        .and_then(|auth_value| {
-            if auth_value.starts_with("Bearer ") {
-                Some(auth_value[7..].to_owned())
-            } else {
-                None
-            }
-        });
+    let token = match headers.get(header::AUTHORIZATION) {
+        Some(auth_header) => auth_header
+            .to_str()
+            .ok()
+            .and_then(|auth_value| auth_value.strip_prefix("Bearer "))
+            .map(|token| token.to_owned()),
+        None => cookie,
+    };
 
-    let user = match token {
-        None => {
-            let json_error = ErrorResponse {
-                message: "Invalid token".to_string(),
-            };
-            Err((axum::http::StatusCode::UNAUTHORIZED, axum::Json(json_error)))
-        }?,
-        Some(val) => auth_service.auth_bearer_token(val.as_str()).await,
+    let authenticated = match token {
+        None => Err(AuthError::InvalidToken),
+        Some(val) if val.starts_with(API_TOKEN_PREFIX) => api_token_service
+            .authenticate(val.as_str())
+            .await
+            .map(|(user, api_token)| (user, Credentials::ApiToken(api_token))),
+        Some(val) => auth_service
+            .auth_bearer_token(val.as_str())
+            .await
+            .map(|(user, claims)| (user, Credentials::Session(claims))),
     }
     .map_err(|e| match e {
-        crate::services::auth::AuthError::InvalidToken => {
+        AuthError::InvalidToken => {
             let json_error = ErrorResponse {
                 message: "Invalid token".to_string(),
             };
             (axum::http::StatusCode::UNAUTHORIZED, axum::Json(json_error))
         }
-        crate::services::auth::AuthError::ExpiredToken => {
+        AuthError::ExpiredToken => {
             let json_error = ErrorResponse {
                 message: "Expired token".to_string(),
             };
             (axum::http::StatusCode::UNAUTHORIZED, axum::Json(json_error))
         }
-        crate::services::auth::AuthError::UserDoesNotExist => {
+        AuthError::RevokedToken => {
+            let json_error = ErrorResponse {
+                message: "Revoked token".to_string(),
+            };
+            (axum::http::StatusCode::UNAUTHORIZED, axum::Json(json_error))
+        }
+        AuthError::UserDoesNotExist => {
             let error = ErrorResponse {
                 message: "User does not exist".to_string(),
             };
             (axum::http::StatusCode::UNAUTHORIZED, axum::Json(error))
         }
-        crate::services::auth::AuthError::InternalError => {
+        AuthError::UserDisabled => {
+            let error = ErrorResponse {
+                message: "Account is disabled".to_string(),
+            };
+            (axum::http::StatusCode::FORBIDDEN, axum::Json(error))
+        }
+        AuthError::InternalError => {
             let json_error = ErrorResponse {
                 message: "Internal error".to_string(),
             };
@@ -99,6 +279,17 @@ async fn process_token(
                 axum::Json(json_error),
             )
         }
-    })?;
-    Ok(user)
+    });
+
+    if let Err((_, axum::Json(error))) = &authenticated {
+        audit_service
+            .record(
+                origin,
+                AuditRecord::new(AuditAction::TokenRejected, AuditOutcome::Failure)
+                    .detail(&error.message),
+            )
+            .await;
+    }
+
+    authenticated
 }
@@ -0,0 +1,104 @@
+use axum::http::{HeaderMap, Method};
+use axum_extra::extract::{cookie::Cookie, CookieJar};
+use sha2::{Digest, Sha256};
+
+use crate::{config::Config, services::auth::AuthTokens, utils::token::generate_token};
+
+/// Refresh token cookie, only sent to the auth endpoints that use it.
+pub const REFRESH_TOKEN_COOKIE: &str = "snailsoup_refresh";
+const REFRESH_TOKEN_COOKIE_PATH: &str = "/api/auth";
+/// CSRF token readable by the frontend, which echoes it in the `X-CSRF-Token` header.
+pub const CSRF_COOKIE: &str = "snailsoup_csrf";
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+/// Adds the session cookies for freshly issued tokens, together with a new CSRF token.
+/// Does nothing unless `SESSION_COOKIES` is enabled.
+pub fn set_session_cookies(jar: CookieJar, config: &Config, tokens: &AuthTokens) -> CookieJar {
+    if !config.session_cookies {
+        return jar;
+    }
+
+    jar.add(session_cookie(
+        config,
+        config.access_token_cookie.clone(),
+        tokens.access_token.clone(),
+        "/",
+        config.jwt_maxage,
+        true,
+    ))
+    .add(session_cookie(
+        config,
+        REFRESH_TOKEN_COOKIE.to_owned(),
+        tokens.refresh_token.clone(),
+        REFRESH_TOKEN_COOKIE_PATH,
+        config.refresh_token_maxage,
+        true,
+    ))
+    .add(session_cookie(
+        config,
+        CSRF_COOKIE.to_owned(),
+        generate_token(),
+        "/",
+        config.refresh_token_maxage,
+        false,
+    ))
+}
+
+pub fn clear_session_cookies(jar: CookieJar, config: &Config) -> CookieJar {
+    if !config.session_cookies {
+        return jar;
+    }
+
+    jar.remove(Cookie::build(config.access_token_cookie.clone()).path("/"))
+        .remove(Cookie::build(REFRESH_TOKEN_COOKIE).path(REFRESH_TOKEN_COOKIE_PATH))
+        .remove(Cookie::build(CSRF_COOKIE).path("/"))
+}
+
+pub fn refresh_token_cookie(jar: &CookieJar, config: &Config) -> Option<String> {
+    if !config.session_cookies {
+        return None;
+    }
+
+    jar.get(REFRESH_TOKEN_COOKIE)
+        .map(|cookie| cookie.value().to_owned())
+}
+
+/// Double-submit check: state-changing requests authenticated by cookie must repeat the
+/// CSRF cookie in the `X-CSRF-Token` header, which other sites can neither read nor set.
+pub fn csrf_token_valid(method: &Method, headers: &HeaderMap) -> bool {
+    if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+        return true;
+    }
+
+    let cookie = CookieJar::from_headers(headers)
+        .get(CSRF_COOKIE)
+        .map(|cookie| cookie.value().to_owned());
+    let header = headers
+        .get(CSRF_HEADER)
+        .and_then(|value| value.to_str().ok());
+
+    match (cookie, header) {
+        // Compare digests so the comparison time does not depend on the matching prefix
+        (Some(cookie), Some(header)) if !cookie.is_empty() => {
+            Sha256::digest(cookie.as_bytes()) == Sha256::digest(header.as_bytes())
+        }
+        _ => false,
+    }
+}
+
+fn session_cookie(
+    config: &Config,
+    name: String,
+    value: String,
+    path: &'static str,
+    maxage_minutes: u32,
+    http_only: bool,
+) -> Cookie<'static> {
+    Cookie::build((name, value))
+        .path(path)
+        .max_age(time::Duration::minutes(maxage_minutes.into()))
+        .http_only(http_only)
+        .secure(config.cookie_secure)
+        .same_site(config.cookie_same_site)
+        .build()
+}
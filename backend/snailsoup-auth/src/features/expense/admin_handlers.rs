@@ -0,0 +1,80 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    features::response::HttpError, services::expense::ExpenseService, utils::period::DatePeriod,
+};
+
+use super::api::{ExpenseResponse, PeriodQuery};
+
+#[utoipa::path(
+    get,
+    path = "/api/admin/expenses",
+    tag = "Expenses - Admin",
+    responses(
+        (status = StatusCode::OK, description = "list expenses successfully", body = [ExpenseResponse])
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn all_expenses(
+    State(service): State<Arc<ExpenseService>>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let expenses: Vec<ExpenseResponse> = service
+        .get_all_expenses()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .into_iter()
+        .map(ExpenseResponse::from_expense)
+        .collect();
+
+    Ok(Json(expenses))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/admin/users/{user_id}/expenses",
+    tag = "Expenses - Admin",
+    responses(
+        (status = StatusCode::OK, description = "list user expenses successfully", body = [ExpenseResponse]),
+        (status = StatusCode::NOT_FOUND, description = "User not found")
+    ),
+    params(
+        ("user_id" = Uuid, Path, description = "User database id to get expenses for"),
+        PeriodQuery
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn expenses_by_user_id(
+    Path(user_id): Path<Uuid>,
+    Query(query): Query<PeriodQuery>,
+    State(service): State<Arc<ExpenseService>>,
+) -> Result<impl IntoResponse, HttpError> {
+    let expenses = match (query.from, query.to) {
+        (None, None) => service.get_expenses_for_user(user_id).await,
+        (Some(from), Some(to)) => {
+            let period = DatePeriod::new(from, to)
+                .ok_or(HttpError::from("Period start must not be after its end"))?;
+            service
+                .get_expenses_for_user_in_period(user_id, period)
+                .await
+        }
+        _ => Err(HttpError::from("Both from and to must be provided"))?,
+    }
+    .map_err(|_| HttpError::from(StatusCode::INTERNAL_SERVER_ERROR))?;
+
+    match expenses {
+        Some(expenses) => Ok(Json(
+            expenses
+                .into_iter()
+                .map(ExpenseResponse::from_expense)
+                .collect::<Vec<ExpenseResponse>>(),
+        )),
+        None => Err(HttpError::from(StatusCode::NOT_FOUND)),
+    }
+}
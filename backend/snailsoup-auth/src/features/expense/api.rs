@@ -0,0 +1,197 @@
+use axum::{
+    middleware::{from_fn, from_fn_with_state},
+    routing::{get, post, put},
+    Router,
+};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    domain::expense::{Category, Expense, FullExpense, Tag},
+    features::auth::middleware::{require_permission, require_scope},
+};
+
+use super::admin_handlers::{all_expenses, expenses_by_user_id};
+use super::category_handlers::{
+    category_by_id, create_category, delete_category, my_categories, update_category,
+};
+use super::handlers::{create_expense, expense_by_id, my_expenses};
+use super::tag_handlers::{create_tag, delete_tag, my_tags, tag_by_id, update_tag};
+
+pub fn get_admin_routes(app_state: AppState) -> Router {
+    Router::new()
+        .route("/api/admin/expenses", get(all_expenses))
+        .route(
+            "/api/admin/users/:user_id/expenses",
+            get(expenses_by_user_id),
+        )
+        .route_layer(from_fn_with_state(
+            app_state.clone(),
+            require_permission("expenses:read"),
+        ))
+        .with_state(app_state)
+}
+
+pub fn get_private_routes(app_state: AppState) -> Router {
+    let read_routes = Router::new()
+        .route("/api/expenses", get(my_expenses))
+        .route("/api/expenses/:expense_id", get(expense_by_id))
+        .route("/api/tags", get(my_tags))
+        .route("/api/tags/:tag_id", get(tag_by_id))
+        .route("/api/categories", get(my_categories))
+        .route("/api/categories/:category_id", get(category_by_id))
+        .route_layer(from_fn(require_scope("expenses:read")));
+
+    let write_routes = Router::new()
+        .route("/api/expenses", post(create_expense))
+        .route("/api/tags", post(create_tag))
+        .route("/api/tags/:tag_id", put(update_tag).delete(delete_tag))
+        .route("/api/categories", post(create_category))
+        .route(
+            "/api/categories/:category_id",
+            put(update_category).delete(delete_category),
+        )
+        .route_layer(from_fn(require_scope("expenses:write")));
+
+    read_routes.merge(write_routes).with_state(app_state)
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct PeriodQuery {
+    /// First day of the period (inclusive)
+    pub from: Option<NaiveDate>,
+    /// Last day of the period (exclusive)
+    pub to: Option<NaiveDate>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct DeleteCategoryQuery {
+    /// Detach the category from its expenses instead of failing when it is in use
+    pub force: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateExpenseRequest {
+    #[schema()]
+    pub category_id: Option<Uuid>,
+    #[schema()]
+    pub description: String,
+    #[schema()]
+    pub expense_date: NaiveDate,
+    #[schema()]
+    pub cost: Decimal,
+    #[schema()]
+    #[serde(default)]
+    pub tags_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TagRequest {
+    #[schema()]
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CategoryRequest {
+    #[schema()]
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+pub struct ExpenseResponse {
+    #[schema()]
+    pub id: Uuid,
+    #[schema()]
+    pub user_id: Uuid,
+    #[schema()]
+    pub category_id: Option<Uuid>,
+    #[schema()]
+    pub description: String,
+    #[schema()]
+    pub expense_date: NaiveDate,
+    #[schema()]
+    pub cost: Decimal,
+}
+
+impl ExpenseResponse {
+    pub fn from_expense(expense: Expense) -> ExpenseResponse {
+        ExpenseResponse {
+            id: expense.id,
+            user_id: expense.data.user_id,
+            category_id: expense.data.category_id,
+            description: expense.data.description,
+            expense_date: expense.data.expense_date,
+            cost: expense.data.cost,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+pub struct FullExpenseResponse {
+    #[schema()]
+    pub id: Uuid,
+    #[schema()]
+    pub user_id: Uuid,
+    #[schema()]
+    pub category_id: Option<Uuid>,
+    #[schema()]
+    pub description: String,
+    #[schema()]
+    pub expense_date: NaiveDate,
+    #[schema()]
+    pub cost: Decimal,
+    #[schema()]
+    pub tags_ids: Vec<Uuid>,
+}
+
+impl FullExpenseResponse {
+    pub fn from_full_expense(expense: FullExpense) -> FullExpenseResponse {
+        FullExpenseResponse {
+            id: expense.id,
+            user_id: expense.data.expense.user_id,
+            category_id: expense.data.expense.category_id,
+            description: expense.data.expense.description,
+            expense_date: expense.data.expense.expense_date,
+            cost: expense.data.expense.cost,
+            tags_ids: expense.data.tags_ids,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+pub struct TagResponse {
+    #[schema()]
+    pub id: Uuid,
+    #[schema()]
+    pub name: String,
+}
+
+impl TagResponse {
+    pub fn from_tag(tag: Tag) -> TagResponse {
+        TagResponse {
+            id: tag.id,
+            name: tag.data.name,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+pub struct CategoryResponse {
+    #[schema()]
+    pub id: Uuid,
+    #[schema()]
+    pub name: String,
+}
+
+impl CategoryResponse {
+    pub fn from_category(category: Category) -> CategoryResponse {
+        CategoryResponse {
+            id: category.id,
+            name: category.data.name,
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        app_user::AppUser,
+        expense::{Category, CategoryData},
+    },
+    features::response::HttpError,
+    services::expense::{CreateError, DeleteError, ExpenseService},
+};
+
+use super::api::{CategoryRequest, CategoryResponse, DeleteCategoryQuery};
+
+#[utoipa::path(
+    get,
+    path = "/api/categories",
+    tag = "Categories",
+    responses(
+        (status = StatusCode::OK, description = "List categories of the current user", body = [CategoryResponse])
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn my_categories(
+    Extension(user): Extension<AppUser>,
+    State(service): State<Arc<ExpenseService>>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let categories: Vec<CategoryResponse> = service
+        .get_categories_for_user(user.id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .unwrap_or_default()
+        .into_iter()
+        .map(CategoryResponse::from_category)
+        .collect();
+
+    Ok(Json(categories))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/categories/{category_id}",
+    tag = "Categories",
+    responses(
+        (status = StatusCode::OK, description = "Category found successfully", body = CategoryResponse),
+        (status = StatusCode::NOT_FOUND, description = "Category not found")
+    ),
+    params(
+        ("category_id" = Uuid, Path, description = "Category database id"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn category_by_id(
+    Extension(user): Extension<AppUser>,
+    Path(category_id): Path<Uuid>,
+    State(service): State<Arc<ExpenseService>>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let category = service
+        .get_category(category_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match category {
+        Some(c) if c.data.user_id == user.id => Ok(Json(CategoryResponse::from_category(c))),
+        _ => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/categories",
+    tag = "Categories",
+    request_body = CategoryRequest,
+    responses(
+        (status = StatusCode::CREATED, body = Uuid)
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn create_category(
+    Extension(user): Extension<AppUser>,
+    State(service): State<Arc<ExpenseService>>,
+    Json(body): Json<CategoryRequest>,
+) -> Result<impl IntoResponse, HttpError> {
+    service
+        .create_category(CategoryData {
+            user_id: user.id,
+            name: body.name,
+        })
+        .await
+        .map_err(|e| match e {
+            CreateError::Validation(message) => HttpError::from(message.as_str()),
+            CreateError::NoUser => HttpError::from(StatusCode::UNAUTHORIZED),
+            CreateError::Internal => HttpError::from(StatusCode::INTERNAL_SERVER_ERROR),
+        })
+        .map(|id| (StatusCode::CREATED, Json(id)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/categories/{category_id}",
+    tag = "Categories",
+    request_body = CategoryRequest,
+    responses(
+        (status = StatusCode::OK, body = Uuid),
+        (status = StatusCode::NOT_FOUND, description = "Category not found")
+    ),
+    params(
+        ("category_id" = Uuid, Path, description = "Category database id"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn update_category(
+    Extension(user): Extension<AppUser>,
+    Path(category_id): Path<Uuid>,
+    State(service): State<Arc<ExpenseService>>,
+    Json(body): Json<CategoryRequest>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let category = service
+        .get_category(category_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if category.is_none_or(|c| c.data.user_id != user.id) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let updated = service
+        .update_category(Category {
+            id: category_id,
+            data: CategoryData {
+                user_id: user.id,
+                name: body.name,
+            },
+        })
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match updated {
+        Some(id) => Ok(Json(id)),
+        None => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/categories/{category_id}",
+    tag = "Categories",
+    responses(
+        (status = StatusCode::NO_CONTENT, description = "Category deleted"),
+        (status = StatusCode::NOT_FOUND, description = "Category not found"),
+        (status = StatusCode::CONFLICT, description = "Category is still used by expenses")
+    ),
+    params(
+        ("category_id" = Uuid, Path, description = "Category database id"),
+        DeleteCategoryQuery
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn delete_category(
+    Extension(user): Extension<AppUser>,
+    Path(category_id): Path<Uuid>,
+    Query(query): Query<DeleteCategoryQuery>,
+    State(service): State<Arc<ExpenseService>>,
+) -> Result<impl IntoResponse, HttpError> {
+    let category = service
+        .get_category(category_id)
+        .await
+        .map_err(|_| HttpError::from(StatusCode::INTERNAL_SERVER_ERROR))?;
+
+    if category.is_none_or(|c| c.data.user_id != user.id) {
+        return Err(HttpError::from(StatusCode::NOT_FOUND));
+    }
+
+    match service
+        .delete_category(category_id, query.force.unwrap_or(false))
+        .await
+    {
+        Ok(Some(_)) => Ok(StatusCode::NO_CONTENT),
+        Ok(None) => Err(HttpError::from(StatusCode::NOT_FOUND)),
+        Err(DeleteError::InUse) => Err(HttpError::from((
+            StatusCode::CONFLICT,
+            "Category is used by expenses",
+        ))),
+        Err(DeleteError::Internal) => Err(HttpError::from(StatusCode::INTERNAL_SERVER_ERROR)),
+    }
+}
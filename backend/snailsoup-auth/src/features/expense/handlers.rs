@@ -0,0 +1,125 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        app_user::AppUser,
+        expense::{ExpenseData, FullExpenseData},
+    },
+    features::response::HttpError,
+    services::expense::{CreateError, ExpenseService},
+    utils::period::DatePeriod,
+};
+
+use super::api::{CreateExpenseRequest, ExpenseResponse, FullExpenseResponse, PeriodQuery};
+
+#[utoipa::path(
+    get,
+    path = "/api/expenses",
+    tag = "Expenses",
+    params(PeriodQuery),
+    responses(
+        (status = StatusCode::OK, description = "List expenses of the current user", body = [ExpenseResponse]),
+        (status = StatusCode::BAD_REQUEST, description = "Invalid period")
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn my_expenses(
+    Extension(user): Extension<AppUser>,
+    Query(query): Query<PeriodQuery>,
+    State(service): State<Arc<ExpenseService>>,
+) -> Result<impl IntoResponse, HttpError> {
+    let expenses = match (query.from, query.to) {
+        (None, None) => service.get_expenses_for_user(user.id).await,
+        (Some(from), Some(to)) => {
+            let period = DatePeriod::new(from, to)
+                .ok_or(HttpError::from("Period start must not be after its end"))?;
+            service
+                .get_expenses_for_user_in_period(user.id, period)
+                .await
+        }
+        _ => Err(HttpError::from("Both from and to must be provided"))?,
+    }
+    .map_err(|_| HttpError::from(StatusCode::INTERNAL_SERVER_ERROR))?
+    .unwrap_or_default();
+
+    let expenses: Vec<ExpenseResponse> = expenses
+        .into_iter()
+        .map(ExpenseResponse::from_expense)
+        .collect();
+
+    Ok(Json(expenses))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/expenses/{expense_id}",
+    tag = "Expenses",
+    responses(
+        (status = StatusCode::OK, description = "Expense found successfully", body = FullExpenseResponse),
+        (status = StatusCode::NOT_FOUND, description = "Expense not found")
+    ),
+    params(
+        ("expense_id" = Uuid, Path, description = "Expense database id"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn expense_by_id(
+    Extension(user): Extension<AppUser>,
+    Path(expense_id): Path<Uuid>,
+    State(service): State<Arc<ExpenseService>>,
+) -> Result<impl IntoResponse, HttpError> {
+    let expense = service
+        .get_expense(expense_id)
+        .await
+        .map_err(|_| HttpError::from(StatusCode::INTERNAL_SERVER_ERROR))?;
+
+    match expense {
+        Some(e) if e.data.expense.user_id == user.id => {
+            Ok(Json(FullExpenseResponse::from_full_expense(e)))
+        }
+        _ => Err(HttpError::from(StatusCode::NOT_FOUND)),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/expenses",
+    tag = "Expenses",
+    request_body = CreateExpenseRequest,
+    responses(
+        (status = StatusCode::CREATED, body = Uuid),
+        (status = StatusCode::BAD_REQUEST, description = "Unknown tag or category")
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn create_expense(
+    Extension(user): Extension<AppUser>,
+    State(service): State<Arc<ExpenseService>>,
+    Json(body): Json<CreateExpenseRequest>,
+) -> Result<impl IntoResponse, HttpError> {
+    service
+        .create_expense(FullExpenseData {
+            expense: ExpenseData {
+                user_id: user.id,
+                category_id: body.category_id,
+                description: body.description,
+                expense_date: body.expense_date,
+                cost: body.cost,
+            },
+            tags_ids: body.tags_ids,
+        })
+        .await
+        .map_err(|e| match e {
+            CreateError::Validation(message) => HttpError::from(message.as_str()),
+            CreateError::NoUser => HttpError::from(StatusCode::UNAUTHORIZED),
+            CreateError::Internal => HttpError::from(StatusCode::INTERNAL_SERVER_ERROR),
+        })
+        .map(|id| (StatusCode::CREATED, Json(id)))
+}
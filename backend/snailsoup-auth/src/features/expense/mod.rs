@@ -0,0 +1,5 @@
+pub mod admin_handlers;
+pub mod api;
+pub mod category_handlers;
+pub mod handlers;
+pub mod tag_handlers;
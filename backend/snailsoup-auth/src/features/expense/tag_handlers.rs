@@ -0,0 +1,182 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        app_user::AppUser,
+        expense::{Tag, TagData},
+    },
+    features::response::HttpError,
+    services::expense::{CreateError, ExpenseService},
+};
+
+use super::api::{TagRequest, TagResponse};
+
+#[utoipa::path(
+    get,
+    path = "/api/tags",
+    tag = "Tags",
+    responses(
+        (status = StatusCode::OK, description = "List tags of the current user", body = [TagResponse])
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn my_tags(
+    Extension(user): Extension<AppUser>,
+    State(service): State<Arc<ExpenseService>>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let tags: Vec<TagResponse> = service
+        .get_tags_for_user(user.id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .unwrap_or_default()
+        .into_iter()
+        .map(TagResponse::from_tag)
+        .collect();
+
+    Ok(Json(tags))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/tags/{tag_id}",
+    tag = "Tags",
+    responses(
+        (status = StatusCode::OK, description = "Tag found successfully", body = TagResponse),
+        (status = StatusCode::NOT_FOUND, description = "Tag not found")
+    ),
+    params(
+        ("tag_id" = Uuid, Path, description = "Tag database id"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn tag_by_id(
+    Extension(user): Extension<AppUser>,
+    Path(tag_id): Path<Uuid>,
+    State(service): State<Arc<ExpenseService>>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let tag = service
+        .get_tag(tag_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match tag {
+        Some(t) if t.data.user_id == user.id => Ok(Json(TagResponse::from_tag(t))),
+        _ => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/tags",
+    tag = "Tags",
+    request_body = TagRequest,
+    responses(
+        (status = StatusCode::CREATED, body = Uuid)
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn create_tag(
+    Extension(user): Extension<AppUser>,
+    State(service): State<Arc<ExpenseService>>,
+    Json(body): Json<TagRequest>,
+) -> Result<impl IntoResponse, HttpError> {
+    service
+        .create_tag(TagData {
+            user_id: user.id,
+            name: body.name,
+        })
+        .await
+        .map_err(|e| match e {
+            CreateError::Validation(message) => HttpError::from(message.as_str()),
+            CreateError::NoUser => HttpError::from(StatusCode::UNAUTHORIZED),
+            CreateError::Internal => HttpError::from(StatusCode::INTERNAL_SERVER_ERROR),
+        })
+        .map(|id| (StatusCode::CREATED, Json(id)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/tags/{tag_id}",
+    tag = "Tags",
+    request_body = TagRequest,
+    responses(
+        (status = StatusCode::OK, body = Uuid),
+        (status = StatusCode::NOT_FOUND, description = "Tag not found")
+    ),
+    params(
+        ("tag_id" = Uuid, Path, description = "Tag database id"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn update_tag(
+    Extension(user): Extension<AppUser>,
+    Path(tag_id): Path<Uuid>,
+    State(service): State<Arc<ExpenseService>>,
+    Json(body): Json<TagRequest>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let tag = service
+        .get_tag(tag_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if tag.is_none_or(|t| t.data.user_id != user.id) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let updated = service
+        .update_tag(Tag {
+            id: tag_id,
+            data: TagData {
+                user_id: user.id,
+                name: body.name,
+            },
+        })
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match updated {
+        Some(id) => Ok(Json(id)),
+        None => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/tags/{tag_id}",
+    tag = "Tags",
+    responses(
+        (status = StatusCode::NO_CONTENT, description = "Tag deleted"),
+        (status = StatusCode::NOT_FOUND, description = "Tag not found")
+    ),
+    params(
+        ("tag_id" = Uuid, Path, description = "Tag database id"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn delete_tag(
+    Extension(user): Extension<AppUser>,
+    Path(tag_id): Path<Uuid>,
+    State(service): State<Arc<ExpenseService>>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let tag = service
+        .get_tag(tag_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if tag.is_none_or(|t| t.data.user_id != user.id) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    match service.delete_tag(tag_id).await {
+        Ok(Some(_)) => Ok(StatusCode::NO_CONTENT),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
@@ -0,0 +1,108 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    domain::app_user::AppUser,
+    features::response::HttpError,
+    services::invite::{InviteError, InviteService},
+};
+
+use super::api::{CreateInviteRequest, CreatedInviteResponse, InviteResponse};
+
+#[utoipa::path(
+    get,
+    path = "/api/admin/invites",
+    tag = "Invites - Admin",
+    responses(
+        (status = StatusCode::OK, description = "list invite codes successfully, without the codes themselves", body = [InviteResponse])
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn all_invites(
+    State(service): State<Arc<InviteService>>,
+) -> Result<impl IntoResponse, HttpError> {
+    service
+        .list()
+        .await
+        .map_err(map_invite_error)
+        .map(|invite_codes| {
+            Json(
+                invite_codes
+                    .into_iter()
+                    .map(InviteResponse::from_invite_code)
+                    .collect::<Vec<_>>(),
+            )
+        })
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/admin/invites",
+    tag = "Invites - Admin",
+    request_body = CreateInviteRequest,
+    responses(
+        (status = StatusCode::CREATED, description = "Invite code created, the code is not shown again", body = CreatedInviteResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Maximum uses below 1 or expiry in the past")
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn create_invite(
+    Extension(user): Extension<AppUser>,
+    State(service): State<Arc<InviteService>>,
+    Json(body): Json<CreateInviteRequest>,
+) -> Result<impl IntoResponse, HttpError> {
+    service
+        .create(user.id, body.max_uses.unwrap_or(1), body.expires_at)
+        .await
+        .map_err(map_invite_error)
+        .map(|(invite_code, code)| {
+            (
+                StatusCode::CREATED,
+                Json(CreatedInviteResponse {
+                    code,
+                    invite: InviteResponse::from_invite_code(invite_code),
+                }),
+            )
+        })
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/admin/invites/{invite_id}",
+    tag = "Invites - Admin",
+    responses(
+        (status = StatusCode::NO_CONTENT, description = "Invite code deleted, it cannot be used anymore"),
+        (status = StatusCode::NOT_FOUND, description = "Invite code not found"),
+    ),
+    params(
+        ("invite_id" = Uuid, Path, description = "Id of the invite code to delete"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn delete_invite(
+    Path(invite_id): Path<Uuid>,
+    State(service): State<Arc<InviteService>>,
+) -> Result<impl IntoResponse, HttpError> {
+    service
+        .delete(invite_id)
+        .await
+        .map_err(map_invite_error)
+        .map(|_| StatusCode::NO_CONTENT)
+}
+
+fn map_invite_error(e: InviteError) -> HttpError {
+    match e {
+        InviteError::InvalidMaxUses => HttpError::from("Maximum uses must be at least 1"),
+        InviteError::ExpiryInPast => HttpError::from("Expiry date must be in the future"),
+        InviteError::InvalidCode | InviteError::InviteDoesNotExist => {
+            HttpError::from(StatusCode::NOT_FOUND)
+        }
+        InviteError::InternalError => HttpError::from(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
@@ -0,0 +1,84 @@
+use axum::{
+    middleware::from_fn_with_state,
+    routing::{delete, get, post},
+    Router,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState, domain::invite_code::InviteCode,
+    features::auth::middleware::require_permission,
+};
+
+use super::admin_handlers::{all_invites, create_invite, delete_invite};
+
+pub fn get_admin_routes(app_state: AppState) -> Router {
+    let read_routes = Router::new()
+        .route("/api/admin/invites", get(all_invites))
+        .route_layer(from_fn_with_state(
+            app_state.clone(),
+            require_permission("invites:read"),
+        ));
+
+    let write_routes = Router::new()
+        .route("/api/admin/invites", post(create_invite))
+        .route("/api/admin/invites/:invite_id", delete(delete_invite))
+        .route_layer(from_fn_with_state(
+            app_state.clone(),
+            require_permission("invites:write"),
+        ));
+
+    read_routes.merge(write_routes).with_state(app_state)
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateInviteRequest {
+    /// Number of registrations the code allows, 1 when omitted
+    #[schema(example = 1)]
+    pub max_uses: Option<i32>,
+    /// The code never expires when omitted
+    #[schema()]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct InviteResponse {
+    #[schema()]
+    pub id: Uuid,
+    #[schema()]
+    pub max_uses: i32,
+    #[schema()]
+    pub uses: i32,
+    /// Admin who created the code
+    #[schema()]
+    pub created_by: Option<Uuid>,
+    #[schema()]
+    pub created_at: DateTime<Utc>,
+    #[schema()]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl InviteResponse {
+    pub fn from_invite_code(invite_code: InviteCode) -> InviteResponse {
+        InviteResponse {
+            id: invite_code.id,
+            max_uses: invite_code.max_uses,
+            uses: invite_code.uses,
+            created_by: invite_code.created_by,
+            created_at: invite_code.created_at,
+            expires_at: invite_code.expires_at,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CreatedInviteResponse {
+    /// Passed as `invite_code` on registration, shown only once
+    #[schema()]
+    pub code: String,
+    #[schema()]
+    pub invite: InviteResponse,
+}
@@ -2,21 +2,39 @@ use axum::Router;
 
 use crate::app_state::AppState;
 
+mod audit;
 mod auth;
+mod client_ip;
+mod expense;
+mod invite;
+mod oauth;
 mod response;
 mod swagger;
 mod user;
 
 pub fn get_routes(app_state: AppState) -> Router {
-    let public_routes = auth::api::get_public_routes(app_state.clone());
+    let public_routes = auth::api::get_public_routes(app_state.clone())
+        .merge(oauth::api::get_public_routes(app_state.clone()));
 
-    let private_routes = user::api::get_private_routes(app_state.clone()).route_layer(
-        axum::middleware::from_fn_with_state(app_state.clone(), auth::middleware::authorize),
-    );
+    let private_routes = user::api::get_private_routes(app_state.clone())
+        .merge(auth::api::get_private_routes(app_state.clone()))
+        .merge(expense::api::get_private_routes(app_state.clone()))
+        .merge(oauth::api::get_private_routes(app_state.clone()))
+        .route_layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            auth::middleware::authorize,
+        ));
 
-    let admin_routes = user::api::get_admin_routes(app_state.clone()).route_layer(
-        axum::middleware::from_fn_with_state(app_state.clone(), auth::middleware::authorize_admin),
-    );
+    // Each admin route checks its own permission with `require_permission`
+    let admin_routes = user::api::get_admin_routes(app_state.clone())
+        .merge(expense::api::get_admin_routes(app_state.clone()))
+        .merge(oauth::api::get_admin_routes(app_state.clone()))
+        .merge(invite::api::get_admin_routes(app_state.clone()))
+        .merge(audit::api::get_admin_routes(app_state.clone()))
+        .route_layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            auth::middleware::authorize,
+        ));
 
     swagger::get_routes()
         .merge(public_routes)
@@ -0,0 +1,125 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    features::response::HttpError,
+    services::oauth::{ClientError, NewClient, OAuthService},
+};
+
+use super::api::{ClientResponse, CreateClientRequest, CreatedClientResponse};
+
+#[utoipa::path(
+    get,
+    path = "/api/admin/oauth/clients",
+    tag = "OAuth - Admin",
+    responses(
+        (status = StatusCode::OK, description = "list OAuth clients successfully", body = [ClientResponse])
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn all_clients(
+    State(service): State<Arc<OAuthService>>,
+) -> Result<impl IntoResponse, HttpError> {
+    service
+        .list_clients()
+        .await
+        .map_err(map_client_error)
+        .map(|clients| {
+            Json(
+                clients
+                    .into_iter()
+                    .map(ClientResponse::from_client)
+                    .collect::<Vec<_>>(),
+            )
+        })
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/admin/oauth/clients",
+    tag = "OAuth - Admin",
+    request_body = CreateClientRequest,
+    responses(
+        (status = StatusCode::CREATED, description = "Client registered, the secret is shown only once", body = CreatedClientResponse),
+        (status = StatusCode::BAD_REQUEST)
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn create_client(
+    State(service): State<Arc<OAuthService>>,
+    Json(body): Json<CreateClientRequest>,
+) -> Result<impl IntoResponse, HttpError> {
+    service
+        .create_client(NewClient {
+            name: body.name,
+            redirect_uris: body.redirect_uris,
+            grant_types: body.grant_types,
+            scopes: body.scopes,
+            confidential: body.confidential,
+        })
+        .await
+        .map_err(map_client_error)
+        .map(|(client, client_secret)| {
+            (
+                StatusCode::CREATED,
+                Json(CreatedClientResponse {
+                    client_secret,
+                    client: ClientResponse::from_client(client),
+                }),
+            )
+        })
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/admin/oauth/clients/{client_id}",
+    tag = "OAuth - Admin",
+    responses(
+        (status = StatusCode::NO_CONTENT, description = "Client deleted"),
+        (status = StatusCode::NOT_FOUND, description = "Client not found"),
+    ),
+    params(
+        ("client_id" = Uuid, Path, description = "Id of the OAuth client to delete"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn delete_client(
+    Path(client_id): Path<Uuid>,
+    State(service): State<Arc<OAuthService>>,
+) -> Result<impl IntoResponse, HttpError> {
+    service
+        .delete_client(client_id)
+        .await
+        .map_err(map_client_error)
+        .map(|_| StatusCode::NO_CONTENT)
+}
+
+fn map_client_error(e: ClientError) -> HttpError {
+    match e {
+        ClientError::MissingName => HttpError::from("Client name must not be empty"),
+        ClientError::MissingGrantTypes => HttpError::from("At least one grant type is required"),
+        ClientError::MissingRedirectUris => {
+            HttpError::from("The authorization code grant requires a redirect URI")
+        }
+        ClientError::InvalidRedirectUri(uri) => {
+            HttpError::from(format!("Invalid redirect URI {}", uri).as_str())
+        }
+        ClientError::UnsupportedGrantType(grant_type) => {
+            HttpError::from(format!("Unsupported grant type {}", grant_type).as_str())
+        }
+        ClientError::InvalidScope(scope) => {
+            HttpError::from(format!("Unknown scope {}", scope).as_str())
+        }
+        ClientError::PublicClientCredentials => {
+            HttpError::from("The client credentials grant requires a confidential client")
+        }
+        ClientError::ClientDoesNotExist => HttpError::from(StatusCode::NOT_FOUND),
+        ClientError::InternalError => HttpError::from(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
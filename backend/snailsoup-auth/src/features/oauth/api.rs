@@ -0,0 +1,383 @@
+use axum::{
+    middleware::{from_fn, from_fn_with_state},
+    routing::{delete, get, post},
+    Router,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    domain::oauth::OAuthClient,
+    features::auth::middleware::{require_permission, require_session},
+    services::{
+        introspection::Introspection,
+        oauth::{AuthorizationRequest, OAuthTokens, UserClaims},
+    },
+};
+
+use super::admin_handlers::{all_clients, create_client, delete_client};
+use super::handlers::{
+    approve_authorization, authorize, introspect, openid_configuration, token, userinfo,
+};
+
+pub fn get_public_routes(app_state: AppState) -> Router {
+    Router::new()
+        .route(
+            "/.well-known/openid-configuration",
+            get(openid_configuration),
+        )
+        .route("/oauth/authorize", get(authorize))
+        .route("/oauth/token", post(token))
+        .route("/oauth/userinfo", get(userinfo))
+        .route("/api/auth/introspect", post(introspect))
+        .with_state(app_state)
+}
+
+pub fn get_private_routes(app_state: AppState) -> Router {
+    Router::new()
+        .route("/api/oauth/authorize", post(approve_authorization))
+        .route_layer(from_fn(require_session))
+        .with_state(app_state)
+}
+
+pub fn get_admin_routes(app_state: AppState) -> Router {
+    let read_routes = Router::new()
+        .route("/api/admin/oauth/clients", get(all_clients))
+        .route_layer(from_fn_with_state(
+            app_state.clone(),
+            require_permission("clients:read"),
+        ));
+
+    let write_routes = Router::new()
+        .route("/api/admin/oauth/clients", post(create_client))
+        .route("/api/admin/oauth/clients/:client_id", delete(delete_client))
+        .route_layer(from_fn_with_state(
+            app_state.clone(),
+            require_permission("clients:write"),
+        ));
+
+    read_routes.merge(write_routes).with_state(app_state)
+}
+
+/// Authorization request of RFC 6749 section 4.1.1 with PKCE (RFC 7636) and OpenID Connect `nonce`
+#[derive(Debug, Deserialize, IntoParams, ToSchema)]
+#[into_params(parameter_in = Query)]
+pub struct AuthorizeQuery {
+    /// Must be `code`
+    #[schema(example = "code")]
+    pub response_type: String,
+    #[schema()]
+    pub client_id: String,
+    /// May be omitted when the client has a single redirect URI
+    #[schema()]
+    pub redirect_uri: Option<String>,
+    /// Space separated scopes, all scopes allowed for the client when omitted
+    #[schema(example = "openid profile email")]
+    pub scope: Option<String>,
+    #[schema()]
+    pub state: Option<String>,
+    /// BASE64URL(SHA256(code_verifier))
+    #[schema()]
+    pub code_challenge: Option<String>,
+    /// Must be `S256`
+    #[schema(example = "S256")]
+    pub code_challenge_method: Option<String>,
+    /// Copied into the ID token
+    #[schema()]
+    pub nonce: Option<String>,
+}
+
+impl AuthorizeQuery {
+    pub fn to_request(&self) -> AuthorizationRequest {
+        AuthorizationRequest {
+            response_type: self.response_type.clone(),
+            client_id: self.client_id.clone(),
+            redirect_uri: self.redirect_uri.clone(),
+            scope: self.scope.clone(),
+            state: self.state.clone(),
+            code_challenge: self.code_challenge.clone(),
+            code_challenge_method: self.code_challenge_method.clone(),
+            nonce: self.nonce.clone(),
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AuthorizeResponse {
+    /// Where to send the user next, back to the client with either a code or an error
+    #[schema()]
+    pub redirect_to: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TokenRequest {
+    /// `authorization_code` or `client_credentials`
+    #[schema(example = "authorization_code")]
+    pub grant_type: String,
+    #[schema()]
+    pub code: Option<String>,
+    /// Required when it was sent to the authorization endpoint, must be the same value
+    #[schema()]
+    pub redirect_uri: Option<String>,
+    #[schema()]
+    pub code_verifier: Option<String>,
+    /// Space separated scopes for the client credentials grant
+    #[schema()]
+    pub scope: Option<String>,
+    /// Alternative to HTTP Basic authentication
+    #[schema()]
+    pub client_id: Option<String>,
+    /// Alternative to HTTP Basic authentication
+    #[schema()]
+    pub client_secret: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct TokenResponse {
+    #[schema()]
+    pub access_token: String,
+    #[schema(example = "Bearer")]
+    pub token_type: String,
+    /// Lifetime of the access token in seconds
+    #[schema()]
+    pub expires_in: i64,
+    #[schema()]
+    pub scope: String,
+    /// Present when the `openid` scope was granted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema()]
+    pub id_token: Option<String>,
+}
+
+impl TokenResponse {
+    pub fn from_tokens(tokens: OAuthTokens) -> TokenResponse {
+        TokenResponse {
+            access_token: tokens.access_token,
+            token_type: "Bearer".to_owned(),
+            expires_in: tokens.expires_in,
+            scope: tokens.scope,
+            id_token: tokens.id_token,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct IntrospectionRequest {
+    /// Access token or API token to check. A `token_type_hint` is not needed, the
+    /// token type is recognized by its format.
+    #[schema()]
+    pub token: String,
+    /// Alternative to HTTP Basic authentication
+    #[schema()]
+    pub client_id: Option<String>,
+    /// Alternative to HTTP Basic authentication
+    #[schema()]
+    pub client_secret: Option<String>,
+}
+
+/// Introspection response of RFC 7662 section 2.2, only `active` is set for inactive tokens
+#[derive(Serialize, ToSchema)]
+pub struct IntrospectionResponse {
+    #[schema()]
+    pub active: bool,
+    /// User id, or the client id for client credentials tokens
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema()]
+    pub sub: Option<Uuid>,
+    /// OAuth client the access token was issued to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema()]
+    pub client_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema()]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "User")]
+    pub role: Option<String>,
+    /// Space separated scopes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "profile:read expenses:read")]
+    pub scope: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema()]
+    pub iat: Option<i64>,
+    /// Absent for API tokens that do not expire
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema()]
+    pub exp: Option<i64>,
+    /// Admin acting as the user when the token was issued by impersonation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema()]
+    pub act: Option<ActorResponse>,
+}
+
+/// Actor of RFC 8693 section 4.1
+#[derive(Serialize, ToSchema)]
+pub struct ActorResponse {
+    /// Admin user id
+    #[schema()]
+    pub sub: Uuid,
+}
+
+impl IntrospectionResponse {
+    pub fn from_introspection(introspection: Option<Introspection>) -> IntrospectionResponse {
+        match introspection {
+            Some(i) => IntrospectionResponse {
+                active: true,
+                sub: i.user.as_ref().map(|user| user.id).or(i.client_id),
+                client_id: i.client_id,
+                role: i.user.as_ref().map(|user| user.account_role.to_string()),
+                username: i.user.map(|user| user.username),
+                scope: Some(i.scopes.join(" ")),
+                iat: i.issued_at.map(|t| t.timestamp()),
+                exp: i.expires_at.map(|t| t.timestamp()),
+                act: i.impersonator_id.map(|sub| ActorResponse { sub }),
+            },
+            None => IntrospectionResponse {
+                active: false,
+                sub: None,
+                client_id: None,
+                username: None,
+                role: None,
+                scope: None,
+                iat: None,
+                exp: None,
+                act: None,
+            },
+        }
+    }
+}
+
+/// Error response of RFC 6749 section 5.2
+#[derive(Serialize, ToSchema)]
+pub struct OAuthErrorResponse {
+    #[schema(example = "invalid_grant")]
+    pub error: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema()]
+    pub error_description: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct UserInfoResponse {
+    #[schema()]
+    pub sub: Uuid,
+    /// Granted with the `profile` scope
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema()]
+    pub preferred_username: Option<String>,
+    /// Granted with the `profile` scope
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema()]
+    pub role: Option<String>,
+    /// Granted with the `email` scope
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema()]
+    pub email: Option<String>,
+}
+
+impl UserInfoResponse {
+    pub fn from_claims(sub: Uuid, claims: UserClaims) -> UserInfoResponse {
+        UserInfoResponse {
+            sub,
+            preferred_username: claims.preferred_username,
+            role: claims.role,
+            email: claims.email,
+        }
+    }
+}
+
+/// OpenID Provider Metadata, see OpenID Connect Discovery 1.0
+#[derive(Serialize, ToSchema)]
+pub struct OpenIdConfigurationResponse {
+    #[schema()]
+    pub issuer: String,
+    #[schema()]
+    pub authorization_endpoint: String,
+    #[schema()]
+    pub token_endpoint: String,
+    #[schema()]
+    pub userinfo_endpoint: String,
+    #[schema()]
+    pub jwks_uri: String,
+    #[schema()]
+    pub response_types_supported: Vec<String>,
+    #[schema()]
+    pub grant_types_supported: Vec<String>,
+    #[schema()]
+    pub subject_types_supported: Vec<String>,
+    #[schema()]
+    pub id_token_signing_alg_values_supported: Vec<String>,
+    #[schema()]
+    pub scopes_supported: Vec<String>,
+    #[schema()]
+    pub token_endpoint_auth_methods_supported: Vec<String>,
+    #[schema()]
+    pub code_challenge_methods_supported: Vec<String>,
+    #[schema()]
+    pub claims_supported: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateClientRequest {
+    #[schema()]
+    pub name: String,
+    /// Required for the authorization code grant, matched exactly
+    #[serde(default)]
+    #[schema()]
+    pub redirect_uris: Vec<String>,
+    /// `authorization_code` and/or `client_credentials`
+    #[schema(example = json!(["authorization_code"]))]
+    pub grant_types: Vec<String>,
+    /// Scopes the client may request, e.g. `openid`, `profile` and `email`
+    #[serde(default)]
+    #[schema(example = json!(["openid", "profile", "email"]))]
+    pub scopes: Vec<String>,
+    /// Confidential clients get a secret, public clients (single page or mobile apps) use PKCE only
+    #[schema()]
+    pub confidential: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ClientResponse {
+    #[schema()]
+    pub client_id: Uuid,
+    #[schema()]
+    pub name: String,
+    #[schema()]
+    pub confidential: bool,
+    #[schema()]
+    pub redirect_uris: Vec<String>,
+    #[schema()]
+    pub grant_types: Vec<String>,
+    #[schema()]
+    pub scopes: Vec<String>,
+    #[schema()]
+    pub created_at: DateTime<Utc>,
+}
+
+impl ClientResponse {
+    pub fn from_client(client: OAuthClient) -> ClientResponse {
+        ClientResponse {
+            client_id: client.id,
+            confidential: client.is_confidential(),
+            name: client.name,
+            redirect_uris: client.redirect_uris,
+            grant_types: client.grant_types,
+            scopes: client.scopes,
+            created_at: client.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CreatedClientResponse {
+    /// Shown only once, absent for public clients
+    #[schema()]
+    pub client_secret: Option<String>,
+    #[schema()]
+    pub client: ClientResponse,
+}
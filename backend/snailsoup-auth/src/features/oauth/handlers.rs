@@ -0,0 +1,381 @@
+use axum::{
+    extract::{Query, RawQuery, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Redirect, Response},
+    Extension, Form, Json,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use percent_encoding::percent_decode_str;
+use std::sync::Arc;
+
+use crate::{
+    config::Config,
+    domain::{
+        app_user::AppUser,
+        oauth::{GRANT_AUTHORIZATION_CODE, GRANT_CLIENT_CREDENTIALS},
+    },
+    features::response::HttpError,
+    services::{
+        introspection::{IntrospectionError, IntrospectionService},
+        oauth::{AuthorizeError, ClientCredentials, OAuthService, TokenError, UserInfoError},
+    },
+};
+
+use super::api::{
+    AuthorizeQuery, AuthorizeResponse, IntrospectionRequest, IntrospectionResponse,
+    OAuthErrorResponse, OpenIdConfigurationResponse, TokenRequest, TokenResponse, UserInfoResponse,
+};
+
+/// OAuth endpoints answer with the error format of RFC 6749 section 5.2 instead of plain text.
+pub(super) struct OAuthError {
+    status: StatusCode,
+    error: &'static str,
+    description: Option<&'static str>,
+    www_authenticate: Option<&'static str>,
+}
+
+impl OAuthError {
+    fn new(status: StatusCode, error: &'static str) -> Self {
+        OAuthError {
+            status,
+            error,
+            description: None,
+            www_authenticate: None,
+        }
+    }
+}
+
+impl IntoResponse for OAuthError {
+    fn into_response(self) -> Response {
+        let mut response = (
+            self.status,
+            Json(OAuthErrorResponse {
+                error: self.error.to_owned(),
+                error_description: self.description.map(|d| d.to_owned()),
+            }),
+        )
+            .into_response();
+        let headers = response.headers_mut();
+        headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+        if let Some(challenge) = self.www_authenticate {
+            headers.insert(
+                header::WWW_AUTHENTICATE,
+                HeaderValue::from_static(challenge),
+            );
+        }
+        response
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/.well-known/openid-configuration",
+    tag = "OAuth",
+    responses(
+        (status = StatusCode::OK, body = OpenIdConfigurationResponse)
+    )
+)]
+pub(super) async fn openid_configuration(State(config): State<Config>) -> impl IntoResponse {
+    let issuer = config.oauth_issuer;
+    let strings = |values: &[&str]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+
+    Json(OpenIdConfigurationResponse {
+        authorization_endpoint: format!("{}/oauth/authorize", issuer),
+        token_endpoint: format!("{}/oauth/token", issuer),
+        userinfo_endpoint: format!("{}/oauth/userinfo", issuer),
+        jwks_uri: format!("{}/.well-known/jwks.json", issuer),
+        response_types_supported: strings(&["code"]),
+        grant_types_supported: strings(&[GRANT_AUTHORIZATION_CODE, GRANT_CLIENT_CREDENTIALS]),
+        subject_types_supported: strings(&["public"]),
+        id_token_signing_alg_values_supported: vec![format!("{:?}", config.jwt_algorithm)],
+        scopes_supported: strings(&["openid", "profile", "email"]),
+        token_endpoint_auth_methods_supported: strings(&[
+            "client_secret_basic",
+            "client_secret_post",
+            "none",
+        ]),
+        code_challenge_methods_supported: strings(&["S256"]),
+        claims_supported: strings(&[
+            "iss",
+            "sub",
+            "aud",
+            "iat",
+            "exp",
+            "nonce",
+            "preferred_username",
+            "role",
+            "email",
+        ]),
+        issuer,
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/oauth/authorize",
+    tag = "OAuth",
+    params(AuthorizeQuery),
+    responses(
+        (status = StatusCode::SEE_OTHER, description = "Redirect to the consent page, or back to the client with an error"),
+        (status = StatusCode::BAD_REQUEST, description = "Unknown client or redirect URI")
+    )
+)]
+pub(super) async fn authorize(
+    RawQuery(raw_query): RawQuery,
+    Query(query): Query<AuthorizeQuery>,
+    State(service): State<Arc<OAuthService>>,
+    State(config): State<Config>,
+) -> Result<impl IntoResponse, HttpError> {
+    match service.check_authorization(&query.to_request()).await {
+        Ok(()) => {
+            let separator = if config.oauth_consent_url.contains('?') {
+                '&'
+            } else {
+                '?'
+            };
+            Ok(Redirect::to(&format!(
+                "{}{}{}",
+                config.oauth_consent_url,
+                separator,
+                raw_query.unwrap_or_default()
+            )))
+        }
+        Err(AuthorizeError::Rejected(redirect)) => Ok(Redirect::to(&redirect)),
+        Err(e) => Err(map_authorize_error(e)),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/oauth/authorize",
+    tag = "OAuth",
+    request_body = AuthorizeQuery,
+    responses(
+        (status = StatusCode::OK, description = "Request approved, or rejected with an error for the client", body = AuthorizeResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Unknown client or redirect URI")
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn approve_authorization(
+    Extension(user): Extension<AppUser>,
+    State(service): State<Arc<OAuthService>>,
+    Json(body): Json<AuthorizeQuery>,
+) -> Result<impl IntoResponse, HttpError> {
+    match service.authorize(&user, &body.to_request()).await {
+        Ok(redirect_to) | Err(AuthorizeError::Rejected(redirect_to)) => {
+            Ok(Json(AuthorizeResponse { redirect_to }))
+        }
+        Err(e) => Err(map_authorize_error(e)),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/oauth/token",
+    tag = "OAuth",
+    request_body(content = TokenRequest, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = StatusCode::OK, body = TokenResponse),
+        (status = StatusCode::BAD_REQUEST, body = OAuthErrorResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Client authentication failed", body = OAuthErrorResponse)
+    )
+)]
+pub(super) async fn token(
+    headers: HeaderMap,
+    State(service): State<Arc<OAuthService>>,
+    Form(body): Form<TokenRequest>,
+) -> Result<impl IntoResponse, OAuthError> {
+    let credentials = client_credentials(
+        &headers,
+        body.client_id.as_deref(),
+        body.client_secret.as_deref(),
+    )?;
+
+    let tokens = match body.grant_type.as_str() {
+        GRANT_AUTHORIZATION_CODE => {
+            service
+                .exchange_code(
+                    credentials,
+                    body.code.as_deref(),
+                    body.redirect_uri.as_deref(),
+                    body.code_verifier.as_deref(),
+                )
+                .await
+        }
+        GRANT_CLIENT_CREDENTIALS => {
+            service
+                .client_credentials(credentials, body.scope.as_deref())
+                .await
+        }
+        _ => Err(TokenError::UnsupportedGrantType),
+    }
+    .map_err(map_token_error)?;
+
+    Ok((
+        [
+            (header::CACHE_CONTROL, "no-store"),
+            (header::PRAGMA, "no-cache"),
+        ],
+        Json(TokenResponse::from_tokens(tokens)),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/oauth/userinfo",
+    tag = "OAuth",
+    responses(
+        (status = StatusCode::OK, body = UserInfoResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Missing, invalid or revoked access token"),
+        (status = StatusCode::FORBIDDEN, description = "Access token lacks the openid scope")
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn userinfo(
+    headers: HeaderMap,
+    State(service): State<Arc<OAuthService>>,
+) -> Result<impl IntoResponse, OAuthError> {
+    let invalid_token = || OAuthError {
+        www_authenticate: Some("Bearer error=\"invalid_token\""),
+        ..OAuthError::new(StatusCode::UNAUTHORIZED, "invalid_token")
+    };
+
+    let access_token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .ok_or_else(invalid_token)?;
+
+    match service.userinfo(access_token).await {
+        Ok((sub, claims)) => Ok(Json(UserInfoResponse::from_claims(sub, claims))),
+        Err(UserInfoError::InvalidToken) => Err(invalid_token()),
+        Err(UserInfoError::InsufficientScope) => Err(OAuthError {
+            www_authenticate: Some("Bearer error=\"insufficient_scope\", scope=\"openid\""),
+            ..OAuthError::new(StatusCode::FORBIDDEN, "insufficient_scope")
+        }),
+        Err(UserInfoError::InternalError) => Err(OAuthError::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "server_error",
+        )),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/auth/introspect",
+    tag = "OAuth",
+    request_body(content = IntrospectionRequest, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = StatusCode::OK, description = "Token state, `active` is false for unusable tokens", body = IntrospectionResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Client authentication failed", body = OAuthErrorResponse)
+    )
+)]
+pub(super) async fn introspect(
+    headers: HeaderMap,
+    State(service): State<Arc<IntrospectionService>>,
+    Form(body): Form<IntrospectionRequest>,
+) -> Result<impl IntoResponse, OAuthError> {
+    let credentials = client_credentials(
+        &headers,
+        body.client_id.as_deref(),
+        body.client_secret.as_deref(),
+    )?;
+
+    let introspection =
+        service
+            .introspect(credentials, &body.token)
+            .await
+            .map_err(|e| match e {
+                IntrospectionError::InvalidClient => map_token_error(TokenError::InvalidClient),
+                IntrospectionError::InternalError => map_token_error(TokenError::InternalError),
+            })?;
+
+    Ok((
+        [(header::CACHE_CONTROL, "no-store")],
+        Json(IntrospectionResponse::from_introspection(introspection)),
+    ))
+}
+
+/// Reads the client credentials from HTTP Basic authentication or, failing that, the form body.
+fn client_credentials(
+    headers: &HeaderMap,
+    client_id: Option<&str>,
+    client_secret: Option<&str>,
+) -> Result<ClientCredentials, OAuthError> {
+    let basic = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Basic "));
+
+    if let Some(encoded) = basic {
+        let decoded = STANDARD
+            .decode(encoded.trim())
+            .ok()
+            .and_then(|d| String::from_utf8(d).ok())
+            .ok_or_else(|| map_token_error(TokenError::InvalidClient))?;
+        let (client_id, client_secret) = decoded
+            .split_once(':')
+            .and_then(|(id, secret)| Some((form_decode(id)?, form_decode(secret)?)))
+            .ok_or_else(|| map_token_error(TokenError::InvalidClient))?;
+
+        return Ok(ClientCredentials {
+            client_id,
+            client_secret: Some(client_secret),
+        });
+    }
+
+    let client_id = client_id.map(|id| id.to_owned()).ok_or(OAuthError {
+        description: Some("client_id is required"),
+        ..OAuthError::new(StatusCode::BAD_REQUEST, "invalid_request")
+    })?;
+
+    Ok(ClientCredentials {
+        client_id,
+        client_secret: client_secret.map(|secret| secret.to_owned()),
+    })
+}
+
+/// Client id and secret are `application/x-www-form-urlencoded` before they are put into
+/// Basic authentication, RFC 6749 section 2.3.1
+fn form_decode(value: &str) -> Option<String> {
+    percent_decode_str(&value.replace('+', " "))
+        .decode_utf8()
+        .ok()
+        .map(|decoded| decoded.into_owned())
+}
+
+fn map_token_error(e: TokenError) -> OAuthError {
+    match e {
+        TokenError::InvalidRequest(description) => OAuthError {
+            description: Some(description),
+            ..OAuthError::new(StatusCode::BAD_REQUEST, "invalid_request")
+        },
+        TokenError::InvalidClient => OAuthError {
+            www_authenticate: Some("Basic"),
+            ..OAuthError::new(StatusCode::UNAUTHORIZED, "invalid_client")
+        },
+        TokenError::InvalidGrant => OAuthError::new(StatusCode::BAD_REQUEST, "invalid_grant"),
+        TokenError::UnauthorizedClient => {
+            OAuthError::new(StatusCode::BAD_REQUEST, "unauthorized_client")
+        }
+        TokenError::UnsupportedGrantType => {
+            OAuthError::new(StatusCode::BAD_REQUEST, "unsupported_grant_type")
+        }
+        TokenError::InvalidScope => OAuthError::new(StatusCode::BAD_REQUEST, "invalid_scope"),
+        TokenError::InternalError => {
+            OAuthError::new(StatusCode::INTERNAL_SERVER_ERROR, "server_error")
+        }
+    }
+}
+
+fn map_authorize_error(e: AuthorizeError) -> HttpError {
+    match e {
+        AuthorizeError::InvalidClient => HttpError::from("Unknown client"),
+        AuthorizeError::InvalidRedirectUri => {
+            HttpError::from("Redirect URI is not registered for the client")
+        }
+        AuthorizeError::Rejected(_) | AuthorizeError::InternalError => {
+            HttpError::from(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -0,0 +1,3 @@
+pub mod admin_handlers;
+pub mod api;
+pub mod handlers;
@@ -1,8 +1,42 @@
-use axum::response::IntoResponse;
+use axum::{response::IntoResponse, Json};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::services::credential_policy::PolicyViolation;
 
 pub struct HttpError {
     error_code: axum::http::StatusCode,
     message: Option<String>,
+    retry_after: Option<i64>,
+    errors: Vec<FieldErrorResponse>,
+}
+
+/// Body of `400` answers to a username or password that does not meet the policy
+#[derive(Serialize, ToSchema)]
+pub struct ValidationErrorResponse {
+    #[schema(example = "Validation failed")]
+    pub message: String,
+    #[schema()]
+    pub errors: Vec<FieldErrorResponse>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct FieldErrorResponse {
+    #[schema(example = "password")]
+    pub field: String,
+    /// `too_short`, `too_long`, `invalid_characters`, `too_simple` or `breached`
+    #[schema(example = "too_short")]
+    pub code: String,
+    #[schema(example = "Password must be at least 8 characters long")]
+    pub message: String,
+}
+
+impl HttpError {
+    /// Adds a `Retry-After` header with the given number of seconds.
+    pub fn retry_after(mut self, seconds: i64) -> Self {
+        self.retry_after = Some(seconds);
+        self
+    }
 }
 
 impl From<axum::http::StatusCode> for HttpError {
@@ -10,6 +44,8 @@ impl From<axum::http::StatusCode> for HttpError {
         HttpError {
             error_code: value,
             message: None,
+            retry_after: None,
+            errors: vec![],
         }
     }
 }
@@ -19,6 +55,8 @@ impl From<(axum::http::StatusCode, &str)> for HttpError {
         HttpError {
             error_code: value.0,
             message: Some(value.1.to_owned()),
+            retry_after: None,
+            errors: vec![],
         }
     }
 }
@@ -28,15 +66,49 @@ impl From<&str> for HttpError {
         HttpError {
             error_code: axum::http::StatusCode::BAD_REQUEST,
             message: Some(value.to_owned()),
+            retry_after: None,
+            errors: vec![],
+        }
+    }
+}
+
+impl From<Vec<PolicyViolation>> for HttpError {
+    fn from(value: Vec<PolicyViolation>) -> Self {
+        HttpError {
+            error_code: axum::http::StatusCode::BAD_REQUEST,
+            message: Some("Validation failed".to_owned()),
+            retry_after: None,
+            errors: value
+                .into_iter()
+                .map(|v| FieldErrorResponse {
+                    field: v.field.to_owned(),
+                    code: v.code.to_owned(),
+                    message: v.message,
+                })
+                .collect(),
         }
     }
 }
 
 impl IntoResponse for HttpError {
     fn into_response(self) -> axum::response::Response {
-        match self.message {
+        let mut response = match self.message {
+            Some(message) if !self.errors.is_empty() => (
+                self.error_code,
+                Json(ValidationErrorResponse {
+                    message,
+                    errors: self.errors,
+                }),
+            )
+                .into_response(),
             Some(message) => (self.error_code, message).into_response(),
             None => self.error_code.into_response(),
+        };
+        if let Some(seconds) = self.retry_after {
+            response
+                .headers_mut()
+                .insert(axum::http::header::RETRY_AFTER, seconds.into());
         }
+        response
     }
 }
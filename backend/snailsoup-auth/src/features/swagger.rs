@@ -7,10 +7,50 @@ use utoipa_rapidoc::RapiDoc;
 use utoipa_redoc::{Redoc, Servable};
 use utoipa_swagger_ui::SwaggerUi;
 
-use crate::features::user::admin_handlers::{__path_all_users, __path_user_by_id};
-use crate::features::user::handlers::__path_me;
+use crate::features::audit::admin_handlers::__path_audit_events;
+use crate::features::expense::admin_handlers::{__path_all_expenses, __path_expenses_by_user_id};
+use crate::features::expense::category_handlers::{
+    __path_category_by_id, __path_create_category, __path_delete_category, __path_my_categories,
+    __path_update_category,
+};
+use crate::features::expense::handlers::{
+    __path_create_expense, __path_expense_by_id, __path_my_expenses,
+};
+use crate::features::expense::tag_handlers::{
+    __path_create_tag, __path_delete_tag, __path_my_tags, __path_tag_by_id, __path_update_tag,
+};
+use crate::features::invite::admin_handlers::{
+    __path_all_invites, __path_create_invite, __path_delete_invite,
+};
+use crate::features::oauth::admin_handlers::{
+    __path_all_clients, __path_create_client, __path_delete_client,
+};
+use crate::features::oauth::handlers::{
+    __path_approve_authorization, __path_authorize, __path_introspect, __path_openid_configuration,
+    __path_token, __path_userinfo,
+};
+use crate::features::user::admin_handlers::{
+    __path_all_users, __path_approve_user, __path_change_role, __path_create_user,
+    __path_delete_user, __path_disable_user, __path_enable_user, __path_impersonate_user,
+    __path_remove_totp, __path_rename_user, __path_reset_password, __path_unlock_user,
+    __path_user_by_id,
+};
+use crate::features::user::api_token_handlers::{
+    __path_create_api_token, __path_my_api_tokens, __path_revoke_api_token,
+};
+use crate::features::user::handlers::{
+    __path_change_password, __path_delete_account, __path_export_account, __path_me,
+};
+use crate::features::user::session_handlers::{__path_my_sessions, __path_revoke_session};
+use crate::features::user::two_factor_handlers::{
+    __path_confirm_totp, __path_disable_totp, __path_enroll_totp, __path_regenerate_recovery_codes,
+};
 
-use crate::features::auth::handlers::{__path_login, __path_register};
+use crate::features::auth::handlers::{
+    __path_confirm_password_reset, __path_forgot_password, __path_jwks, __path_login,
+    __path_login_mfa, __path_logout, __path_logout_everywhere, __path_refresh, __path_register,
+    __path_resend_verification, __path_verify, __path_verify_email,
+};
 
 pub fn get_routes() -> Router {
     Router::new()
@@ -22,20 +62,94 @@ pub fn get_routes() -> Router {
 #[derive(OpenApi)]
 #[openapi(
             paths(
-                login, register, //Auth
-                all_users, user_by_id, //Admin - User
-                me //User
+                login, login_mfa, register, refresh, logout, logout_everywhere, jwks,
+                forgot_password, confirm_password_reset, verify, verify_email,
+                resend_verification, //Auth
+                all_users, user_by_id, create_user, change_role, rename_user, disable_user,
+                enable_user, approve_user, delete_user, reset_password, remove_totp, unlock_user, //Admin - User
+                impersonate_user, //Admin - Impersonation
+                all_invites, create_invite, delete_invite, //Admin - Invites
+                audit_events, //Admin - Audit
+                me, change_password, //User
+                export_account, delete_account, //User - Account
+                enroll_totp, confirm_totp, disable_totp, regenerate_recovery_codes, //User - 2FA
+                my_api_tokens, create_api_token, revoke_api_token, //User - API tokens
+                my_sessions, revoke_session, //User - Sessions
+                openid_configuration, authorize, approve_authorization, token, userinfo,
+                introspect, //OAuth
+                all_clients, create_client, delete_client, //Admin - OAuth
+                all_expenses, expenses_by_user_id, //Admin - Expenses
+                my_expenses, expense_by_id, create_expense, //Expenses
+                my_tags, tag_by_id, create_tag, update_tag, delete_tag, //Tags
+                my_categories, category_by_id, create_category, update_category, delete_category //Categories
             ),
             components(
                 schemas(
+                    super::response::ValidationErrorResponse,
+                    super::response::FieldErrorResponse,
                     super::user::api::UserResponse,
+                    super::user::api::UserPageResponse,
+                    super::user::api::ImpersonationResponse,
+                    super::user::api::ChangePasswordRequest,
+                    super::user::api::ResetPasswordRequest,
+                    super::user::api::CreateUserRequest,
+                    super::user::api::ChangeRoleRequest,
+                    super::user::api::RenameUserRequest,
+                    super::user::api::TotpCodeRequest,
+                    super::user::api::TotpEnrollmentResponse,
+                    super::user::api::RecoveryCodesResponse,
+                    super::user::api::CreateApiTokenRequest,
+                    super::user::api::ApiTokenResponse,
+                    super::user::api::CreatedApiTokenResponse,
+                    super::user::api::SessionResponse,
+                    super::user::api::DeleteAccountRequest,
+                    super::user::api::ExportedAccountResponse,
+                    super::user::api::AccountExportResponse,
+                    super::invite::api::CreateInviteRequest,
+                    super::invite::api::InviteResponse,
+                    super::invite::api::CreatedInviteResponse,
+                    super::audit::api::AuditEventResponse,
+                    super::oauth::api::AuthorizeQuery,
+                    super::oauth::api::AuthorizeResponse,
+                    super::oauth::api::TokenRequest,
+                    super::oauth::api::TokenResponse,
+                    super::oauth::api::OAuthErrorResponse,
+                    super::oauth::api::IntrospectionRequest,
+                    super::oauth::api::IntrospectionResponse,
+                    super::oauth::api::ActorResponse,
+                    super::oauth::api::UserInfoResponse,
+                    super::oauth::api::OpenIdConfigurationResponse,
+                    super::oauth::api::CreateClientRequest,
+                    super::oauth::api::ClientResponse,
+                    super::oauth::api::CreatedClientResponse,
                     super::auth::api::LoginRequest,
-                    super::auth::api::RegisterRequest
+                    super::auth::api::RegisterRequest,
+                    super::auth::api::RefreshRequest,
+                    super::auth::api::LogoutRequest,
+                    super::auth::api::ForgotPasswordRequest,
+                    super::auth::api::ConfirmPasswordResetRequest,
+                    super::auth::api::VerifyEmailRequest,
+                    super::auth::api::ResendVerificationRequest,
+                    super::auth::api::LoginResponse,
+                    super::auth::api::SessionResponse,
+                    super::auth::api::AuthResponse,
+                    super::auth::api::MfaLoginRequest,
+                    super::auth::api::MfaRequiredResponse,
+                    super::expense::api::CreateExpenseRequest,
+                    super::expense::api::TagRequest,
+                    super::expense::api::CategoryRequest,
+                    super::expense::api::ExpenseResponse,
+                    super::expense::api::FullExpenseResponse,
+                    super::expense::api::TagResponse,
+                    super::expense::api::CategoryResponse
                 )
             ),
             modifiers(&SecurityAddon),
             tags(
-                (name = "Expenses", description = "Expense CRUD")
+                (name = "Expenses", description = "Expense CRUD"),
+                (name = "Tags", description = "User tags CRUD"),
+                (name = "Categories", description = "User categories CRUD"),
+                (name = "OAuth", description = "OAuth 2.0 authorization server and OpenID Connect provider")
             )
         )]
 struct ApiDoc;
@@ -1,15 +1,36 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
-    Json,
+    Extension, Json,
 };
 use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::services::user::UserService;
+use crate::{
+    domain::{
+        app_user::AppUser,
+        audit_event::{AuditAction, AuditOutcome},
+    },
+    features::response::{HttpError, ValidationErrorResponse},
+    services::{
+        audit::{AuditRecord, AuditService, RequestOrigin},
+        auth::{AuthService, ImpersonationError, PasswordChangeError},
+        email_verification::EmailVerificationService,
+        login_throttle::{LoginThrottleService, UnlockError},
+        two_factor::{TwoFactorError, TwoFactorService},
+        user::{UserError, UserService},
+    },
+    utils::user_query::{SortOrder, UserCursor, UserFilter, UserQuery, UserSort},
+};
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 200;
 
-use super::api::UserResponse;
+use super::api::{
+    ChangeRoleRequest, CreateUserRequest, ImpersonationResponse, RenameUserRequest,
+    ResetPasswordRequest, UserListQuery, UserPageResponse, UserResponse,
+};
 
 #[utoipa::path(
     get,
@@ -25,12 +46,28 @@ use super::api::UserResponse;
     security(("BearerToken" = []))
 )]
 pub(super) async fn user_by_id(
+    Extension(user): Extension<AppUser>,
+    origin: RequestOrigin,
     Path(user_id): Path<Uuid>,
     service: State<Arc<UserService>>,
+    State(audit_service): State<Arc<AuditService>>,
 ) -> Result<impl IntoResponse, StatusCode> {
-    let user = service.get(user_id).await;
+    let found = service.get(user_id).await;
 
-    match user {
+    let outcome = match found {
+        Some(_) => AuditOutcome::Success,
+        None => AuditOutcome::Failure,
+    };
+    audit_service
+        .record(
+            &origin,
+            AuditRecord::new(AuditAction::UserRead, outcome)
+                .actor(&user)
+                .target(user_id),
+        )
+        .await;
+
+    match found {
         Some(u) => Ok(Json(UserResponse::from_user(u))),
         None => Err(StatusCode::NOT_FOUND),
     }
@@ -41,17 +78,537 @@ pub(super) async fn user_by_id(
     path = "/api/admin/users",
     tag = "Users - Admin",
     responses(
-        (status = StatusCode::OK, description = "list users successfully", body = [UserResponse])
+        (status = StatusCode::OK, description = "list users successfully", body = UserPageResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Invalid filter, sort order or cursor")
+    ),
+    params(UserListQuery),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn all_users(
+    Query(query): Query<UserListQuery>,
+    State(service): State<Arc<UserService>>,
+) -> Result<impl IntoResponse, HttpError> {
+    let role = match query.role {
+        Some(role) => Some(service.find_role(&role).await.map_err(map_user_error)?),
+        None => None,
+    };
+
+    let sort = match query.sort.as_deref() {
+        None | Some("username") => UserSort::Username,
+        Some("created_at") => UserSort::CreatedAt,
+        Some(_) => Err(HttpError::from("Sort must be username or created_at"))?,
+    };
+
+    let order = match query.order.as_deref() {
+        None | Some("asc") => SortOrder::Ascending,
+        Some("desc") => SortOrder::Descending,
+        Some(_) => Err(HttpError::from("Order must be asc or desc"))?,
+    };
+
+    let after = match query.cursor.as_deref() {
+        None => None,
+        Some(cursor) => Some(
+            UserCursor::decode(cursor)
+                .filter(|cursor| cursor.sort() == sort)
+                .ok_or(HttpError::from("Invalid cursor"))?,
+        ),
+    };
+
+    let page = service
+        .list(UserQuery {
+            filter: UserFilter {
+                role,
+                username_contains: query.username.filter(|u| !u.is_empty()),
+                pending_approval: query.pending_approval,
+            },
+            sort,
+            order,
+            after,
+            limit: query
+                .limit
+                .unwrap_or(DEFAULT_PAGE_SIZE)
+                .clamp(1, MAX_PAGE_SIZE),
+        })
+        .await
+        .map_err(map_user_error)?;
+
+    Ok(Json(UserPageResponse {
+        users: page
+            .users
+            .into_iter()
+            .map(UserResponse::from_user)
+            .collect(),
+        total: page.total,
+        next_cursor: page.next_cursor.map(|cursor| cursor.encode()),
+    }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/admin/users/{user_id}/password",
+    tag = "Users - Admin",
+    request_body = ResetPasswordRequest,
+    responses(
+        (status = StatusCode::NO_CONTENT, description = "Password reset and existing tokens revoked"),
+        (status = StatusCode::BAD_REQUEST, description = "Password does not meet the policy", body = ValidationErrorResponse),
+        (status = StatusCode::NOT_FOUND, description = "User not found")
+    ),
+    params(
+        ("user_id" = Uuid, Path, description = "User database id to reset password for"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn reset_password(
+    Extension(user): Extension<AppUser>,
+    origin: RequestOrigin,
+    Path(user_id): Path<Uuid>,
+    State(service): State<Arc<AuthService>>,
+    State(audit_service): State<Arc<AuditService>>,
+    Json(body): Json<ResetPasswordRequest>,
+) -> Result<impl IntoResponse, HttpError> {
+    let result = service.reset_password(user_id, &body.new_password).await;
+
+    audit_service
+        .record(
+            &origin,
+            AuditRecord::from_result(AuditAction::UserPasswordReset, &result)
+                .actor(&user)
+                .target(user_id),
+        )
+        .await;
+
+    result
+        .map_err(|e| match e {
+            PasswordChangeError::UserDoesNotExist => HttpError::from(StatusCode::NOT_FOUND),
+            PasswordChangeError::PolicyViolation(violations) => HttpError::from(violations),
+            PasswordChangeError::IncorrectPassword | PasswordChangeError::InternalError => {
+                HttpError::from(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        })
+        .map(|_| StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/admin/users/{user_id}/totp",
+    tag = "Users - Admin",
+    responses(
+        (status = StatusCode::NO_CONTENT, description = "Two-factor authentication disabled"),
+        (status = StatusCode::NOT_FOUND, description = "User has no authenticator")
+    ),
+    params(
+        ("user_id" = Uuid, Path, description = "User database id to disable two-factor authentication for"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn remove_totp(
+    Extension(user): Extension<AppUser>,
+    origin: RequestOrigin,
+    Path(user_id): Path<Uuid>,
+    State(service): State<Arc<TwoFactorService>>,
+    State(audit_service): State<Arc<AuditService>>,
+) -> Result<impl IntoResponse, HttpError> {
+    let result = service.remove(user_id).await;
+
+    audit_service
+        .record(
+            &origin,
+            AuditRecord::from_result(AuditAction::UserTotpRemove, &result)
+                .actor(&user)
+                .target(user_id),
+        )
+        .await;
+
+    result
+        .map_err(|e| match e {
+            TwoFactorError::NotEnrolled => HttpError::from(StatusCode::NOT_FOUND),
+            TwoFactorError::AlreadyEnabled
+            | TwoFactorError::InvalidCode
+            | TwoFactorError::InternalError => HttpError::from(StatusCode::INTERNAL_SERVER_ERROR),
+        })
+        .map(|_| StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/admin/users/{user_id}/unlock",
+    tag = "Users - Admin",
+    responses(
+        (status = StatusCode::NO_CONTENT, description = "Failed login attempts cleared and lockout lifted"),
+        (status = StatusCode::NOT_FOUND, description = "User not found")
+    ),
+    params(
+        ("user_id" = Uuid, Path, description = "User database id to unlock"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn unlock_user(
+    Extension(user): Extension<AppUser>,
+    origin: RequestOrigin,
+    Path(user_id): Path<Uuid>,
+    State(service): State<Arc<LoginThrottleService>>,
+    State(audit_service): State<Arc<AuditService>>,
+) -> Result<impl IntoResponse, HttpError> {
+    let result = service.unlock(user_id).await;
+
+    audit_service
+        .record(
+            &origin,
+            AuditRecord::from_result(AuditAction::UserUnlock, &result)
+                .actor(&user)
+                .target(user_id),
+        )
+        .await;
+
+    result
+        .map_err(|e| match e {
+            UnlockError::UserDoesNotExist => HttpError::from(StatusCode::NOT_FOUND),
+            UnlockError::InternalError => HttpError::from(StatusCode::INTERNAL_SERVER_ERROR),
+        })
+        .map(|_| StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/admin/users",
+    tag = "Users - Admin",
+    request_body = CreateUserRequest,
+    responses(
+        (status = StatusCode::CREATED, description = "User created", body = UserResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Unknown role, username or email already in use, or username or password not meeting the policy", body = ValidationErrorResponse)
     ),
     security(("BearerToken" = []))
 )]
-pub(super) async fn all_users(service: State<Arc<UserService>>) -> impl IntoResponse {
-    let users: Vec<UserResponse> = service
-        .get_all()
+pub(super) async fn create_user(
+    Extension(user): Extension<AppUser>,
+    origin: RequestOrigin,
+    State(service): State<Arc<UserService>>,
+    State(verification_service): State<Arc<EmailVerificationService>>,
+    State(audit_service): State<Arc<AuditService>>,
+    Json(body): Json<CreateUserRequest>,
+) -> Result<impl IntoResponse, HttpError> {
+    let result = service
+        .create(
+            &body.username,
+            &body.password,
+            body.email.as_deref(),
+            &body.account_role,
+        )
+        .await;
+
+    let mut record = AuditRecord::from_result(AuditAction::UserCreate, &result)
+        .actor(&user)
+        .detail(&body.username);
+    if let Ok(created) = &result {
+        record = record.target(created.id);
+    }
+    audit_service.record(&origin, record).await;
+
+    let created = result.map_err(map_user_error)?;
+
+    if verification_service
+        .send_verification(&created)
         .await
-        .into_iter()
-        .map(|e| UserResponse::from_user(e))
-        .collect();
+        .is_err()
+    {
+        println!("Cannot create verification token for {}", created.username);
+    }
+
+    Ok((StatusCode::CREATED, Json(UserResponse::from_user(created))))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/admin/users/{user_id}/role",
+    tag = "Users - Admin",
+    request_body = ChangeRoleRequest,
+    responses(
+        (status = StatusCode::NO_CONTENT, description = "Role changed"),
+        (status = StatusCode::BAD_REQUEST, description = "Unknown role"),
+        (status = StatusCode::FORBIDDEN, description = "Administrators cannot change their own role"),
+        (status = StatusCode::NOT_FOUND, description = "User not found")
+    ),
+    params(
+        ("user_id" = Uuid, Path, description = "User database id to change role for"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn change_role(
+    Extension(user): Extension<AppUser>,
+    origin: RequestOrigin,
+    Path(user_id): Path<Uuid>,
+    State(service): State<Arc<UserService>>,
+    State(audit_service): State<Arc<AuditService>>,
+    Json(body): Json<ChangeRoleRequest>,
+) -> Result<impl IntoResponse, HttpError> {
+    let result = service
+        .change_role(&user, user_id, &body.account_role)
+        .await;
+
+    audit_service
+        .record(
+            &origin,
+            AuditRecord::from_result(AuditAction::UserRoleChange, &result)
+                .actor(&user)
+                .target(user_id)
+                .detail(&body.account_role),
+        )
+        .await;
+
+    result
+        .map_err(map_user_error)
+        .map(|_| StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/admin/users/{user_id}/username",
+    tag = "Users - Admin",
+    request_body = RenameUserRequest,
+    responses(
+        (status = StatusCode::NO_CONTENT, description = "User renamed"),
+        (status = StatusCode::BAD_REQUEST, description = "Username already in use or not meeting the policy", body = ValidationErrorResponse),
+        (status = StatusCode::NOT_FOUND, description = "User not found")
+    ),
+    params(
+        ("user_id" = Uuid, Path, description = "User database id to rename"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn rename_user(
+    Extension(user): Extension<AppUser>,
+    origin: RequestOrigin,
+    Path(user_id): Path<Uuid>,
+    State(service): State<Arc<UserService>>,
+    State(audit_service): State<Arc<AuditService>>,
+    Json(body): Json<RenameUserRequest>,
+) -> Result<impl IntoResponse, HttpError> {
+    let result = service.rename(user_id, &body.username).await;
+
+    audit_service
+        .record(
+            &origin,
+            AuditRecord::from_result(AuditAction::UserRename, &result)
+                .actor(&user)
+                .target(user_id)
+                .detail(&body.username),
+        )
+        .await;
+
+    result
+        .map_err(map_user_error)
+        .map(|_| StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/admin/users/{user_id}/approve",
+    tag = "Users - Admin",
+    responses(
+        (status = StatusCode::NO_CONTENT, description = "Account approved, the user can log in"),
+        (status = StatusCode::NOT_FOUND, description = "User not found")
+    ),
+    params(
+        ("user_id" = Uuid, Path, description = "User database id to approve"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn approve_user(
+    Extension(user): Extension<AppUser>,
+    origin: RequestOrigin,
+    Path(user_id): Path<Uuid>,
+    State(service): State<Arc<UserService>>,
+    State(audit_service): State<Arc<AuditService>>,
+) -> Result<impl IntoResponse, HttpError> {
+    let result = service.approve(&user, user_id).await;
+
+    audit_service
+        .record(
+            &origin,
+            AuditRecord::from_result(AuditAction::UserApprove, &result)
+                .actor(&user)
+                .target(user_id),
+        )
+        .await;
+
+    result
+        .map_err(map_user_error)
+        .map(|_| StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/admin/users/{user_id}/disable",
+    tag = "Users - Admin",
+    responses(
+        (status = StatusCode::NO_CONTENT, description = "Account disabled and existing tokens revoked"),
+        (status = StatusCode::FORBIDDEN, description = "Administrators cannot disable their own account"),
+        (status = StatusCode::NOT_FOUND, description = "User not found")
+    ),
+    params(
+        ("user_id" = Uuid, Path, description = "User database id to disable"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn disable_user(
+    Extension(user): Extension<AppUser>,
+    origin: RequestOrigin,
+    Path(user_id): Path<Uuid>,
+    State(service): State<Arc<UserService>>,
+    State(audit_service): State<Arc<AuditService>>,
+) -> Result<impl IntoResponse, HttpError> {
+    let result = service.disable(&user, user_id).await;
+
+    audit_service
+        .record(
+            &origin,
+            AuditRecord::from_result(AuditAction::UserDisable, &result)
+                .actor(&user)
+                .target(user_id),
+        )
+        .await;
+
+    result
+        .map_err(map_user_error)
+        .map(|_| StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/admin/users/{user_id}/enable",
+    tag = "Users - Admin",
+    responses(
+        (status = StatusCode::NO_CONTENT, description = "Account enabled"),
+        (status = StatusCode::NOT_FOUND, description = "User not found")
+    ),
+    params(
+        ("user_id" = Uuid, Path, description = "User database id to enable"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn enable_user(
+    Extension(user): Extension<AppUser>,
+    origin: RequestOrigin,
+    Path(user_id): Path<Uuid>,
+    State(service): State<Arc<UserService>>,
+    State(audit_service): State<Arc<AuditService>>,
+) -> Result<impl IntoResponse, HttpError> {
+    let result = service.enable(user_id).await;
+
+    audit_service
+        .record(
+            &origin,
+            AuditRecord::from_result(AuditAction::UserEnable, &result)
+                .actor(&user)
+                .target(user_id),
+        )
+        .await;
+
+    result
+        .map_err(map_user_error)
+        .map(|_| StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/admin/users/{user_id}",
+    tag = "Users - Admin",
+    responses(
+        (status = StatusCode::NO_CONTENT, description = "User deleted together with all of their data"),
+        (status = StatusCode::FORBIDDEN, description = "Administrators cannot delete their own account"),
+        (status = StatusCode::NOT_FOUND, description = "User not found")
+    ),
+    params(
+        ("user_id" = Uuid, Path, description = "User database id to delete"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn delete_user(
+    Extension(user): Extension<AppUser>,
+    origin: RequestOrigin,
+    Path(user_id): Path<Uuid>,
+    State(service): State<Arc<UserService>>,
+    State(audit_service): State<Arc<AuditService>>,
+) -> Result<impl IntoResponse, HttpError> {
+    let result = service.delete(&user, user_id).await;
+
+    audit_service
+        .record(
+            &origin,
+            AuditRecord::from_result(AuditAction::UserDelete, &result)
+                .actor(&user)
+                .target(user_id),
+        )
+        .await;
+
+    result
+        .map_err(map_user_error)
+        .map(|_| StatusCode::NO_CONTENT)
+}
+
+fn map_user_error(e: UserError) -> HttpError {
+    match e {
+        UserError::UserDoesNotExist => HttpError::from(StatusCode::NOT_FOUND),
+        UserError::UsernameInUse => HttpError::from("Username already in use"),
+        UserError::EmailInUse => HttpError::from("Email already in use"),
+        UserError::PolicyViolation(violations) => HttpError::from(violations),
+        UserError::UnknownRole(role) => HttpError::from(format!("Unknown role {}", role).as_str()),
+        UserError::OwnAccount => HttpError::from((
+            StatusCode::FORBIDDEN,
+            "Cannot perform this action on your own account",
+        )),
+        UserError::InternalError => HttpError::from(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/admin/users/{user_id}/impersonate",
+    tag = "Users - Admin",
+    responses(
+        (status = StatusCode::OK, description = "Short-lived access token acting as the user", body = ImpersonationResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Own account, an account holding users:impersonate or a disabled account"),
+        (status = StatusCode::NOT_FOUND, description = "User not found")
+    ),
+    params(
+        ("user_id" = Uuid, Path, description = "User database id to impersonate"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn impersonate_user(
+    Extension(user): Extension<AppUser>,
+    origin: RequestOrigin,
+    Path(user_id): Path<Uuid>,
+    State(service): State<Arc<AuthService>>,
+    State(audit_service): State<Arc<AuditService>>,
+) -> Result<impl IntoResponse, HttpError> {
+    let result = service.impersonate(&user, user_id).await;
+
+    let record = match &result {
+        Ok(_) => AuditRecord::new(AuditAction::UserImpersonate, AuditOutcome::Success),
+        Err(e) => {
+            AuditRecord::new(AuditAction::UserImpersonate, AuditOutcome::Failure).detail(match e {
+                ImpersonationError::OwnAccount => "own_account",
+                ImpersonationError::ProtectedAccount => "protected_account",
+                ImpersonationError::UserDisabled => "user_disabled",
+                ImpersonationError::UserDoesNotExist => "user_does_not_exist",
+                ImpersonationError::InternalError => "internal_error",
+            })
+        }
+    };
+    audit_service
+        .record(&origin, record.actor(&user).target(user_id))
+        .await;
 
-    Json(users)
+    result
+        .map_err(|e| match e {
+            ImpersonationError::OwnAccount => HttpError::from("Cannot impersonate yourself"),
+            ImpersonationError::ProtectedAccount => {
+                HttpError::from("Accounts that may impersonate others cannot be impersonated")
+            }
+            ImpersonationError::UserDisabled => HttpError::from("Account is disabled"),
+            ImpersonationError::UserDoesNotExist => HttpError::from(StatusCode::NOT_FOUND),
+            ImpersonationError::InternalError => HttpError::from(StatusCode::INTERNAL_SERVER_ERROR),
+        })
+        .map(|token| Json(ImpersonationResponse::from_token(token)))
 }
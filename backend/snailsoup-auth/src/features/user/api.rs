@@ -1,24 +1,108 @@
-use axum::{routing::get, Router};
+use axum::{
+    middleware::{from_fn, from_fn_with_state},
+    routing::{delete, get, post, put},
+    Router,
+};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
-use crate::{app_state::AppState, domain::app_user::AppUser};
+use crate::{
+    app_state::AppState,
+    domain::{api_token::ApiToken, app_user::AppUser, session::Session},
+    features::{
+        auth::middleware::{require_permission, require_scope, require_session},
+        expense::api::{CategoryResponse, FullExpenseResponse, TagResponse},
+    },
+    services::{account::AccountExport, auth::ImpersonationToken, two_factor::TotpEnrollment},
+};
 
-use super::admin_handlers::{all_users, user_by_id};
-use super::handlers::me;
+use super::admin_handlers::{
+    all_users, approve_user, change_role, create_user, delete_user, disable_user, enable_user,
+    impersonate_user, remove_totp, rename_user, reset_password, unlock_user, user_by_id,
+};
+use super::api_token_handlers::{create_api_token, my_api_tokens, revoke_api_token};
+use super::handlers::{change_password, delete_account, export_account, me};
+use super::session_handlers::{my_sessions, revoke_session};
+use super::two_factor_handlers::{
+    confirm_totp, disable_totp, enroll_totp, regenerate_recovery_codes,
+};
 
 pub fn get_admin_routes(app_state: AppState) -> Router {
-    Router::new()
+    let read_routes = Router::new()
         .route("/api/admin/users", get(all_users))
         .route("/api/admin/users/:user_id", get(user_by_id))
+        .route_layer(from_fn_with_state(
+            app_state.clone(),
+            require_permission("users:read"),
+        ));
+
+    let write_routes = Router::new()
+        .route("/api/admin/users", post(create_user))
+        .route("/api/admin/users/:user_id/role", put(change_role))
+        .route("/api/admin/users/:user_id/username", put(rename_user))
+        .route("/api/admin/users/:user_id/approve", post(approve_user))
+        .route("/api/admin/users/:user_id/disable", post(disable_user))
+        .route("/api/admin/users/:user_id/enable", post(enable_user))
+        .route("/api/admin/users/:user_id/password", put(reset_password))
+        .route("/api/admin/users/:user_id/totp", delete(remove_totp))
+        .route("/api/admin/users/:user_id/unlock", post(unlock_user))
+        .route_layer(from_fn_with_state(
+            app_state.clone(),
+            require_permission("users:write"),
+        ));
+
+    let delete_routes = Router::new()
+        .route("/api/admin/users/:user_id", delete(delete_user))
+        .route_layer(from_fn_with_state(
+            app_state.clone(),
+            require_permission("users:delete"),
+        ));
+
+    let impersonate_routes = Router::new()
+        .route(
+            "/api/admin/users/:user_id/impersonate",
+            post(impersonate_user),
+        )
+        .route_layer(from_fn_with_state(
+            app_state.clone(),
+            require_permission("users:impersonate"),
+        ))
+        .route_layer(from_fn(require_session));
+
+    read_routes
+        .merge(write_routes)
+        .merge(delete_routes)
+        .merge(impersonate_routes)
         .with_state(app_state)
 }
 
 pub fn get_private_routes(app_state: AppState) -> Router {
-    Router::new()
+    let profile_routes = Router::new()
         .route("/api/users/me", get(me))
-        .with_state(app_state)
+        .route_layer(from_fn(require_scope("profile:read")));
+
+    let account_routes = Router::new()
+        .route("/api/users/me", delete(delete_account))
+        .route("/api/users/me/export", get(export_account))
+        .route("/api/users/me/password", put(change_password))
+        .route("/api/users/me/totp", post(enroll_totp).delete(disable_totp))
+        .route("/api/users/me/totp/confirm", post(confirm_totp))
+        .route(
+            "/api/users/me/recovery-codes",
+            post(regenerate_recovery_codes),
+        )
+        .route(
+            "/api/users/me/tokens",
+            get(my_api_tokens).post(create_api_token),
+        )
+        .route("/api/users/me/tokens/:token_id", delete(revoke_api_token))
+        .route("/api/users/me/sessions", get(my_sessions))
+        .route("/api/users/me/sessions/:session_id", delete(revoke_session))
+        .route_layer(from_fn(require_session));
+
+    profile_routes.merge(account_routes).with_state(app_state)
 }
 
 #[derive(Serialize, Deserialize, ToSchema, Clone)]
@@ -29,6 +113,16 @@ pub struct UserResponse {
     pub username: String,
     #[schema()]
     pub account_role: String,
+    #[schema()]
+    pub email: Option<String>,
+    #[schema()]
+    pub email_verified_at: Option<DateTime<Utc>>,
+    /// Set while the account is disabled
+    #[schema()]
+    pub disabled_at: Option<DateTime<Utc>>,
+    /// Set until an admin approves an account registered in approval mode
+    #[schema()]
+    pub pending_approval: bool,
 }
 
 impl UserResponse {
@@ -36,7 +130,286 @@ impl UserResponse {
         UserResponse {
             id: user.id,
             username: user.username,
-            account_role: user.account_role,
+            account_role: user.account_role.to_string(),
+            email: user.email,
+            email_verified_at: user.email_verified_at,
+            disabled_at: user.disabled_at,
+            pending_approval: user.pending_approval,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct UserPageResponse {
+    #[schema()]
+    pub users: Vec<UserResponse>,
+    /// Number of users matching the filters across all pages
+    #[schema()]
+    pub total: i64,
+    /// Pass as `cursor` to get the next page, absent on the last page
+    #[schema()]
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct UserListQuery {
+    /// Only users with this role, `Admin` or `User`
+    pub role: Option<String>,
+    /// Only users whose username contains this text, ignoring case
+    pub username: Option<String>,
+    /// Only accounts awaiting (`true`) or not awaiting (`false`) approval
+    pub pending_approval: Option<bool>,
+    /// `username` (default) or `created_at`
+    pub sort: Option<String>,
+    /// `asc` (default) or `desc`
+    pub order: Option<String>,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
+    /// Page size, 50 by default and at most 200
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ChangePasswordRequest {
+    #[schema()]
+    pub old_password: String,
+    #[schema()]
+    pub new_password: String,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportQuery {
+    /// `json` (default) or `csv` for a zip archive of CSV files
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DeleteAccountRequest {
+    /// Current password, confirming the deletion
+    #[schema()]
+    pub password: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ExportedAccountResponse {
+    #[schema()]
+    pub id: Uuid,
+    #[schema()]
+    pub username: String,
+    #[schema()]
+    pub email: Option<String>,
+    #[schema()]
+    pub email_verified_at: Option<DateTime<Utc>>,
+    #[schema()]
+    pub account_role: String,
+    #[schema()]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AccountExportResponse {
+    #[schema()]
+    pub exported_at: DateTime<Utc>,
+    #[schema()]
+    pub account: ExportedAccountResponse,
+    #[schema()]
+    pub tags: Vec<TagResponse>,
+    #[schema()]
+    pub categories: Vec<CategoryResponse>,
+    #[schema()]
+    pub expenses: Vec<FullExpenseResponse>,
+}
+
+impl AccountExportResponse {
+    pub fn from_export(export: AccountExport) -> AccountExportResponse {
+        AccountExportResponse {
+            exported_at: export.exported_at,
+            account: ExportedAccountResponse {
+                id: export.user.id,
+                username: export.user.username,
+                email: export.user.email,
+                email_verified_at: export.user.email_verified_at,
+                account_role: export.user.account_role.to_string(),
+                created_at: export.user.created_at,
+            },
+            tags: export.tags.into_iter().map(TagResponse::from_tag).collect(),
+            categories: export
+                .categories
+                .into_iter()
+                .map(CategoryResponse::from_category)
+                .collect(),
+            expenses: export
+                .expenses
+                .into_iter()
+                .map(FullExpenseResponse::from_full_expense)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ResetPasswordRequest {
+    #[schema()]
+    pub new_password: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateUserRequest {
+    #[schema()]
+    pub username: String,
+    #[schema()]
+    pub password: String,
+    #[schema()]
+    pub email: Option<String>,
+    /// `Admin` or `User`
+    #[schema(example = "User")]
+    pub account_role: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ChangeRoleRequest {
+    /// `Admin` or `User`
+    #[schema(example = "Admin")]
+    pub account_role: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RenameUserRequest {
+    #[schema()]
+    pub username: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TotpCodeRequest {
+    /// Code from the authenticator app; recovery codes are accepted too once enabled
+    #[schema()]
+    pub code: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct TotpEnrollmentResponse {
+    /// Base32 encoded secret for manual entry
+    #[schema()]
+    pub secret: String,
+    /// `otpauth://` URI, usually rendered as a QR code
+    #[schema()]
+    pub otpauth_uri: String,
+}
+
+impl TotpEnrollmentResponse {
+    pub fn from_enrollment(enrollment: TotpEnrollment) -> TotpEnrollmentResponse {
+        TotpEnrollmentResponse {
+            secret: enrollment.secret,
+            otpauth_uri: enrollment.otpauth_uri,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RecoveryCodesResponse {
+    /// Single-use codes replacing the authenticator app, shown only once
+    #[schema()]
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateApiTokenRequest {
+    /// Helps to tell tokens apart, e.g. the name of the script using it
+    #[schema()]
+    pub name: String,
+    /// Any of `profile:read`, `expenses:read`, `expenses:write`, `users:read`, `users:write`, `users:delete`,
+    /// `clients:read`, `clients:write`, `invites:read`, `invites:write`, `audit:read`
+    #[schema(example = json!(["expenses:read"]))]
+    pub scopes: Vec<String>,
+    /// The token never expires when omitted
+    #[schema()]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ApiTokenResponse {
+    #[schema()]
+    pub id: Uuid,
+    #[schema()]
+    pub name: String,
+    #[schema()]
+    pub scopes: Vec<String>,
+    #[schema()]
+    pub created_at: DateTime<Utc>,
+    #[schema()]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[schema()]
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl ApiTokenResponse {
+    pub fn from_api_token(api_token: ApiToken) -> ApiTokenResponse {
+        ApiTokenResponse {
+            id: api_token.id,
+            name: api_token.name,
+            scopes: api_token.scopes,
+            created_at: api_token.created_at,
+            expires_at: api_token.expires_at,
+            last_used_at: api_token.last_used_at,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CreatedApiTokenResponse {
+    /// Send as `Authorization: Bearer <token>`. Shown only once, it cannot be retrieved later
+    #[schema()]
+    pub token: String,
+    #[schema()]
+    pub api_token: ApiTokenResponse,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SessionResponse {
+    #[schema()]
+    pub id: Uuid,
+    #[schema(example = "Firefox on Linux")]
+    pub device_name: String,
+    /// Client address at login
+    #[schema()]
+    pub ip: Option<String>,
+    #[schema()]
+    pub created_at: DateTime<Utc>,
+    #[schema()]
+    pub last_seen_at: DateTime<Utc>,
+    /// Whether the request listing the sessions was made with this session
+    #[schema()]
+    pub current: bool,
+}
+
+impl SessionResponse {
+    pub fn from_session(session: Session, current_session_id: Option<&str>) -> SessionResponse {
+        SessionResponse {
+            current: current_session_id == Some(session.id.to_string().as_str()),
+            id: session.id,
+            device_name: session.device_name,
+            ip: session.ip,
+            created_at: session.created_at,
+            last_seen_at: session.last_seen_at,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ImpersonationResponse {
+    /// Access token acting as the user, carrying the admin in its `act` claim
+    #[schema()]
+    pub access_token: String,
+    #[schema()]
+    pub expires_at: DateTime<Utc>,
+}
+
+impl ImpersonationResponse {
+    pub fn from_token(token: ImpersonationToken) -> ImpersonationResponse {
+        ImpersonationResponse {
+            access_token: token.access_token,
+            expires_at: token.expires_at,
         }
     }
 }
@@ -0,0 +1,112 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    domain::app_user::AppUser,
+    features::response::HttpError,
+    services::api_token::{ApiTokenError, ApiTokenService},
+};
+
+use super::api::{ApiTokenResponse, CreateApiTokenRequest, CreatedApiTokenResponse};
+
+#[utoipa::path(
+    get,
+    path = "/api/users/me/tokens",
+    tag = "Users",
+    responses(
+        (status = StatusCode::OK, description = "API tokens of the user, without their secret", body = [ApiTokenResponse]),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn my_api_tokens(
+    Extension(user): Extension<AppUser>,
+    State(service): State<Arc<ApiTokenService>>,
+) -> Result<impl IntoResponse, HttpError> {
+    service
+        .list(user.id)
+        .await
+        .map_err(map_api_token_error)
+        .map(|tokens| {
+            Json(
+                tokens
+                    .into_iter()
+                    .map(ApiTokenResponse::from_api_token)
+                    .collect::<Vec<ApiTokenResponse>>(),
+            )
+        })
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/users/me/tokens",
+    tag = "Users",
+    request_body = CreateApiTokenRequest,
+    responses(
+        (status = StatusCode::CREATED, description = "Token created, the secret is not shown again", body = CreatedApiTokenResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Missing name or scopes, unknown scope or expiry in the past"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn create_api_token(
+    Extension(user): Extension<AppUser>,
+    State(service): State<Arc<ApiTokenService>>,
+    Json(body): Json<CreateApiTokenRequest>,
+) -> Result<impl IntoResponse, HttpError> {
+    service
+        .create(user.id, &body.name, body.scopes, body.expires_at)
+        .await
+        .map_err(map_api_token_error)
+        .map(|(api_token, token)| {
+            (
+                StatusCode::CREATED,
+                Json(CreatedApiTokenResponse {
+                    token,
+                    api_token: ApiTokenResponse::from_api_token(api_token),
+                }),
+            )
+        })
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/users/me/tokens/{token_id}",
+    tag = "Users",
+    responses(
+        (status = StatusCode::NO_CONTENT, description = "Token revoked"),
+        (status = StatusCode::NOT_FOUND, description = "Token not found"),
+    ),
+    params(
+        ("token_id" = Uuid, Path, description = "Id of the API token to revoke"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn revoke_api_token(
+    Extension(user): Extension<AppUser>,
+    Path(token_id): Path<Uuid>,
+    State(service): State<Arc<ApiTokenService>>,
+) -> Result<impl IntoResponse, HttpError> {
+    service
+        .revoke(user.id, token_id)
+        .await
+        .map_err(map_api_token_error)
+        .map(|_| StatusCode::NO_CONTENT)
+}
+
+fn map_api_token_error(e: ApiTokenError) -> HttpError {
+    match e {
+        ApiTokenError::MissingName => HttpError::from("Token name must not be empty"),
+        ApiTokenError::MissingScopes => HttpError::from("At least one scope is required"),
+        ApiTokenError::UnknownScope(scope) => {
+            HttpError::from(format!("Unknown scope {}", scope).as_str())
+        }
+        ApiTokenError::ExpiryInPast => HttpError::from("Expiry date must be in the future"),
+        ApiTokenError::TokenDoesNotExist => HttpError::from(StatusCode::NOT_FOUND),
+        ApiTokenError::InternalError => HttpError::from(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
@@ -0,0 +1,84 @@
+use std::io::{Cursor, Write};
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+use super::api::AccountExportResponse;
+
+/// Zip archive with one CSV file per kind of data in the export. Tag ids of an expense are
+/// separated by spaces.
+pub fn csv_zip(export: &AccountExportResponse) -> Result<Vec<u8>, std::io::Error> {
+    let mut account = csv::Writer::from_writer(vec![]);
+    account.write_record([
+        "id",
+        "username",
+        "email",
+        "email_verified_at",
+        "account_role",
+        "created_at",
+    ])?;
+    account.write_record([
+        export.account.id.to_string(),
+        export.account.username.clone(),
+        export.account.email.clone().unwrap_or_default(),
+        export
+            .account
+            .email_verified_at
+            .map(|t| t.to_rfc3339())
+            .unwrap_or_default(),
+        export.account.account_role.clone(),
+        export.account.created_at.to_rfc3339(),
+    ])?;
+
+    let mut tags = csv::Writer::from_writer(vec![]);
+    tags.write_record(["id", "name"])?;
+    for tag in &export.tags {
+        tags.write_record([tag.id.to_string(), tag.name.clone()])?;
+    }
+
+    let mut categories = csv::Writer::from_writer(vec![]);
+    categories.write_record(["id", "name"])?;
+    for category in &export.categories {
+        categories.write_record([category.id.to_string(), category.name.clone()])?;
+    }
+
+    let mut expenses = csv::Writer::from_writer(vec![]);
+    expenses.write_record([
+        "id",
+        "category_id",
+        "description",
+        "expense_date",
+        "cost",
+        "tag_ids",
+    ])?;
+    for expense in &export.expenses {
+        expenses.write_record([
+            expense.id.to_string(),
+            expense
+                .category_id
+                .map(|id| id.to_string())
+                .unwrap_or_default(),
+            expense.description.clone(),
+            expense.expense_date.to_string(),
+            expense.cost.to_string(),
+            expense
+                .tags_ids
+                .iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+                .join(" "),
+        ])?;
+    }
+
+    let mut archive = ZipWriter::new(Cursor::new(vec![]));
+    for (name, writer) in [
+        ("account.csv", account),
+        ("tags.csv", tags),
+        ("categories.csv", categories),
+        ("expenses.csv", expenses),
+    ] {
+        let content = writer.into_inner().map_err(|e| e.into_error())?;
+        archive.start_file(name, SimpleFileOptions::default())?;
+        archive.write_all(&content)?;
+    }
+
+    Ok(archive.finish()?.into_inner())
+}
@@ -1,8 +1,33 @@
-use axum::{response::IntoResponse, Extension, Json};
+use axum::{
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Extension, Json,
+};
+use axum_extra::extract::CookieJar;
+use std::sync::Arc;
 
-use crate::domain::app_user::AppUser;
+use crate::{
+    config::Config,
+    domain::{
+        app_user::AppUser,
+        audit_event::{AuditAction, AuditOutcome},
+    },
+    features::{
+        auth::session_cookie::clear_session_cookies,
+        response::{HttpError, ValidationErrorResponse},
+    },
+    services::{
+        account::{AccountError, AccountService},
+        audit::{AuditRecord, AuditService, RequestOrigin},
+        auth::{AuthService, PasswordChangeError, TokenClaims},
+    },
+};
 
-use super::api::UserResponse;
+use super::api::{
+    AccountExportResponse, ChangePasswordRequest, DeleteAccountRequest, ExportQuery, UserResponse,
+};
+use super::export_archive::csv_zip;
 
 #[utoipa::path(
     get,
@@ -16,3 +41,174 @@ use super::api::UserResponse;
 pub(super) async fn me(Extension(user): Extension<AppUser>) -> impl IntoResponse {
     Json(UserResponse::from_user(user))
 }
+
+#[utoipa::path(
+    put,
+    path = "/api/users/me/password",
+    tag = "Users",
+    request_body = ChangePasswordRequest,
+    responses(
+        (status = StatusCode::NO_CONTENT, description = "Password changed"),
+        (status = StatusCode::BAD_REQUEST, description = "New password does not meet the policy", body = ValidationErrorResponse),
+        (status = StatusCode::FORBIDDEN, description = "Old password is incorrect"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn change_password(
+    Extension(user): Extension<AppUser>,
+    Extension(claims): Extension<TokenClaims>,
+    origin: RequestOrigin,
+    State(service): State<Arc<AuthService>>,
+    State(audit_service): State<Arc<AuditService>>,
+    Json(body): Json<ChangePasswordRequest>,
+) -> Result<impl IntoResponse, HttpError> {
+    let result = service
+        .change_password(&user, &claims, &body.old_password, &body.new_password)
+        .await;
+
+    let record = match &result {
+        Ok(_) => AuditRecord::new(AuditAction::PasswordChange, AuditOutcome::Success),
+        Err(e) => {
+            AuditRecord::new(AuditAction::PasswordChange, AuditOutcome::Failure).detail(match e {
+                PasswordChangeError::IncorrectPassword => "incorrect_password",
+                PasswordChangeError::UserDoesNotExist => "user_does_not_exist",
+                PasswordChangeError::PolicyViolation(_) => "policy_violation",
+                PasswordChangeError::InternalError => "internal_error",
+            })
+        }
+    };
+    audit_service.record(&origin, record.actor(&user)).await;
+
+    result
+        .map_err(|e| match e {
+            PasswordChangeError::IncorrectPassword => {
+                HttpError::from((StatusCode::FORBIDDEN, "Old password is incorrect"))
+            }
+            PasswordChangeError::UserDoesNotExist => HttpError::from(StatusCode::UNAUTHORIZED),
+            PasswordChangeError::PolicyViolation(violations) => HttpError::from(violations),
+            PasswordChangeError::InternalError => {
+                HttpError::from(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        })
+        .map(|_| StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/users/me/export",
+    tag = "Users",
+    params(ExportQuery),
+    responses(
+        (status = StatusCode::OK, description = "Archive of the account, its tags, categories and expenses", body = AccountExportResponse),
+        (status = StatusCode::OK, description = "Zip archive of CSV files when `format=csv`", content_type = "application/zip"),
+        (status = StatusCode::BAD_REQUEST, description = "Unknown export format"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn export_account(
+    Extension(user): Extension<AppUser>,
+    origin: RequestOrigin,
+    State(service): State<Arc<AccountService>>,
+    State(audit_service): State<Arc<AuditService>>,
+    Query(query): Query<ExportQuery>,
+) -> Result<impl IntoResponse, HttpError> {
+    let format = query.format.unwrap_or_else(|| "json".to_string());
+    if format != "json" && format != "csv" {
+        return Err(HttpError::from((
+            StatusCode::BAD_REQUEST,
+            "Format must be json or csv",
+        )));
+    }
+
+    let result = service.export(&user).await;
+
+    audit_service
+        .record(
+            &origin,
+            AuditRecord::from_result(AuditAction::AccountExport, &result)
+                .actor(&user)
+                .detail(&format),
+        )
+        .await;
+
+    let export = AccountExportResponse::from_export(
+        result.map_err(|_| HttpError::from(StatusCode::INTERNAL_SERVER_ERROR))?,
+    );
+
+    if format == "csv" {
+        let archive =
+            csv_zip(&export).map_err(|_| HttpError::from(StatusCode::INTERNAL_SERVER_ERROR))?;
+        Ok((
+            [
+                (header::CONTENT_TYPE, "application/zip"),
+                (
+                    header::CONTENT_DISPOSITION,
+                    "attachment; filename=\"snailsoup-export.zip\"",
+                ),
+            ],
+            archive,
+        )
+            .into_response())
+    } else {
+        Ok((
+            [(
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"snailsoup-export.json\"",
+            )],
+            Json(export),
+        )
+            .into_response())
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/users/me",
+    tag = "Users",
+    request_body = DeleteAccountRequest,
+    responses(
+        (status = StatusCode::NO_CONTENT, description = "Account and all of its data deleted"),
+        (status = StatusCode::FORBIDDEN, description = "Password is incorrect"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn delete_account(
+    Extension(user): Extension<AppUser>,
+    origin: RequestOrigin,
+    jar: CookieJar,
+    State(service): State<Arc<AccountService>>,
+    State(audit_service): State<Arc<AuditService>>,
+    State(config): State<Config>,
+    Json(body): Json<DeleteAccountRequest>,
+) -> Result<impl IntoResponse, HttpError> {
+    let result = service.delete(&user, &body.password).await;
+
+    // Events of a deleted account were stripped of its personal data, its deletion only keeps the id
+    let (origin, record) = match &result {
+        Ok(_) => (
+            RequestOrigin::default(),
+            AuditRecord::new(AuditAction::AccountDelete, AuditOutcome::Success).actor_id(user.id),
+        ),
+        Err(e) => (
+            origin,
+            AuditRecord::new(AuditAction::AccountDelete, AuditOutcome::Failure)
+                .actor(&user)
+                .detail(match e {
+                    AccountError::IncorrectPassword => "incorrect_password",
+                    AccountError::UserDoesNotExist => "user_does_not_exist",
+                    AccountError::InternalError => "internal_error",
+                }),
+        ),
+    };
+    audit_service.record(&origin, record.target(user.id)).await;
+
+    result
+        .map_err(|e| match e {
+            AccountError::IncorrectPassword => {
+                HttpError::from((StatusCode::FORBIDDEN, "Password is incorrect"))
+            }
+            AccountError::UserDoesNotExist => HttpError::from(StatusCode::UNAUTHORIZED),
+            AccountError::InternalError => HttpError::from(StatusCode::INTERNAL_SERVER_ERROR),
+        })
+        .map(|_| (StatusCode::NO_CONTENT, clear_session_cookies(jar, &config)))
+}
@@ -1,3 +1,7 @@
 pub mod admin_handlers;
 pub mod api;
+pub mod api_token_handlers;
+pub mod export_archive;
 pub mod handlers;
+pub mod session_handlers;
+pub mod two_factor_handlers;
@@ -0,0 +1,91 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    domain::{app_user::AppUser, audit_event::AuditAction},
+    features::response::HttpError,
+    services::{
+        audit::{AuditRecord, AuditService, RequestOrigin},
+        auth::TokenClaims,
+        session::{SessionError, SessionService},
+    },
+};
+
+use super::api::SessionResponse;
+
+#[utoipa::path(
+    get,
+    path = "/api/users/me/sessions",
+    tag = "Users",
+    responses(
+        (status = StatusCode::OK, description = "Active sessions of the user, most recently used first", body = [SessionResponse]),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn my_sessions(
+    Extension(user): Extension<AppUser>,
+    Extension(claims): Extension<TokenClaims>,
+    State(service): State<Arc<SessionService>>,
+) -> Result<impl IntoResponse, HttpError> {
+    service
+        .list(user.id)
+        .await
+        .map_err(map_session_error)
+        .map(|sessions| {
+            Json(
+                sessions
+                    .into_iter()
+                    .map(|session| SessionResponse::from_session(session, claims.sid.as_deref()))
+                    .collect::<Vec<SessionResponse>>(),
+            )
+        })
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/users/me/sessions/{session_id}",
+    tag = "Users",
+    responses(
+        (status = StatusCode::NO_CONTENT, description = "Session revoked, its access and refresh tokens stop working"),
+        (status = StatusCode::NOT_FOUND, description = "Session not found or already revoked"),
+    ),
+    params(
+        ("session_id" = Uuid, Path, description = "Id of the session to revoke"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn revoke_session(
+    Extension(user): Extension<AppUser>,
+    origin: RequestOrigin,
+    Path(session_id): Path<Uuid>,
+    State(service): State<Arc<SessionService>>,
+    State(audit_service): State<Arc<AuditService>>,
+) -> Result<impl IntoResponse, HttpError> {
+    let result = service.revoke(user.id, session_id).await;
+
+    audit_service
+        .record(
+            &origin,
+            AuditRecord::from_result(AuditAction::SessionRevoke, &result)
+                .actor(&user)
+                .detail(&session_id.to_string()),
+        )
+        .await;
+
+    result
+        .map_err(map_session_error)
+        .map(|_| StatusCode::NO_CONTENT)
+}
+
+fn map_session_error(e: SessionError) -> HttpError {
+    match e {
+        SessionError::SessionDoesNotExist => HttpError::from(StatusCode::NOT_FOUND),
+        SessionError::InternalError => HttpError::from(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
@@ -0,0 +1,116 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Extension, Json};
+use std::sync::Arc;
+
+use crate::{
+    domain::app_user::AppUser,
+    features::response::HttpError,
+    services::two_factor::{TwoFactorError, TwoFactorService},
+};
+
+use super::api::{RecoveryCodesResponse, TotpCodeRequest, TotpEnrollmentResponse};
+
+#[utoipa::path(
+    post,
+    path = "/api/users/me/totp",
+    tag = "Users",
+    responses(
+        (status = StatusCode::OK, description = "Secret generated, confirm it with a code to enable two-factor authentication", body = TotpEnrollmentResponse),
+        (status = StatusCode::CONFLICT, description = "Two-factor authentication is already enabled"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn enroll_totp(
+    Extension(user): Extension<AppUser>,
+    State(service): State<Arc<TwoFactorService>>,
+) -> Result<impl IntoResponse, HttpError> {
+    service
+        .enroll(&user)
+        .await
+        .map_err(map_two_factor_error)
+        .map(|enrollment| Json(TotpEnrollmentResponse::from_enrollment(enrollment)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/users/me/totp/confirm",
+    tag = "Users",
+    request_body = TotpCodeRequest,
+    responses(
+        (status = StatusCode::OK, description = "Two-factor authentication enabled", body = RecoveryCodesResponse),
+        (status = StatusCode::BAD_REQUEST, description = "No authenticator was enrolled"),
+        (status = StatusCode::FORBIDDEN, description = "Invalid code"),
+        (status = StatusCode::CONFLICT, description = "Two-factor authentication is already enabled"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn confirm_totp(
+    Extension(user): Extension<AppUser>,
+    State(service): State<Arc<TwoFactorService>>,
+    Json(body): Json<TotpCodeRequest>,
+) -> Result<impl IntoResponse, HttpError> {
+    service
+        .confirm(&user, &body.code)
+        .await
+        .map_err(map_two_factor_error)
+        .map(|recovery_codes| Json(RecoveryCodesResponse { recovery_codes }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/users/me/totp",
+    tag = "Users",
+    request_body = TotpCodeRequest,
+    responses(
+        (status = StatusCode::NO_CONTENT, description = "Two-factor authentication disabled"),
+        (status = StatusCode::BAD_REQUEST, description = "Two-factor authentication is not enabled"),
+        (status = StatusCode::FORBIDDEN, description = "Invalid code"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn disable_totp(
+    Extension(user): Extension<AppUser>,
+    State(service): State<Arc<TwoFactorService>>,
+    Json(body): Json<TotpCodeRequest>,
+) -> Result<impl IntoResponse, HttpError> {
+    service
+        .disable(user.id, &body.code)
+        .await
+        .map_err(map_two_factor_error)
+        .map(|_| StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/users/me/recovery-codes",
+    tag = "Users",
+    request_body = TotpCodeRequest,
+    responses(
+        (status = StatusCode::OK, description = "Previous recovery codes replaced", body = RecoveryCodesResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Two-factor authentication is not enabled"),
+        (status = StatusCode::FORBIDDEN, description = "Invalid code"),
+    ),
+    security(("BearerToken" = []))
+)]
+pub(super) async fn regenerate_recovery_codes(
+    Extension(user): Extension<AppUser>,
+    State(service): State<Arc<TwoFactorService>>,
+    Json(body): Json<TotpCodeRequest>,
+) -> Result<impl IntoResponse, HttpError> {
+    service
+        .regenerate_recovery_codes(user.id, &body.code)
+        .await
+        .map_err(map_two_factor_error)
+        .map(|recovery_codes| Json(RecoveryCodesResponse { recovery_codes }))
+}
+
+fn map_two_factor_error(e: TwoFactorError) -> HttpError {
+    match e {
+        TwoFactorError::NotEnrolled => HttpError::from("Two-factor authentication is not enabled"),
+        TwoFactorError::AlreadyEnabled => HttpError::from((
+            StatusCode::CONFLICT,
+            "Two-factor authentication is already enabled",
+        )),
+        TwoFactorError::InvalidCode => HttpError::from((StatusCode::FORBIDDEN, "Invalid code")),
+        TwoFactorError::InternalError => HttpError::from(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
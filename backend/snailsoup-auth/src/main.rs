@@ -4,8 +4,9 @@ mod db;
 mod domain;
 mod features;
 mod services;
+mod utils;
 
-use std::sync::Arc;
+use std::{net::SocketAddr, sync::Arc};
 
 use dotenvy::dotenv;
 use sqlx::{migrate::MigrateDatabase, postgres::PgPoolOptions, Pool, Postgres};
@@ -13,7 +14,25 @@ use sqlx::{migrate::MigrateDatabase, postgres::PgPoolOptions, Pool, Postgres};
 use crate::{
     app_state::AppState,
     config::Config,
-    services::{auth::AuthService, user::UserService},
+    services::{
+        account::AccountService,
+        api_token::ApiTokenService,
+        audit::AuditService,
+        auth::{AuthService, JwtKeys, TokenRepositories},
+        email_verification::EmailVerificationService,
+        expense::ExpenseService,
+        introspection::IntrospectionService,
+        invite::InviteService,
+        login_throttle::LoginThrottleService,
+        mail::mailer_from_config,
+        oauth::OAuthService,
+        password_reset::PasswordResetService,
+        permission::PermissionService,
+        registration::RegistrationService,
+        session::SessionService,
+        two_factor::TwoFactorService,
+        user::UserService,
+    },
 };
 
 async fn connect_to_db() -> Result<Pool<Postgres>, String> {
@@ -76,16 +95,135 @@ async fn main() {
         config.jwt_maxage, config.jwt_expires_in
     );
 
+    let jwt_keys = match JwtKeys::from_config(&config) {
+        Ok(keys) => Arc::new(keys),
+        Err(e) => panic!("{}", e),
+    };
+
+    let mailer = match mailer_from_config(&config) {
+        Ok(mailer) => mailer,
+        Err(e) => panic!("{}", e),
+    };
+
     let app_user_repo = Arc::new(db::AppUserRepository::new(pool.clone()));
+    let expense_repo = Arc::new(db::ExpenseRepository::new(pool.clone()));
+    let refresh_token_repo = Arc::new(db::RefreshTokenRepository::new(pool.clone()));
+    let revocation_repo = Arc::new(db::RevocationRepository::new(pool.clone()));
+    let session_repo = Arc::new(db::SessionRepository::new(pool.clone()));
+    let password_reset_repo = Arc::new(db::PasswordResetRepository::new(pool.clone()));
+    let email_verification_repo = Arc::new(db::EmailVerificationRepository::new(pool.clone()));
+    let two_factor_repo = Arc::new(db::TwoFactorRepository::new(pool.clone()));
+    let login_failure_repo = Arc::new(db::LoginFailureRepository::new(pool.clone()));
+    let permission_repo = Arc::new(db::PermissionRepository::new(pool.clone()));
+    let api_token_repo = Arc::new(db::ApiTokenRepository::new(pool.clone()));
+    let oauth_repo = Arc::new(db::OAuthRepository::new(pool.clone()));
+    let invite_code_repo = Arc::new(db::InviteCodeRepository::new(pool.clone()));
+    let audit_repo = Arc::new(db::AuditRepository::new(pool.clone()));
+
+    let two_factor_service = Arc::new(TwoFactorService::new(
+        two_factor_repo.clone(),
+        config.clone(),
+    ));
 
-    let app_state = AppState::new(
+    let login_throttle_service = Arc::new(LoginThrottleService::new(
+        login_failure_repo.clone(),
+        app_user_repo.clone(),
         config.clone(),
-        Arc::new(AuthService::new(app_user_repo.clone(), config.clone())),
-        Arc::new(UserService::new(app_user_repo.clone())),
-    );
+    ));
+
+    let invite_service = Arc::new(InviteService::new(invite_code_repo.clone()));
+
+    let permission_service = Arc::new(PermissionService::new(permission_repo.clone()));
+
+    let auth_service = Arc::new(AuthService::new(
+        app_user_repo.clone(),
+        TokenRepositories {
+            refresh_tokens: refresh_token_repo.clone(),
+            revocations: revocation_repo.clone(),
+            sessions: session_repo.clone(),
+            api_tokens: api_token_repo.clone(),
+        },
+        two_factor_service.clone(),
+        login_throttle_service.clone(),
+        permission_service.clone(),
+        jwt_keys.clone(),
+        config.clone(),
+    ));
+
+    let api_token_service = Arc::new(ApiTokenService::new(
+        api_token_repo.clone(),
+        app_user_repo.clone(),
+    ));
+
+    let oauth_service = Arc::new(OAuthService::new(
+        oauth_repo.clone(),
+        app_user_repo.clone(),
+        revocation_repo.clone(),
+        jwt_keys.clone(),
+        config.clone(),
+    ));
+
+    let app_state = AppState {
+        config: config.clone(),
+        auth_service: auth_service.clone(),
+        user_service: Arc::new(UserService::new(
+            app_user_repo.clone(),
+            auth_service.clone(),
+            permission_service.clone(),
+        )),
+        expense_service: Arc::new(ExpenseService::new(
+            expense_repo.clone(),
+            app_user_repo.clone(),
+        )),
+        password_reset_service: Arc::new(PasswordResetService::new(
+            app_user_repo.clone(),
+            password_reset_repo.clone(),
+            auth_service.clone(),
+            mailer.clone(),
+            config.clone(),
+        )),
+        email_verification_service: Arc::new(EmailVerificationService::new(
+            app_user_repo.clone(),
+            email_verification_repo.clone(),
+            mailer.clone(),
+            config.clone(),
+        )),
+        two_factor_service: two_factor_service.clone(),
+        login_throttle_service: login_throttle_service.clone(),
+        permission_service: permission_service.clone(),
+        api_token_service: api_token_service.clone(),
+        oauth_service: oauth_service.clone(),
+        introspection_service: Arc::new(IntrospectionService::new(
+            auth_service.clone(),
+            api_token_service.clone(),
+            oauth_service.clone(),
+        )),
+        registration_service: Arc::new(RegistrationService::new(
+            auth_service.clone(),
+            invite_service.clone(),
+            config.clone(),
+        )),
+        invite_service: invite_service.clone(),
+        audit_service: Arc::new(AuditService::new(audit_repo.clone())),
+        account_service: Arc::new(AccountService::new(
+            app_user_repo.clone(),
+            expense_repo.clone(),
+            auth_service.clone(),
+        )),
+        session_service: Arc::new(SessionService::new(
+            session_repo.clone(),
+            refresh_token_repo.clone(),
+            config.clone(),
+        )),
+    };
 
     let app = features::get_routes(app_state);
 
     let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .unwrap();
 }
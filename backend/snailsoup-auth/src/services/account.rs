@@ -0,0 +1,111 @@
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+use crate::{
+    db::{AppUserRepository, ExpenseRepository},
+    domain::{
+        app_user::AppUser,
+        expense::{Category, FullExpense, FullExpenseData, Tag},
+    },
+    services::auth::{AuthService, PasswordChangeError},
+};
+
+/// Self-service data export and account deletion.
+pub struct AccountService {
+    user_repository: Arc<AppUserRepository>,
+    expense_repository: Arc<ExpenseRepository>,
+    auth_service: Arc<AuthService>,
+}
+
+/// Everything stored about a user that belongs to them
+pub struct AccountExport {
+    pub exported_at: DateTime<Utc>,
+    pub user: AppUser,
+    pub tags: Vec<Tag>,
+    pub categories: Vec<Category>,
+    pub expenses: Vec<FullExpense>,
+}
+
+pub enum AccountError {
+    IncorrectPassword,
+    UserDoesNotExist,
+    InternalError,
+}
+
+impl AccountService {
+    pub fn new(
+        user_repository: Arc<AppUserRepository>,
+        expense_repository: Arc<ExpenseRepository>,
+        auth_service: Arc<AuthService>,
+    ) -> AccountService {
+        AccountService {
+            user_repository,
+            expense_repository,
+            auth_service,
+        }
+    }
+
+    pub async fn export(&self, user: &AppUser) -> Result<AccountExport, AccountError> {
+        let tags = self
+            .expense_repository
+            .get_all_tags_by_user_id(user.id)
+            .await
+            .map_err(|_| AccountError::InternalError)?;
+
+        let categories = self
+            .expense_repository
+            .get_all_categories_by_user_id(user.id)
+            .await
+            .map_err(|_| AccountError::InternalError)?;
+
+        let mut tag_ids = self
+            .expense_repository
+            .get_expense_tag_ids_by_user_id(user.id)
+            .await
+            .map_err(|_| AccountError::InternalError)?;
+
+        let expenses = self
+            .expense_repository
+            .get_all_expenses_by_user_id(user.id)
+            .await
+            .map_err(|_| AccountError::InternalError)?
+            .into_iter()
+            .map(|expense| FullExpense {
+                id: expense.id,
+                data: FullExpenseData {
+                    expense: expense.data,
+                    tags_ids: tag_ids.remove(&expense.id).unwrap_or_default(),
+                },
+            })
+            .collect();
+
+        Ok(AccountExport {
+            exported_at: Utc::now(),
+            user: user.clone(),
+            tags,
+            categories,
+            expenses,
+        })
+    }
+
+    /// Deletes the account after the user confirmed their password. Its expenses, categories,
+    /// tags, sessions and tokens are removed with it, so every token issued to it stops working.
+    pub async fn delete(&self, user: &AppUser, password: &str) -> Result<(), AccountError> {
+        self.auth_service
+            .confirm_password(user, password)
+            .map_err(|e| match e {
+                PasswordChangeError::IncorrectPassword => AccountError::IncorrectPassword,
+                _ => AccountError::InternalError,
+            })?;
+
+        self.user_repository
+            .delete(user.id)
+            .await
+            .map_err(|_| AccountError::InternalError)?
+            .ok_or(AccountError::UserDoesNotExist)?;
+
+        println!("User {} deleted their account", user.username);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    db::{ApiTokenRepository, AppUserRepository},
+    domain::{
+        api_token::{ApiToken, API_TOKEN_PREFIX, API_TOKEN_SCOPES},
+        app_user::AppUser,
+    },
+    services::auth::AuthError,
+    utils::token::{generate_token, hash_token},
+};
+
+pub struct ApiTokenService {
+    api_token_repository: Arc<ApiTokenRepository>,
+    user_repository: Arc<AppUserRepository>,
+}
+
+pub enum ApiTokenError {
+    MissingName,
+    MissingScopes,
+    UnknownScope(String),
+    ExpiryInPast,
+    TokenDoesNotExist,
+    InternalError,
+}
+
+impl ApiTokenService {
+    pub fn new(
+        api_token_repository: Arc<ApiTokenRepository>,
+        user_repository: Arc<AppUserRepository>,
+    ) -> ApiTokenService {
+        ApiTokenService {
+            api_token_repository,
+            user_repository,
+        }
+    }
+
+    /// Issues a new token. The plain token is returned only here, just its hash is stored.
+    pub async fn create(
+        &self,
+        user_id: Uuid,
+        name: &str,
+        scopes: Vec<String>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(ApiToken, String), ApiTokenError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(ApiTokenError::MissingName);
+        }
+
+        if scopes.is_empty() {
+            return Err(ApiTokenError::MissingScopes);
+        }
+
+        if let Some(scope) = scopes
+            .iter()
+            .find(|s| !API_TOKEN_SCOPES.contains(&s.as_str()))
+        {
+            return Err(ApiTokenError::UnknownScope(scope.clone()));
+        }
+
+        let now = Utc::now();
+        if expires_at.is_some_and(|t| t <= now) {
+            return Err(ApiTokenError::ExpiryInPast);
+        }
+
+        let mut scopes = scopes;
+        scopes.sort();
+        scopes.dedup();
+
+        let token = format!("{}{}", API_TOKEN_PREFIX, generate_token());
+        let api_token = ApiToken {
+            id: Uuid::new_v4(),
+            user_id,
+            name: name.to_owned(),
+            token_hash: hash_token(&token),
+            scopes,
+            created_at: now,
+            expires_at,
+            last_used_at: None,
+        };
+
+        self.api_token_repository
+            .insert(api_token.clone())
+            .await
+            .map_err(|_| ApiTokenError::InternalError)?;
+
+        Ok((api_token, token))
+    }
+
+    pub async fn list(&self, user_id: Uuid) -> Result<Vec<ApiToken>, ApiTokenError> {
+        self.api_token_repository
+            .get_all_for_user(user_id)
+            .await
+            .map_err(|_| ApiTokenError::InternalError)
+    }
+
+    pub async fn revoke(&self, user_id: Uuid, id: Uuid) -> Result<(), ApiTokenError> {
+        self.api_token_repository
+            .delete(id, user_id)
+            .await
+            .map_err(|_| ApiTokenError::InternalError)?
+            .ok_or(ApiTokenError::TokenDoesNotExist)?;
+
+        Ok(())
+    }
+
+    pub async fn authenticate(&self, token: &str) -> Result<(AppUser, ApiToken), AuthError> {
+        let api_token = self
+            .api_token_repository
+            .get_by_hash(&hash_token(token))
+            .await
+            .map_err(|_| AuthError::InternalError)?
+            .ok_or(AuthError::InvalidToken)?;
+
+        let now = Utc::now();
+        if api_token.expires_at.is_some_and(|t| t < now) {
+            return Err(AuthError::ExpiredToken);
+        }
+
+        let user = self
+            .user_repository
+            .get(api_token.user_id)
+            .await
+            .map_err(|_| AuthError::InternalError)?
+            .ok_or(AuthError::UserDoesNotExist)?;
+
+        if user.disabled_at.is_some() {
+            return Err(AuthError::UserDisabled);
+        }
+
+        self.api_token_repository
+            .mark_used(api_token.id, now)
+            .await
+            .map_err(|_| AuthError::InternalError)?;
+
+        Ok((user, api_token))
+    }
+}
@@ -0,0 +1,134 @@
+use chrono::Utc;
+use std::{net::IpAddr, sync::Arc};
+use uuid::Uuid;
+
+use crate::{
+    db::AuditRepository,
+    domain::{
+        app_user::AppUser,
+        audit_event::{AuditAction, AuditEvent, AuditOutcome},
+    },
+    utils::audit_query::AuditQuery,
+};
+
+/// Column lengths, longer values are cut off
+const MAX_ACTOR_NAME_LENGTH: usize = 255;
+const MAX_USER_AGENT_LENGTH: usize = 512;
+
+pub struct AuditService {
+    audit_repository: Arc<AuditRepository>,
+}
+
+pub enum AuditError {
+    InternalError,
+}
+
+/// Where a request came from, stored with the events it causes
+#[derive(Clone, Default)]
+pub struct RequestOrigin {
+    pub ip: Option<IpAddr>,
+    pub user_agent: Option<String>,
+}
+
+/// Event about to be recorded
+pub struct AuditRecord {
+    pub action: AuditAction,
+    pub outcome: AuditOutcome,
+    pub actor_id: Option<Uuid>,
+    pub actor_name: Option<String>,
+    pub target_id: Option<Uuid>,
+    pub detail: Option<String>,
+}
+
+impl AuditRecord {
+    pub fn new(action: AuditAction, outcome: AuditOutcome) -> AuditRecord {
+        AuditRecord {
+            action,
+            outcome,
+            actor_id: None,
+            actor_name: None,
+            target_id: None,
+            detail: None,
+        }
+    }
+
+    /// Success or failure depending on the result of the audited operation
+    pub fn from_result<T, E>(action: AuditAction, result: &Result<T, E>) -> AuditRecord {
+        match result {
+            Ok(_) => AuditRecord::new(action, AuditOutcome::Success),
+            Err(_) => AuditRecord::new(action, AuditOutcome::Failure),
+        }
+    }
+
+    pub fn actor(mut self, user: &AppUser) -> AuditRecord {
+        self.actor_id = Some(user.id);
+        self.actor_name = Some(user.username.clone());
+        self
+    }
+
+    pub fn actor_id(mut self, actor_id: Uuid) -> AuditRecord {
+        self.actor_id = Some(actor_id);
+        self
+    }
+
+    pub fn target(mut self, target_id: Uuid) -> AuditRecord {
+        self.target_id = Some(target_id);
+        self
+    }
+
+    pub fn actor_name(mut self, actor_name: &str) -> AuditRecord {
+        self.actor_name = Some(actor_name.to_owned());
+        self
+    }
+
+    pub fn detail(mut self, detail: &str) -> AuditRecord {
+        self.detail = Some(detail.to_owned());
+        self
+    }
+}
+
+impl AuditService {
+    pub fn new(audit_repository: Arc<AuditRepository>) -> AuditService {
+        AuditService { audit_repository }
+    }
+
+    /// Stores the event. Failures are only logged so that auditing never breaks the request.
+    pub async fn record(&self, origin: &RequestOrigin, record: AuditRecord) {
+        let event = AuditEvent {
+            id: Uuid::new_v4(),
+            occurred_at: Utc::now(),
+            actor_id: record.actor_id,
+            actor_name: record
+                .actor_name
+                .map(|name| truncate(&name, MAX_ACTOR_NAME_LENGTH)),
+            action: record.action.as_str().to_owned(),
+            target_id: record.target_id,
+            ip: origin.ip.map(|ip| ip.to_string()),
+            user_agent: origin
+                .user_agent
+                .as_ref()
+                .map(|agent| truncate(agent, MAX_USER_AGENT_LENGTH)),
+            outcome: record.outcome.as_str().to_owned(),
+            detail: record.detail,
+        };
+
+        if let Err(e) = self.audit_repository.insert(event).await {
+            println!(
+                "Cannot record audit event {}: {}",
+                record.action.as_str(),
+                e
+            );
+        }
+    }
+
+    pub async fn search(&self, query: AuditQuery) -> Result<Vec<AuditEvent>, AuditError> {
+        self.audit_repository
+            .search(&query)
+            .await
+            .map_err(|_| AuditError::InternalError)
+    }
+}
+
+fn truncate(value: &str, max_length: usize) -> String {
+    value.chars().take(max_length).collect()
+}
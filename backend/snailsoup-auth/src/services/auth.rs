@@ -1,66 +1,273 @@
+mod keys;
 mod token_claim;
 
 use argon2::{
     password_hash::{rand_core::OsRng, SaltString},
-    Argon2, PasswordHash, PasswordHasher, PasswordVerifier,
+    Argon2, Params, PasswordHash, PasswordHasher, PasswordVerifier, Version,
 };
-use chrono::{DateTime, Duration, Utc};
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
-use std::sync::Arc;
+use chrono::{DateTime, Duration, SubsecRound, Utc};
+use jsonwebtoken::{decode, decode_header, encode, jwk::JwkSet, Validation};
+use std::{net::IpAddr, sync::Arc};
 use uuid::Uuid;
 
-use crate::{config::Config, db::AppUserRepository, domain::app_user::AppUser};
+use crate::{
+    config::Config,
+    db::{
+        ApiTokenRepository, AppUserRepository, RefreshTokenRepository, RevocationRepository,
+        SessionRepository,
+    },
+    domain::{
+        app_user::{AppUser, Role},
+        refresh_token::RefreshToken,
+        session::Session,
+    },
+    services::{
+        credential_policy::{normalize_login, CredentialPolicy, PolicyViolation},
+        login_throttle::{AccountKey, LoginThrottleService, ThrottleError},
+        permission::{PermissionError, PermissionService},
+        two_factor::{TwoFactorError, TwoFactorService},
+    },
+    utils::{
+        token::{generate_token, hash_token},
+        user_agent::device_name,
+    },
+};
 
+pub use self::keys::JwtKeys;
 pub use self::token_claim::TokenClaims;
+use self::token_claim::{ActorClaim, MfaClaims};
+
+const MFA_AUDIENCE: &str = "mfa";
+/// Lets a user act as others, accounts holding it cannot be impersonated themselves
+const IMPERSONATE_PERMISSION: &str = "users:impersonate";
+/// `last_seen_at` of a session is only updated once it is older than this
+const SESSION_SEEN_INTERVAL_MINUTES: i64 = 5;
 
 pub struct AuthService {
     user_repository: Arc<AppUserRepository>,
+    refresh_token_repository: Arc<RefreshTokenRepository>,
+    revocation_repository: Arc<RevocationRepository>,
+    session_repository: Arc<SessionRepository>,
+    api_token_repository: Arc<ApiTokenRepository>,
+    two_factor_service: Arc<TwoFactorService>,
+    login_throttle: Arc<LoginThrottleService>,
+    permission_service: Arc<PermissionService>,
+    keys: Arc<JwtKeys>,
+    credential_policy: CredentialPolicy,
+    /// Hashes new passwords with the configured parameters
+    argon2: Argon2<'static>,
+    /// Verified against when the username is unknown so that login takes as long as for real users
+    dummy_password_hash: String,
     config: Config,
 }
 
+/// Where issued tokens are tracked: refresh token families, revocations, the sessions (logins)
+/// they belong to and personal API tokens
+pub struct TokenRepositories {
+    pub refresh_tokens: Arc<RefreshTokenRepository>,
+    pub revocations: Arc<RevocationRepository>,
+    pub sessions: Arc<SessionRepository>,
+    pub api_tokens: Arc<ApiTokenRepository>,
+}
+
+pub struct AuthTokens {
+    /// Account the tokens were issued for
+    pub user_id: Uuid,
+    pub access_token: String,
+    pub refresh_token: String,
+}
+
+pub enum LoginOutcome {
+    Authenticated(AuthTokens),
+    /// Password was correct, the token must be exchanged together with a second factor code
+    MfaRequired {
+        user_id: Uuid,
+        mfa_token: String,
+    },
+}
+
 pub enum LoginError {
     IncorrectUser,
     IncorrectPassword,
+    /// Retry after the given number of seconds
+    TooManyAttempts(i64),
+    /// Account is locked for the given number of seconds
+    AccountLocked(i64),
+    AccountDisabled,
+    /// Password is correct but `REQUIRE_EMAIL_VERIFICATION` is set and the address is unverified
+    EmailNotVerified,
+    /// Password is correct but an admin has not approved the account yet
+    PendingApproval,
     InternalError,
     InternalPasswordError,
     UnexpectedError,
 }
 
+pub enum MfaLoginError {
+    InvalidToken,
+    ExpiredToken,
+    InvalidCode,
+    TooManyAttempts(i64),
+    AccountLocked(i64),
+    AccountDisabled,
+    UserDoesNotExist,
+    InternalError,
+}
+
 pub enum AuthError {
     InvalidToken,
     ExpiredToken,
+    RevokedToken,
     UserDoesNotExist,
+    UserDisabled,
     InternalError,
 }
 
 pub enum RegisterError {
     UsernameInUse,
+    EmailInUse,
+    /// `REQUIRE_EMAIL_VERIFICATION` is set and no email was given
+    EmailRequired,
+    /// Username or password does not meet the credential policy
+    PolicyViolation(Vec<PolicyViolation>),
+    /// `REGISTRATION_MODE` is `invite` and no code was given
+    InviteCodeRequired,
+    /// Invite code is unknown, expired or used up
+    InvalidInviteCode,
+    InternalError,
+}
+
+pub enum LogoutError {
+    InvalidToken,
+    InternalError,
+}
+
+pub enum PasswordChangeError {
+    IncorrectPassword,
+    PolicyViolation(Vec<PolicyViolation>),
+    UserDoesNotExist,
+    InternalError,
+}
+
+/// Access token letting an admin act as another user
+pub struct ImpersonationToken {
+    pub access_token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+pub enum ImpersonationError {
+    /// Admins cannot impersonate themselves
+    OwnAccount,
+    /// Accounts that may impersonate others cannot be impersonated
+    ProtectedAccount,
+    UserDisabled,
+    UserDoesNotExist,
+    InternalError,
+}
+
+pub enum RefreshError {
+    InvalidToken,
+    ExpiredToken,
+    ReusedToken,
+    UserDoesNotExist,
+    AccountDisabled,
     InternalError,
 }
 
 impl AuthService {
-    pub fn new(user_repository: Arc<AppUserRepository>, config: Config) -> AuthService {
+    pub fn new(
+        user_repository: Arc<AppUserRepository>,
+        token_repositories: TokenRepositories,
+        two_factor_service: Arc<TwoFactorService>,
+        login_throttle: Arc<LoginThrottleService>,
+        permission_service: Arc<PermissionService>,
+        keys: Arc<JwtKeys>,
+        config: Config,
+    ) -> AuthService {
+        let argon2 = match Params::new(
+            config.argon2_memory_kib,
+            config.argon2_iterations,
+            config.argon2_parallelism,
+            None,
+        ) {
+            Ok(params) => Argon2::new(argon2::Algorithm::Argon2id, Version::V0x13, params),
+            Err(e) => panic!("Invalid Argon2 parameters: {}", e),
+        };
+
+        let dummy_password_hash = match hash_password(&argon2, &generate_token()) {
+            Ok(hash) => hash,
+            Err(e) => panic!("Cannot hash dummy password: {}", e),
+        };
+
+        let credential_policy = match CredentialPolicy::from_config(&config) {
+            Ok(policy) => policy,
+            Err(e) => panic!("{}", e),
+        };
+
         AuthService {
             user_repository,
+            refresh_token_repository: token_repositories.refresh_tokens,
+            revocation_repository: token_repositories.revocations,
+            session_repository: token_repositories.sessions,
+            api_token_repository: token_repositories.api_tokens,
+            two_factor_service,
+            login_throttle,
+            permission_service,
+            keys,
+            credential_policy,
+            argon2,
+            dummy_password_hash,
             config,
         }
     }
 
-    pub async fn login(&self, username: &str, password: &str) -> Result<String, LoginError> {
-        let user_opt = match self.user_repository.get_by_name(username).await {
+    pub async fn login(
+        &self,
+        username: &str,
+        password: &str,
+        ip: IpAddr,
+        user_agent: Option<&str>,
+    ) -> Result<LoginOutcome, LoginError> {
+        let username = &normalize_login(username);
+
+        let user_opt = match self.user_repository.get_by_login(username).await {
             Ok(user) => user,
             Err(_) => Err(LoginError::InternalError)?,
         };
 
+        let account = AccountKey::for_login(user_opt.as_ref(), username);
+        self.login_throttle
+            .check(&account, ip)
+            .await
+            .map_err(|e| match e {
+                ThrottleError::TooManyAttempts(retry_after) => {
+                    LoginError::TooManyAttempts(retry_after)
+                }
+                ThrottleError::Locked(retry_after) => LoginError::AccountLocked(retry_after),
+                ThrottleError::InternalError => LoginError::InternalError,
+            })?;
+
         let user = match user_opt {
             Some(user) => user,
-            None => Err(LoginError::IncorrectUser)?,
+            None => {
+                if let Ok(parsed_hash) = PasswordHash::new(&self.dummy_password_hash) {
+                    let _ = self
+                        .argon2
+                        .verify_password(password.as_bytes(), &parsed_hash);
+                }
+                self.login_throttle
+                    .record_failure(&account, ip)
+                    .await
+                    .map_err(|_| LoginError::InternalError)?;
+                Err(LoginError::IncorrectUser)?
+            }
         };
 
         let is_valid = match PasswordHash::new(&user.password_hash) {
-            Ok(parsed_hash) => Argon2::default()
+            Ok(parsed_hash) => self
+                .argon2
                 .verify_password(password.as_bytes(), &parsed_hash)
-                .map_or(false, |_| true),
+                .is_ok(),
             Err(_) => {
                 println!("{} has incorrect password in database!", user.username);
                 Err(LoginError::InternalPasswordError)?
@@ -68,37 +275,286 @@ impl AuthService {
         };
 
         if !is_valid {
+            self.login_throttle
+                .record_failure(&account, ip)
+                .await
+                .map_err(|_| LoginError::InternalError)?;
             return Err(LoginError::IncorrectPassword);
         }
 
+        if self.needs_rehash(&user.password_hash) {
+            self.upgrade_password_hash(&user, password).await;
+        }
+
+        // Checked only after the password so that it does not reveal which accounts exist
+        if user.disabled_at.is_some() {
+            return Err(LoginError::AccountDisabled);
+        }
+
+        if self.config.require_email_verification && user.email_verified_at.is_none() {
+            return Err(LoginError::EmailNotVerified);
+        }
+
+        if user.pending_approval {
+            return Err(LoginError::PendingApproval);
+        }
+
+        let mfa_enabled = self
+            .two_factor_service
+            .is_enabled(user.id)
+            .await
+            .map_err(|_| LoginError::InternalError)?;
+
+        if mfa_enabled {
+            return self
+                .create_mfa_token(&user)
+                .map(|mfa_token| LoginOutcome::MfaRequired {
+                    user_id: user.id,
+                    mfa_token,
+                })
+                .map_err(|_| LoginError::UnexpectedError);
+        }
+
+        self.login_throttle
+            .record_success(user.id)
+            .await
+            .map_err(|_| LoginError::InternalError)?;
+
+        let session_id = self
+            .create_session(user.id, ip, user_agent)
+            .await
+            .map_err(|_| LoginError::InternalError)?;
+
+        let access_token = self
+            .create_access_token(&user, session_id)
+            .map_err(|_| LoginError::UnexpectedError)?;
+
+        let refresh_token = self
+            .create_refresh_token(user.id, session_id)
+            .await
+            .map_err(|_| LoginError::InternalError)?;
+
+        Ok(LoginOutcome::Authenticated(AuthTokens {
+            user_id: user.id,
+            access_token,
+            refresh_token,
+        }))
+    }
+
+    /// Second login step: exchanges the token from the password step and a TOTP or recovery code
+    /// for regular tokens.
+    pub async fn login_mfa(
+        &self,
+        mfa_token: &str,
+        code: &str,
+        ip: IpAddr,
+        user_agent: Option<&str>,
+    ) -> Result<AuthTokens, MfaLoginError> {
+        let header = decode_header(mfa_token).map_err(|_| MfaLoginError::InvalidToken)?;
+        let (algorithm, decoding_key) = self
+            .keys
+            .decoding_key(header.kid.as_deref())
+            .ok_or(MfaLoginError::InvalidToken)?;
+
+        let mut validation = Validation::new(algorithm);
+        validation.set_audience(&[MFA_AUDIENCE]);
+
+        let claims = decode::<MfaClaims>(mfa_token, decoding_key, &validation)
+            .map_err(|e| match e.kind() {
+                jsonwebtoken::errors::ErrorKind::ExpiredSignature => MfaLoginError::ExpiredToken,
+                _ => MfaLoginError::InvalidToken,
+            })?
+            .claims;
+
+        let user_id = Uuid::parse_str(&claims.id).map_err(|_| MfaLoginError::InvalidToken)?;
+
+        let user = self
+            .user_repository
+            .get(user_id)
+            .await
+            .map_err(|_| MfaLoginError::InternalError)?
+            .ok_or(MfaLoginError::UserDoesNotExist)?;
+
+        if user.disabled_at.is_some() {
+            return Err(MfaLoginError::AccountDisabled);
+        }
+
+        // Codes are guessed as easily as passwords, failures count towards the same lockout
+        let account = AccountKey::User(user.id);
+        self.login_throttle
+            .check(&account, ip)
+            .await
+            .map_err(|e| match e {
+                ThrottleError::TooManyAttempts(retry_after) => {
+                    MfaLoginError::TooManyAttempts(retry_after)
+                }
+                ThrottleError::Locked(retry_after) => MfaLoginError::AccountLocked(retry_after),
+                ThrottleError::InternalError => MfaLoginError::InternalError,
+            })?;
+
+        let verified = self.two_factor_service.verify(user_id, code).await;
+
+        if let Err(TwoFactorError::InvalidCode) = verified {
+            self.login_throttle
+                .record_failure(&account, ip)
+                .await
+                .map_err(|_| MfaLoginError::InternalError)?;
+        }
+
+        verified.map_err(|e| match e {
+            TwoFactorError::InvalidCode => MfaLoginError::InvalidCode,
+            TwoFactorError::NotEnrolled | TwoFactorError::AlreadyEnabled => {
+                MfaLoginError::InvalidToken
+            }
+            TwoFactorError::InternalError => MfaLoginError::InternalError,
+        })?;
+
+        self.login_throttle
+            .record_success(user.id)
+            .await
+            .map_err(|_| MfaLoginError::InternalError)?;
+
+        let session_id = self
+            .create_session(user.id, ip, user_agent)
+            .await
+            .map_err(|_| MfaLoginError::InternalError)?;
+
+        let access_token = self
+            .create_access_token(&user, session_id)
+            .map_err(|_| MfaLoginError::InternalError)?;
+
+        let refresh_token = self
+            .create_refresh_token(user.id, session_id)
+            .await
+            .map_err(|_| MfaLoginError::InternalError)?;
+
+        Ok(AuthTokens {
+            user_id: user.id,
+            access_token,
+            refresh_token,
+        })
+    }
+
+    /// Exchanges a refresh token for a new access token and a new refresh token.
+    /// Presenting an already rotated token revokes every token of its family.
+    pub async fn refresh(&self, refresh_token: &str) -> Result<AuthTokens, RefreshError> {
+        let stored_token = self
+            .refresh_token_repository
+            .get_by_hash(&hash_token(refresh_token))
+            .await
+            .map_err(|_| RefreshError::InternalError)?
+            .ok_or(RefreshError::InvalidToken)?;
+
+        if stored_token.revoked_at.is_some() {
+            return Err(RefreshError::InvalidToken);
+        }
+
         let now = Utc::now();
 
-        let claims = TokenClaims {
-            id: user.id.to_string(),
-            created_at: now.timestamp(),
-            exp: (now + Duration::minutes(self.config.jwt_maxage.into())).timestamp(),
-        };
+        if stored_token.used_at.is_some() {
+            return Err(self.revoke_reused_family(&stored_token).await);
+        }
 
-        let token = encode(
-            &Header::default(),
-            &claims,
-            &EncodingKey::from_secret(self.config.jwt_secret.as_ref()),
-        )
-        .map_err(|_| LoginError::UnexpectedError)?;
+        if stored_token.expires_at < now {
+            return Err(RefreshError::ExpiredToken);
+        }
 
-        Ok(token)
+        let marked = self
+            .refresh_token_repository
+            .mark_used(stored_token.id, now)
+            .await
+            .map_err(|_| RefreshError::InternalError)?;
+
+        if marked.is_none() {
+            return Err(self.revoke_reused_family(&stored_token).await);
+        }
+
+        let user = self
+            .user_repository
+            .get(stored_token.user_id)
+            .await
+            .map_err(|_| RefreshError::InternalError)?
+            .ok_or(RefreshError::UserDoesNotExist)?;
+
+        if user.disabled_at.is_some() {
+            return Err(RefreshError::AccountDisabled);
+        }
+
+        // The family id is the session id, families of older logins have no session row
+        self.session_repository
+            .mark_seen(stored_token.family_id, now, now - session_seen_interval())
+            .await
+            .map_err(|_| RefreshError::InternalError)?;
+
+        let access_token = self
+            .create_access_token(&user, stored_token.family_id)
+            .map_err(|_| RefreshError::InternalError)?;
+
+        let refresh_token = self
+            .create_refresh_token(user.id, stored_token.family_id)
+            .await
+            .map_err(|_| RefreshError::InternalError)?;
+
+        Ok(AuthTokens {
+            user_id: user.id,
+            access_token,
+            refresh_token,
+        })
     }
 
-    pub async fn register(&self, username: &str, password: &str) -> Result<AppUser, RegisterError> {
-        let salt = SaltString::generate(&mut OsRng);
-        let hashed_password = Argon2::default()
-            .hash_password(password.as_bytes(), &salt)
-            .map_err(|_| RegisterError::InternalError)
-            .map(|hash| hash.to_string())?;
+    /// Self-service registration, see `RegistrationService` for the registration mode
+    pub async fn register(
+        &self,
+        username: &str,
+        password: &str,
+        email: Option<&str>,
+        pending_approval: bool,
+    ) -> Result<AppUser, RegisterError> {
+        if self.config.require_email_verification && email.is_none() {
+            return Err(RegisterError::EmailRequired);
+        }
+
+        self.insert_user(username, password, email, Role::default(), pending_approval)
+            .await
+    }
+
+    /// Creates an active account with the given role, used by administrators
+    pub async fn create_user(
+        &self,
+        username: &str,
+        password: &str,
+        email: Option<&str>,
+        account_role: Role,
+    ) -> Result<AppUser, RegisterError> {
+        self.insert_user(username, password, email, account_role, false)
+            .await
+    }
+
+    async fn insert_user(
+        &self,
+        username: &str,
+        password: &str,
+        email: Option<&str>,
+        account_role: Role,
+        pending_approval: bool,
+    ) -> Result<AppUser, RegisterError> {
+        let (username, mut violations) = match self.credential_policy.check_username(username) {
+            Ok(username) => (username, vec![]),
+            Err(violations) => (username.to_owned(), violations),
+        };
+        if let Err(password_violations) = self.credential_policy.check_password(password) {
+            violations.extend(password_violations);
+        }
+        if !violations.is_empty() {
+            return Err(RegisterError::PolicyViolation(violations));
+        }
+
+        let hashed_password =
+            hash_password(&self.argon2, password).map_err(|_| RegisterError::InternalError)?;
 
         let existing_user = self
             .user_repository
-            .get_by_name(username)
+            .get_by_name(&username)
             .await
             .map_err(|e| {
                 println!("{}", e);
@@ -109,13 +565,33 @@ impl AuthService {
             return Err(RegisterError::UsernameInUse);
         }
 
+        if let Some(email) = email {
+            let existing_email = self
+                .user_repository
+                .get_by_email(email)
+                .await
+                .map_err(|e| {
+                    println!("{}", e);
+                    RegisterError::InternalError
+                })?;
+
+            if existing_email.is_some() {
+                return Err(RegisterError::EmailInUse);
+            }
+        }
+
         let created_user = self
             .user_repository
             .insert(AppUser {
                 id: Uuid::new_v4(),
-                username: username.to_owned(),
+                username,
                 password_hash: hashed_password,
-                account_role: "User".to_owned(),
+                account_role,
+                email: email.map(|e| e.to_owned()),
+                email_verified_at: None,
+                disabled_at: None,
+                pending_approval,
+                created_at: Utc::now(),
             })
             .await
             .map_err(|e| {
@@ -126,13 +602,174 @@ impl AuthService {
         Ok(created_user)
     }
 
-    pub async fn auth_bearer_token(&self, token: &str) -> Result<AppUser, AuthError> {
-        let claims = decode::<TokenClaims>(
-            &token,
-            &DecodingKey::from_secret(self.config.jwt_secret.as_ref()),
-            &Validation::default(),
-        )
-        .map_err(|_| AuthError::InvalidToken)?;
+    /// Changes the password and signs out every other session and API token of the user.
+    /// The session of `claims` stays signed in.
+    pub async fn change_password(
+        &self,
+        user: &AppUser,
+        claims: &TokenClaims,
+        old_password: &str,
+        new_password: &str,
+    ) -> Result<(), PasswordChangeError> {
+        self.confirm_password(user, old_password)?;
+
+        self.set_password(user.id, new_password).await?;
+
+        let now = Utc::now();
+        let session_id = match &claims.sid {
+            Some(sid) => {
+                Some(Uuid::parse_str(sid).map_err(|_| PasswordChangeError::InternalError)?)
+            }
+            None => None,
+        };
+
+        match session_id {
+            Some(session_id) => {
+                self.session_repository
+                    .revoke_all_for_user_except(user.id, session_id, now)
+                    .await
+                    .map_err(|_| PasswordChangeError::InternalError)?;
+                self.refresh_token_repository
+                    .revoke_all_for_user_except_family(user.id, session_id, now)
+                    .await
+                    .map_err(|_| PasswordChangeError::InternalError)?;
+            }
+            None => {
+                self.session_repository
+                    .revoke_all_for_user(user.id, now)
+                    .await
+                    .map_err(|_| PasswordChangeError::InternalError)?;
+                self.refresh_token_repository
+                    .revoke_all_for_user(user.id, now)
+                    .await
+                    .map_err(|_| PasswordChangeError::InternalError)?;
+            }
+        }
+
+        self.api_token_repository
+            .delete_all_for_user(user.id)
+            .await
+            .map_err(|_| PasswordChangeError::InternalError)?;
+
+        Ok(())
+    }
+
+    /// Re-authenticates a signed in user before a sensitive change
+    pub fn confirm_password(
+        &self,
+        user: &AppUser,
+        password: &str,
+    ) -> Result<(), PasswordChangeError> {
+        let is_valid = match PasswordHash::new(&user.password_hash) {
+            Ok(parsed_hash) => self
+                .argon2
+                .verify_password(password.as_bytes(), &parsed_hash)
+                .is_ok(),
+            Err(_) => {
+                println!("{} has incorrect password in database!", user.username);
+                Err(PasswordChangeError::InternalError)?
+            }
+        };
+
+        if !is_valid {
+            return Err(PasswordChangeError::IncorrectPassword);
+        }
+
+        Ok(())
+    }
+
+    /// Sets a new password without knowing the old one and logs the user out everywhere.
+    pub async fn reset_password(
+        &self,
+        user_id: Uuid,
+        new_password: &str,
+    ) -> Result<(), PasswordChangeError> {
+        self.set_password(user_id, new_password).await?;
+
+        self.logout_everywhere(user_id)
+            .await
+            .map_err(|_| PasswordChangeError::InternalError)
+    }
+
+    /// Checks a new username against the credential policy, returns it in the form to store
+    pub fn check_username(&self, username: &str) -> Result<String, Vec<PolicyViolation>> {
+        self.credential_policy.check_username(username)
+    }
+
+    pub fn check_password(&self, password: &str) -> Result<(), Vec<PolicyViolation>> {
+        self.credential_policy.check_password(password)
+    }
+
+    /// Whether a stored hash was made with another algorithm, version or parameters than
+    /// currently configured
+    fn needs_rehash(&self, password_hash: &str) -> bool {
+        let Ok(parsed_hash) = PasswordHash::new(password_hash) else {
+            return false;
+        };
+        let configured = self.argon2.params();
+
+        parsed_hash.algorithm != argon2::Algorithm::Argon2id.ident()
+            || parsed_hash.version != Some(Version::V0x13.into())
+            || Params::try_from(&parsed_hash).map_or(true, |params| {
+                params.m_cost() != configured.m_cost()
+                    || params.t_cost() != configured.t_cost()
+                    || params.p_cost() != configured.p_cost()
+            })
+    }
+
+    /// Re-hashes the just verified password with the configured parameters. Failures are only
+    /// logged, the login goes on with the old hash.
+    async fn upgrade_password_hash(&self, user: &AppUser, password: &str) {
+        let hashed_password = match hash_password(&self.argon2, password) {
+            Ok(hash) => hash,
+            Err(e) => {
+                println!("Cannot re-hash password of {}: {}", user.username, e);
+                return;
+            }
+        };
+
+        match self
+            .user_repository
+            .update_password(user.id, &hashed_password)
+            .await
+        {
+            Ok(_) => println!("Upgraded password hash of {}", user.username),
+            Err(e) => println!(
+                "Cannot store re-hashed password of {}: {}",
+                user.username, e
+            ),
+        }
+    }
+
+    async fn set_password(&self, user_id: Uuid, password: &str) -> Result<(), PasswordChangeError> {
+        self.credential_policy
+            .check_password(password)
+            .map_err(PasswordChangeError::PolicyViolation)?;
+
+        let hashed_password = hash_password(&self.argon2, password)
+            .map_err(|_| PasswordChangeError::InternalError)?;
+
+        self.user_repository
+            .update_password(user_id, &hashed_password)
+            .await
+            .map_err(|_| PasswordChangeError::InternalError)?
+            .ok_or(PasswordChangeError::UserDoesNotExist)?;
+
+        Ok(())
+    }
+
+    pub async fn auth_bearer_token(
+        &self,
+        token: &str,
+    ) -> Result<(AppUser, TokenClaims), AuthError> {
+        let header = decode_header(token).map_err(|_| AuthError::InvalidToken)?;
+        let (algorithm, decoding_key) = self
+            .keys
+            .decoding_key(header.kid.as_deref())
+            .ok_or(AuthError::InvalidToken)?;
+
+        let claims = decode::<TokenClaims>(token, decoding_key, &Validation::new(algorithm))
+            .map_err(|_| AuthError::InvalidToken)?;
 
         let user_id = Uuid::parse_str(&claims.claims.id).map_err(|_| AuthError::InvalidToken)?;
 
@@ -146,14 +783,330 @@ impl AuthService {
             return Err(AuthError::ExpiredToken);
         }
 
+        let jti = Uuid::parse_str(&claims.claims.jti).map_err(|_| AuthError::InvalidToken)?;
+        if self
+            .revocation_repository
+            .is_token_revoked(jti)
+            .await
+            .map_err(|_| AuthError::InternalError)?
+        {
+            return Err(AuthError::RevokedToken);
+        }
+
+        let revoked_before = self
+            .revocation_repository
+            .get_revoked_before(user_id)
+            .await
+            .map_err(|_| AuthError::InternalError)?;
+        // Tokens issued in the second of the revocation pass here, those issued before it are
+        // still rejected through their revoked session below
+        if revoked_before.is_some_and(|t| claims.claims.created_at < t.timestamp()) {
+            return Err(AuthError::RevokedToken);
+        }
+
+        if let Some(sid) = &claims.claims.sid {
+            let session_id = Uuid::parse_str(sid).map_err(|_| AuthError::InvalidToken)?;
+            let session = self
+                .session_repository
+                .get(session_id)
+                .await
+                .map_err(|_| AuthError::InternalError)?;
+
+            match session {
+                Some(session) if session.user_id != user_id => Err(AuthError::InvalidToken)?,
+                Some(session) if session.revoked_at.is_some() => Err(AuthError::RevokedToken)?,
+                Some(session) => {
+                    let stale_before = now - session_seen_interval();
+                    if session.last_seen_at < stale_before {
+                        self.session_repository
+                            .mark_seen(session_id, now, stale_before)
+                            .await
+                            .map_err(|_| AuthError::InternalError)?
+                    }
+                }
+                _ => Err(AuthError::RevokedToken)?,
+            }
+        }
+
+        if claims.claims.act.is_some() {
+            let impersonator_id = claims
+                .claims
+                .impersonator_id()
+                .ok_or(AuthError::InvalidToken)?;
+            let impersonator = self
+                .user_repository
+                .get(impersonator_id)
+                .await
+                .map_err(|_| AuthError::InternalError)?
+                .filter(|admin| admin.disabled_at.is_none())
+                .ok_or(AuthError::RevokedToken)?;
+
+            // The token dies with the admin's account or permission
+            if !self
+                .may_impersonate(&impersonator)
+                .await
+                .map_err(|_| AuthError::InternalError)?
+            {
+                return Err(AuthError::RevokedToken);
+            }
+        }
+
         let user = self
             .user_repository
             .get(user_id)
             .await
             .map_err(|_| AuthError::InternalError)?;
         match user {
-            Some(user) => Ok(user),
+            Some(user) if user.disabled_at.is_some() => Err(AuthError::UserDisabled),
+            Some(user) => Ok((user, claims.claims)),
             None => Err(AuthError::UserDoesNotExist),
         }
     }
+
+    /// Issues a short-lived access token for `user_id` naming `admin` in its `act` claim.
+    /// It belongs to no session and comes without refresh token, so it simply expires.
+    pub async fn impersonate(
+        &self,
+        admin: &AppUser,
+        user_id: Uuid,
+    ) -> Result<ImpersonationToken, ImpersonationError> {
+        if admin.id == user_id {
+            return Err(ImpersonationError::OwnAccount);
+        }
+
+        let user = self
+            .user_repository
+            .get(user_id)
+            .await
+            .map_err(|_| ImpersonationError::InternalError)?
+            .ok_or(ImpersonationError::UserDoesNotExist)?;
+
+        if self
+            .may_impersonate(&user)
+            .await
+            .map_err(|_| ImpersonationError::InternalError)?
+        {
+            return Err(ImpersonationError::ProtectedAccount);
+        }
+        if user.disabled_at.is_some() {
+            return Err(ImpersonationError::UserDisabled);
+        }
+
+        let now = Utc::now();
+        let expires_at = now + Duration::minutes(self.config.impersonation_token_maxage.into());
+        let claims = TokenClaims {
+            id: user.id.to_string(),
+            jti: Uuid::new_v4().to_string(),
+            created_at: now.timestamp(),
+            exp: expires_at.timestamp(),
+            sid: None,
+            act: Some(ActorClaim {
+                sub: admin.id.to_string(),
+            }),
+        };
+
+        let access_token = encode(self.keys.header(), &claims, self.keys.encoding_key())
+            .map_err(|_| ImpersonationError::InternalError)?;
+
+        println!("{} started impersonating {}", admin.username, user.username);
+
+        Ok(ImpersonationToken {
+            access_token,
+            expires_at,
+        })
+    }
+
+    async fn may_impersonate(&self, user: &AppUser) -> Result<bool, PermissionError> {
+        self.permission_service
+            .has_permission(&user.account_role, IMPERSONATE_PERMISSION)
+            .await
+    }
+
+    pub fn jwks(&self) -> &JwkSet {
+        self.keys.jwks()
+    }
+
+    /// Revokes the access token described by `claims` together with its session and, if given,
+    /// the refresh token family it was issued with.
+    pub async fn logout(
+        &self,
+        claims: &TokenClaims,
+        refresh_token: Option<&str>,
+    ) -> Result<(), LogoutError> {
+        let user_id = Uuid::parse_str(&claims.id).map_err(|_| LogoutError::InvalidToken)?;
+        let jti = Uuid::parse_str(&claims.jti).map_err(|_| LogoutError::InvalidToken)?;
+        let expires_at =
+            DateTime::<Utc>::from_timestamp(claims.exp, 0).ok_or(LogoutError::InvalidToken)?;
+
+        self.revocation_repository
+            .revoke_token(jti, user_id, expires_at)
+            .await
+            .map_err(|_| LogoutError::InternalError)?;
+
+        // Impersonation tokens only end themselves, never the sessions of the impersonated user
+        if claims.act.is_some() {
+            return Ok(());
+        }
+
+        let now = Utc::now();
+
+        if let Some(sid) = &claims.sid {
+            let session_id = Uuid::parse_str(sid).map_err(|_| LogoutError::InvalidToken)?;
+            self.session_repository
+                .revoke(session_id, user_id, now)
+                .await
+                .map_err(|_| LogoutError::InternalError)?;
+            self.refresh_token_repository
+                .revoke_family(session_id, now)
+                .await
+                .map_err(|_| LogoutError::InternalError)?;
+        }
+
+        if let Some(refresh_token) = refresh_token {
+            self.refresh_token_repository
+                .revoke_family_by_token_hash(&hash_token(refresh_token), user_id, now)
+                .await
+                .map_err(|_| LogoutError::InternalError)?;
+        }
+
+        Ok(())
+    }
+
+    /// Invalidates every access, refresh and API token issued to the user so far.
+    pub async fn logout_everywhere(&self, user_id: Uuid) -> Result<(), LogoutError> {
+        let now = Utc::now();
+
+        // Token issue times have whole seconds, so has the cutoff. Tokens of earlier in the same
+        // second end with the sessions revoked below.
+        self.revocation_repository
+            .revoke_all_for_user(user_id, now.trunc_subsecs(0))
+            .await
+            .map_err(|_| LogoutError::InternalError)?;
+
+        self.refresh_token_repository
+            .revoke_all_for_user(user_id, now)
+            .await
+            .map_err(|_| LogoutError::InternalError)?;
+
+        self.session_repository
+            .revoke_all_for_user(user_id, now)
+            .await
+            .map_err(|_| LogoutError::InternalError)?;
+
+        self.api_token_repository
+            .delete_all_for_user(user_id)
+            .await
+            .map_err(|_| LogoutError::InternalError)?;
+
+        Ok(())
+    }
+
+    fn create_access_token(
+        &self,
+        user: &AppUser,
+        session_id: Uuid,
+    ) -> Result<String, jsonwebtoken::errors::Error> {
+        let now = Utc::now();
+
+        let claims = TokenClaims {
+            id: user.id.to_string(),
+            jti: Uuid::new_v4().to_string(),
+            created_at: now.timestamp(),
+            exp: (now + Duration::minutes(self.config.jwt_maxage.into())).timestamp(),
+            sid: Some(session_id.to_string()),
+            act: None,
+        };
+
+        encode(self.keys.header(), &claims, self.keys.encoding_key())
+    }
+
+    fn create_mfa_token(&self, user: &AppUser) -> Result<String, jsonwebtoken::errors::Error> {
+        let claims = MfaClaims {
+            id: user.id.to_string(),
+            aud: MFA_AUDIENCE.to_owned(),
+            exp: (Utc::now() + Duration::minutes(self.config.mfa_token_maxage.into())).timestamp(),
+        };
+
+        encode(self.keys.header(), &claims, self.keys.encoding_key())
+    }
+
+    async fn create_session(
+        &self,
+        user_id: Uuid,
+        ip: IpAddr,
+        user_agent: Option<&str>,
+    ) -> Result<Uuid, sqlx::Error> {
+        let now = Utc::now();
+
+        self.session_repository
+            .insert(Session {
+                id: Uuid::new_v4(),
+                user_id,
+                device_name: device_name(user_agent),
+                ip: Some(ip.to_string()),
+                created_at: now,
+                last_seen_at: now,
+                revoked_at: None,
+            })
+            .await
+    }
+
+    async fn create_refresh_token(
+        &self,
+        user_id: Uuid,
+        family_id: Uuid,
+    ) -> Result<String, sqlx::Error> {
+        let token = generate_token();
+        let now = Utc::now();
+
+        self.refresh_token_repository
+            .insert(RefreshToken {
+                id: Uuid::new_v4(),
+                user_id,
+                family_id,
+                token_hash: hash_token(&token),
+                created_at: now,
+                expires_at: now + Duration::minutes(self.config.refresh_token_maxage.into()),
+                used_at: None,
+                revoked_at: None,
+            })
+            .await?;
+
+        Ok(token)
+    }
+
+    /// Revokes the family of a reused refresh token and ends its session, whose access tokens
+    /// may have been stolen along with it.
+    async fn revoke_reused_family(&self, stored_token: &RefreshToken) -> RefreshError {
+        println!(
+            "Refresh token reuse detected, revoking token family {}",
+            stored_token.family_id
+        );
+        let now = Utc::now();
+
+        let revoked = self
+            .refresh_token_repository
+            .revoke_family(stored_token.family_id, now)
+            .await;
+        let session_revoked = self
+            .session_repository
+            .revoke(stored_token.family_id, stored_token.user_id, now)
+            .await;
+
+        match (revoked, session_revoked) {
+            (Ok(_), Ok(_)) => RefreshError::ReusedToken,
+            _ => RefreshError::InternalError,
+        }
+    }
+}
+
+fn session_seen_interval() -> Duration {
+    Duration::minutes(SESSION_SEEN_INTERVAL_MINUTES)
+}
+
+fn hash_password(argon2: &Argon2, password: &str) -> Result<String, argon2::password_hash::Error> {
+    let salt = SaltString::generate(&mut OsRng);
+    argon2
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
 }
@@ -0,0 +1,208 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ed25519_dalek::pkcs8::{DecodePrivateKey, DecodePublicKey};
+use jsonwebtoken::{
+    jwk::{
+        AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+        OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+    },
+    Algorithm, DecodingKey, EncodingKey, Header,
+};
+use rsa::{pkcs1::DecodeRsaPrivateKey, pkcs1::DecodeRsaPublicKey, traits::PublicKeyParts};
+use std::str::FromStr;
+
+use crate::config::Config;
+
+struct VerificationKey {
+    kid: Option<String>,
+    algorithm: Algorithm,
+    key: DecodingKey,
+}
+
+/// Signing key and every key accepted when verifying tokens.
+pub struct JwtKeys {
+    header: Header,
+    encoding_key: EncodingKey,
+    verification_keys: Vec<VerificationKey>,
+    jwks: JwkSet,
+}
+
+impl JwtKeys {
+    pub fn from_config(config: &Config) -> Result<JwtKeys, String> {
+        let algorithm = config.jwt_algorithm;
+
+        if is_hmac(algorithm) {
+            let secret = config
+                .jwt_secret
+                .as_ref()
+                .ok_or("JWT_SECRET must be set for HMAC algorithms")?;
+            return Ok(JwtKeys {
+                header: Header::new(algorithm),
+                encoding_key: EncodingKey::from_secret(secret.as_ref()),
+                verification_keys: vec![VerificationKey {
+                    kid: None,
+                    algorithm,
+                    key: DecodingKey::from_secret(secret.as_ref()),
+                }],
+                jwks: JwkSet { keys: Vec::new() },
+            });
+        }
+
+        let kid = config.jwt_key_id.clone().ok_or("JWT_KEY_ID must be set")?;
+        let private_key_path = config
+            .jwt_private_key_path
+            .as_ref()
+            .ok_or("JWT_PRIVATE_KEY_PATH must be set")?;
+        let private_pem = std::fs::read_to_string(private_key_path)
+            .map_err(|e| format!("Cannot read {}: {}", private_key_path, e))?;
+
+        let (encoding_key, signing_jwk) = if algorithm == Algorithm::EdDSA {
+            let signing_key = ed25519_dalek::SigningKey::from_pkcs8_pem(&private_pem)
+                .map_err(|_| "JWT_PRIVATE_KEY_PATH is not a PKCS#8 Ed25519 key")?;
+            (
+                EncodingKey::from_ed_pem(private_pem.as_bytes()).map_err(|e| e.to_string())?,
+                ed25519_jwk(&kid, signing_key.verifying_key()),
+            )
+        } else if is_rsa(algorithm) {
+            let private_key = rsa::RsaPrivateKey::from_pkcs8_pem(&private_pem)
+                .or_else(|_| rsa::RsaPrivateKey::from_pkcs1_pem(&private_pem))
+                .map_err(|_| "JWT_PRIVATE_KEY_PATH is not an RSA private key")?;
+            (
+                EncodingKey::from_rsa_pem(private_pem.as_bytes()).map_err(|e| e.to_string())?,
+                rsa_jwk(&kid, algorithm, &private_key.to_public_key()),
+            )
+        } else {
+            return Err(format!("Unsupported JWT_ALGORITHM {:?}", algorithm));
+        };
+
+        let mut jwks = JwkSet {
+            keys: vec![signing_jwk],
+        };
+
+        for (retired_kid, path) in &config.jwt_verification_keys {
+            let public_pem = std::fs::read_to_string(path)
+                .map_err(|e| format!("Cannot read {}: {}", path, e))?;
+            jwks.keys
+                .push(public_key_jwk(retired_kid, algorithm, &public_pem)?);
+        }
+
+        let verification_keys = jwks
+            .keys
+            .iter()
+            .map(|jwk| {
+                Ok(VerificationKey {
+                    kid: jwk.common.key_id.clone(),
+                    algorithm: jwk
+                        .common
+                        .key_algorithm
+                        .and_then(|alg| Algorithm::from_str(&alg.to_string()).ok())
+                        .ok_or("Unsupported verification key algorithm")?,
+                    key: DecodingKey::from_jwk(jwk).map_err(|e| e.to_string())?,
+                })
+            })
+            .collect::<Result<Vec<VerificationKey>, String>>()?;
+
+        let mut header = Header::new(algorithm);
+        header.kid = Some(kid);
+
+        Ok(JwtKeys {
+            header,
+            encoding_key,
+            verification_keys,
+            jwks,
+        })
+    }
+
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    pub fn encoding_key(&self) -> &EncodingKey {
+        &self.encoding_key
+    }
+
+    /// Finds the key a token with the given `kid` header was signed with.
+    pub fn decoding_key(&self, kid: Option<&str>) -> Option<(Algorithm, &DecodingKey)> {
+        self.verification_keys
+            .iter()
+            .find(|key| key.kid.as_deref() == kid)
+            .map(|key| (key.algorithm, &key.key))
+    }
+
+    /// Public keys in JWKS format. Empty when tokens are signed with a shared secret.
+    pub fn jwks(&self) -> &JwkSet {
+        &self.jwks
+    }
+}
+
+fn is_hmac(algorithm: Algorithm) -> bool {
+    matches!(
+        algorithm,
+        Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+    )
+}
+
+fn is_rsa(algorithm: Algorithm) -> bool {
+    matches!(
+        algorithm,
+        Algorithm::RS256
+            | Algorithm::RS384
+            | Algorithm::RS512
+            | Algorithm::PS256
+            | Algorithm::PS384
+            | Algorithm::PS512
+    )
+}
+
+fn public_key_jwk(kid: &str, algorithm: Algorithm, public_pem: &str) -> Result<Jwk, String> {
+    if let Ok(public_key) = rsa::RsaPublicKey::from_public_key_pem(public_pem)
+        .or_else(|_| rsa::RsaPublicKey::from_pkcs1_pem(public_pem))
+    {
+        // A retired key may come from before an algorithm change
+        let rsa_algorithm = if is_rsa(algorithm) {
+            algorithm
+        } else {
+            Algorithm::RS256
+        };
+        return Ok(rsa_jwk(kid, rsa_algorithm, &public_key));
+    }
+
+    if let Ok(verifying_key) = ed25519_dalek::VerifyingKey::from_public_key_pem(public_pem) {
+        return Ok(ed25519_jwk(kid, verifying_key));
+    }
+
+    Err(format!(
+        "Verification key {} is not an RSA or Ed25519 public key",
+        kid
+    ))
+}
+
+fn rsa_jwk(kid: &str, algorithm: Algorithm, public_key: &rsa::RsaPublicKey) -> Jwk {
+    Jwk {
+        common: common_parameters(kid, algorithm),
+        algorithm: AlgorithmParameters::RSA(RSAKeyParameters {
+            key_type: RSAKeyType::RSA,
+            n: URL_SAFE_NO_PAD.encode(public_key.n().to_bytes_be()),
+            e: URL_SAFE_NO_PAD.encode(public_key.e().to_bytes_be()),
+        }),
+    }
+}
+
+fn ed25519_jwk(kid: &str, verifying_key: ed25519_dalek::VerifyingKey) -> Jwk {
+    Jwk {
+        common: common_parameters(kid, Algorithm::EdDSA),
+        algorithm: AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+            key_type: OctetKeyPairType::OctetKeyPair,
+            curve: EllipticCurve::Ed25519,
+            x: URL_SAFE_NO_PAD.encode(verifying_key.to_bytes()),
+        }),
+    }
+}
+
+fn common_parameters(kid: &str, algorithm: Algorithm) -> CommonParameters {
+    CommonParameters {
+        public_key_use: Some(PublicKeyUse::Signature),
+        key_algorithm: KeyAlgorithm::from_str(&format!("{:?}", algorithm)).ok(),
+        key_id: Some(kid.to_owned()),
+        ..Default::default()
+    }
+}
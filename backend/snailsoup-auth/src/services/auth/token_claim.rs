@@ -1,8 +1,40 @@
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenClaims {
     pub id: String,
+    pub jti: String,
     pub created_at: i64,
     pub exp: i64,
+    /// Session the token was issued for, absent in tokens issued before sessions were recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+    /// Admin acting as the user, only present in impersonation tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<ActorClaim>,
+}
+
+impl TokenClaims {
+    /// Id of the admin impersonating the user, `None` for regular access tokens
+    pub fn impersonator_id(&self) -> Option<Uuid> {
+        self.act
+            .as_ref()
+            .and_then(|act| Uuid::parse_str(&act.sub).ok())
+    }
+}
+
+/// Actor claim of RFC 8693 section 4.1
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActorClaim {
+    pub sub: String,
+}
+
+/// Claims of the short-lived token handed out after the password step when a second factor is required.
+/// The audience keeps it from being accepted as an access token.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MfaClaims {
+    pub id: String,
+    pub aud: String,
+    pub exp: i64,
 }
@@ -0,0 +1,191 @@
+use std::{collections::HashSet, fs};
+use unicode_normalization::UnicodeNormalization;
+use unicode_security::MixedScript;
+
+use crate::config::Config;
+
+/// Characters allowed in usernames besides letters and digits
+const USERNAME_PUNCTUATION: [char; 3] = ['.', '_', '-'];
+
+/// Rule a username or password does not meet, reported per field to the client
+pub struct PolicyViolation {
+    /// `username` or `password`
+    pub field: &'static str,
+    pub code: &'static str,
+    pub message: String,
+}
+
+/// Rules for new usernames and passwords. Existing accounts are not checked,
+/// so tightening the policy never locks anyone out.
+pub struct CredentialPolicy {
+    username_min_length: usize,
+    username_max_length: usize,
+    password_min_length: usize,
+    password_max_length: usize,
+    password_min_character_classes: usize,
+    /// Lowercased entries of `BREACHED_PASSWORDS_FILE`
+    breached_passwords: HashSet<String>,
+}
+
+impl CredentialPolicy {
+    pub fn from_config(config: &Config) -> Result<CredentialPolicy, String> {
+        let breached_passwords = match &config.breached_passwords_file {
+            Some(path) => fs::read_to_string(path)
+                .map_err(|e| format!("Cannot read BREACHED_PASSWORDS_FILE {}: {}", path, e))?
+                .lines()
+                .map(|line| line.trim_end_matches('\r').to_lowercase())
+                .filter(|line| !line.is_empty())
+                .collect(),
+            None => HashSet::new(),
+        };
+
+        Ok(CredentialPolicy {
+            username_min_length: config.username_min_length as usize,
+            username_max_length: config.username_max_length as usize,
+            password_min_length: config.password_min_length as usize,
+            password_max_length: config.password_max_length as usize,
+            password_min_character_classes: config.password_min_character_classes as usize,
+            breached_passwords,
+        })
+    }
+
+    /// Returns the username in NFKC form, which is how it gets stored
+    pub fn check_username(&self, username: &str) -> Result<String, Vec<PolicyViolation>> {
+        let username: String = username.nfkc().collect();
+        let length = username.chars().count();
+        let mut violations = vec![];
+
+        if length < self.username_min_length {
+            violations.push(violation(
+                "username",
+                "too_short",
+                format!(
+                    "Username must be at least {} characters long",
+                    self.username_min_length
+                ),
+            ));
+        }
+
+        if length > self.username_max_length {
+            violations.push(violation(
+                "username",
+                "too_long",
+                format!(
+                    "Username must be at most {} characters long",
+                    self.username_max_length
+                ),
+            ));
+        }
+
+        if !username
+            .chars()
+            .all(|c| c.is_alphanumeric() || USERNAME_PUNCTUATION.contains(&c))
+        {
+            violations.push(violation(
+                "username",
+                "invalid_characters",
+                "Username may only contain letters, digits, '.', '_' and '-'".to_owned(),
+            ));
+        }
+
+        // Letters of one script may look like those of another (Latin `a`, Cyrillic `а`), so
+        // mixing them would allow names that cannot be told apart from existing ones (UTS #39)
+        if !username.as_str().is_single_script() {
+            violations.push(violation(
+                "username",
+                "mixed_scripts",
+                "Username must not mix letters of different scripts".to_owned(),
+            ));
+        }
+
+        if violations.is_empty() {
+            Ok(username)
+        } else {
+            Err(violations)
+        }
+    }
+
+    pub fn check_password(&self, password: &str) -> Result<(), Vec<PolicyViolation>> {
+        let length = password.chars().count();
+        let mut violations = vec![];
+
+        if length < self.password_min_length {
+            violations.push(violation(
+                "password",
+                "too_short",
+                format!(
+                    "Password must be at least {} characters long",
+                    self.password_min_length
+                ),
+            ));
+        }
+
+        if length > self.password_max_length {
+            violations.push(violation(
+                "password",
+                "too_long",
+                format!(
+                    "Password must be at most {} characters long",
+                    self.password_max_length
+                ),
+            ));
+        }
+
+        if password.chars().any(char::is_control) {
+            violations.push(violation(
+                "password",
+                "invalid_characters",
+                "Password must not contain control characters".to_owned(),
+            ));
+        }
+
+        let character_classes = [
+            password.chars().any(char::is_lowercase),
+            password.chars().any(char::is_uppercase),
+            password.chars().any(|c| c.is_numeric()),
+            password.chars().any(|c| !c.is_alphanumeric()),
+        ]
+        .into_iter()
+        .filter(|present| *present)
+        .count();
+
+        if character_classes < self.password_min_character_classes {
+            violations.push(violation(
+                "password",
+                "too_simple",
+                format!(
+                    "Password must mix at least {} of lowercase letters, uppercase letters, digits and other characters",
+                    self.password_min_character_classes
+                ),
+            ));
+        }
+
+        if self.breached_passwords.contains(&password.to_lowercase()) {
+            violations.push(violation(
+                "password",
+                "breached",
+                "Password appears in a list of breached passwords".to_owned(),
+            ));
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+/// Form of a username or email used for lookups, so that logins and their throttling
+/// ignore case and Unicode compatibility variants
+pub fn normalize_login(login: &str) -> String {
+    login.nfkc().collect::<String>().to_lowercase()
+}
+
+fn violation(field: &'static str, code: &'static str, message: String) -> PolicyViolation {
+    PolicyViolation {
+        field,
+        code,
+        message,
+    }
+}
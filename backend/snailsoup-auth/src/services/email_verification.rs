@@ -0,0 +1,188 @@
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    db::{AppUserRepository, EmailVerificationRepository},
+    domain::{app_user::AppUser, email_verification::EmailVerificationToken},
+    services::mail::{Email, MailError, Mailer},
+    utils::token::{generate_token, hash_token},
+};
+
+pub enum VerificationRequestError {
+    InternalError,
+}
+
+pub enum VerificationError {
+    InvalidToken,
+    ExpiredToken,
+    InternalError,
+}
+
+pub struct EmailVerificationService {
+    user_repository: Arc<AppUserRepository>,
+    verification_repository: Arc<EmailVerificationRepository>,
+    mailer: Arc<dyn Mailer>,
+    config: Config,
+}
+
+impl EmailVerificationService {
+    pub fn new(
+        user_repository: Arc<AppUserRepository>,
+        verification_repository: Arc<EmailVerificationRepository>,
+        mailer: Arc<dyn Mailer>,
+        config: Config,
+    ) -> EmailVerificationService {
+        EmailVerificationService {
+            user_repository,
+            verification_repository,
+            mailer,
+            config,
+        }
+    }
+
+    /// Mails a one-time verification link to the user's address, if it has one that is not
+    /// verified yet. Links sent before stop working.
+    pub async fn send_verification(&self, user: &AppUser) -> Result<(), VerificationRequestError> {
+        let email = match (&user.email, user.email_verified_at) {
+            (Some(email), None) => email.clone(),
+            _ => return Ok(()),
+        };
+
+        let token = generate_token();
+        let now = Utc::now();
+
+        self.verification_repository
+            .replace_for_user(EmailVerificationToken {
+                id: Uuid::new_v4(),
+                user_id: user.id,
+                email: email.clone(),
+                token_hash: hash_token(&token),
+                created_at: now,
+                expires_at: now
+                    + Duration::minutes(self.config.email_verification_token_maxage.into()),
+                used_at: None,
+            })
+            .await
+            .map_err(|_| VerificationRequestError::InternalError)?;
+
+        let sent = self
+            .mailer
+            .send(Email {
+                to: email,
+                subject: "Verify your snail-soup email address".to_owned(),
+                body: format!(
+                    "Hi {},\n\nuse the link below to confirm this address. It expires in {} minutes.\n\n{}?token={}\n\nIf you did not create a snail-soup account, ignore this message.",
+                    user.username,
+                    self.config.email_verification_token_maxage,
+                    self.config.email_verification_url,
+                    token
+                ),
+            })
+            .await;
+
+        if let Err(e) = sent {
+            match e {
+                MailError::InvalidAddress => {
+                    println!("{} has an invalid email address", user.username)
+                }
+                MailError::Transport(e) => println!("Cannot send verification email: {}", e),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Tells the owner of an existing account that someone tried to register with its email
+    /// address or, without one, its username. Sent instead of a verification link when
+    /// registration conflicts are concealed, so both outcomes cost the same.
+    pub async fn notify_registration_attempt(
+        &self,
+        username: &str,
+        email: Option<&str>,
+    ) -> Result<(), VerificationRequestError> {
+        let user = match email {
+            Some(email) => self.user_repository.get_by_email(email).await,
+            None => self.user_repository.get_by_name(username).await,
+        }
+        .map_err(|_| VerificationRequestError::InternalError)?;
+
+        let (user, to) = match user {
+            Some(user) => match user.email.clone() {
+                Some(to) => (user, to),
+                None => return Ok(()),
+            },
+            None => return Ok(()),
+        };
+
+        let sent = self
+            .mailer
+            .send(Email {
+                to,
+                subject: "Someone tried to register with your snail-soup account".to_owned(),
+                body: format!(
+                    "Hi {},\n\nsomeone tried to create a new snail-soup account with your {}. Your account was not changed.\n\nIf it was you, sign in instead or reset your password. Otherwise you can ignore this message.",
+                    user.username,
+                    if email.is_some() { "email address" } else { "username" }
+                ),
+            })
+            .await;
+
+        if let Err(e) = sent {
+            match e {
+                MailError::InvalidAddress => {
+                    println!("{} has an invalid email address", user.username)
+                }
+                MailError::Transport(e) => println!("Cannot send registration notice: {}", e),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sends a new link to the account with the given username or email.
+    /// Unknown and already verified accounts are ignored so callers cannot tell them apart.
+    pub async fn resend(&self, login: &str) -> Result<(), VerificationRequestError> {
+        let user = self
+            .user_repository
+            .get_by_login(login)
+            .await
+            .map_err(|_| VerificationRequestError::InternalError)?;
+
+        match user {
+            Some(user) => self.send_verification(&user).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Consumes a verification token and marks the address it was sent to as verified.
+    pub async fn verify(&self, token: &str) -> Result<(), VerificationError> {
+        let stored_token = self
+            .verification_repository
+            .get_by_hash(&hash_token(token))
+            .await
+            .map_err(|_| VerificationError::InternalError)?
+            .ok_or(VerificationError::InvalidToken)?;
+
+        let now = Utc::now();
+
+        if stored_token.expires_at < now {
+            return Err(VerificationError::ExpiredToken);
+        }
+
+        self.verification_repository
+            .mark_used(stored_token.id, now)
+            .await
+            .map_err(|_| VerificationError::InternalError)?
+            .ok_or(VerificationError::InvalidToken)?;
+
+        self.user_repository
+            .set_email_verified(stored_token.user_id, &stored_token.email, now)
+            .await
+            .map_err(|_| VerificationError::InternalError)?
+            .ok_or(VerificationError::InvalidToken)?;
+
+        Ok(())
+    }
+}
@@ -19,6 +19,11 @@ pub enum CreateError {
     Validation(String),
 }
 
+pub enum DeleteError {
+    Internal,
+    InUse,
+}
+
 pub struct ExpenseService {
     expense_repository: Arc<ExpenseRepository>,
     user_repository: Arc<AppUserRepository>,
@@ -30,25 +35,23 @@ impl ExpenseService {
         user_repository: Arc<AppUserRepository>,
     ) -> ExpenseService {
         ExpenseService {
-            expense_repository: expense_repository,
-            user_repository: user_repository,
+            expense_repository,
+            user_repository,
         }
     }
 
     pub async fn get_expense(&self, expense_id: Uuid) -> Result<Option<FullExpense>, GetError> {
-        Ok(self
-            .expense_repository
+        self.expense_repository
             .get_expense(expense_id)
             .await
-            .map_err(|_| GetError::Internal)?)
+            .map_err(|_| GetError::Internal)
     }
 
     pub async fn get_all_expenses(&self) -> Result<Vec<Expense>, GetError> {
-        Ok(self
-            .expense_repository
+        self.expense_repository
             .get_all_expenses()
             .await
-            .map_err(|_| GetError::Internal)?)
+            .map_err(|_| GetError::Internal)
     }
 
     pub async fn get_expenses_for_user(
@@ -126,6 +129,18 @@ impl ExpenseService {
             }
         }
 
+        if let Some(category_id) = full_expense.expense.category_id {
+            let category = self
+                .expense_repository
+                .get_category(category_id)
+                .await
+                .map_err(|_| CreateError::Internal)?;
+
+            if category.is_none_or(|c| c.data.user_id != full_expense.expense.user_id) {
+                return Err(CreateError::Validation("Invalid category".to_owned()));
+            }
+        }
+
         let new_expense = FullExpense {
             id: Uuid::new_v4(),
             data: full_expense,
@@ -192,6 +207,13 @@ impl ExpenseService {
             .map_err(|_| GetError::Internal)
     }
 
+    pub async fn delete_tag(&self, tag_id: Uuid) -> Result<Option<Uuid>, DeleteError> {
+        self.expense_repository
+            .delete_tag(tag_id)
+            .await
+            .map_err(|_| DeleteError::Internal)
+    }
+
     pub async fn get_categories_for_user(
         &self,
         user_id: Uuid,
@@ -249,4 +271,26 @@ impl ExpenseService {
             .await
             .map_err(|_| GetError::Internal)
     }
+
+    pub async fn delete_category(
+        &self,
+        category_id: Uuid,
+        force: bool,
+    ) -> Result<Option<Uuid>, DeleteError> {
+        // Expenses of a deleted category are left uncategorized by the foreign key
+        if !force
+            && self
+                .expense_repository
+                .is_category_in_use(category_id)
+                .await
+                .map_err(|_| DeleteError::Internal)?
+        {
+            return Err(DeleteError::InUse);
+        }
+
+        self.expense_repository
+            .delete_category(category_id)
+            .await
+            .map_err(|_| DeleteError::Internal)
+    }
 }
@@ -0,0 +1,129 @@
+use chrono::{DateTime, Utc};
+use jsonwebtoken::decode_header;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        api_token::{API_TOKEN_PREFIX, API_TOKEN_SCOPES},
+        app_user::AppUser,
+    },
+    services::{
+        api_token::ApiTokenService,
+        auth::{AuthError, AuthService},
+        oauth::{AccessTokenError, ClientCredentials, OAuthService, TokenError, ACCESS_TOKEN_TYPE},
+    },
+};
+
+/// Token introspection (RFC 7662) for resource servers, which authenticate as
+/// confidential OAuth clients.
+pub struct IntrospectionService {
+    auth_service: Arc<AuthService>,
+    api_token_service: Arc<ApiTokenService>,
+    oauth_service: Arc<OAuthService>,
+}
+
+/// State of an active token.
+pub struct Introspection {
+    /// `None` for client credentials tokens
+    pub user: Option<AppUser>,
+    /// OAuth client an access token was issued to
+    pub client_id: Option<Uuid>,
+    /// Access tokens carry every scope, API and OAuth tokens only their own
+    pub scopes: Vec<String>,
+    pub issued_at: Option<DateTime<Utc>>,
+    /// `None` for API tokens without expiry
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Admin acting as the user with an impersonation token
+    pub impersonator_id: Option<Uuid>,
+}
+
+pub enum IntrospectionError {
+    InvalidClient,
+    InternalError,
+}
+
+impl IntrospectionService {
+    pub fn new(
+        auth_service: Arc<AuthService>,
+        api_token_service: Arc<ApiTokenService>,
+        oauth_service: Arc<OAuthService>,
+    ) -> IntrospectionService {
+        IntrospectionService {
+            auth_service,
+            api_token_service,
+            oauth_service,
+        }
+    }
+
+    /// Returns `None` when the token is invalid, expired, revoked or belongs to a disabled
+    /// or deleted account, i.e. whenever the API itself would reject it.
+    pub async fn introspect(
+        &self,
+        credentials: ClientCredentials,
+        token: &str,
+    ) -> Result<Option<Introspection>, IntrospectionError> {
+        self.oauth_service
+            .authenticate_confidential_client(credentials)
+            .await
+            .map_err(|e| match e {
+                TokenError::InternalError => IntrospectionError::InternalError,
+                _ => IntrospectionError::InvalidClient,
+            })?;
+
+        // Access tokens issued to OAuth clients are told apart by their `typ` header (RFC 9068)
+        if decode_header(token).is_ok_and(|h| h.typ.as_deref() == Some(ACCESS_TOKEN_TYPE)) {
+            return self.introspect_access_token(token).await;
+        }
+
+        let introspection = if token.starts_with(API_TOKEN_PREFIX) {
+            self.api_token_service
+                .authenticate(token)
+                .await
+                .map(|(user, api_token)| Introspection {
+                    user: Some(user),
+                    client_id: None,
+                    scopes: api_token.scopes,
+                    issued_at: Some(api_token.created_at),
+                    expires_at: api_token.expires_at,
+                    impersonator_id: None,
+                })
+        } else {
+            self.auth_service
+                .auth_bearer_token(token)
+                .await
+                .map(|(user, claims)| Introspection {
+                    user: Some(user),
+                    client_id: None,
+                    scopes: API_TOKEN_SCOPES.iter().map(|s| s.to_string()).collect(),
+                    issued_at: DateTime::from_timestamp(claims.created_at, 0),
+                    expires_at: DateTime::from_timestamp(claims.exp, 0),
+                    impersonator_id: claims.impersonator_id(),
+                })
+        };
+
+        match introspection {
+            Ok(introspection) => Ok(Some(introspection)),
+            Err(AuthError::InternalError) => Err(IntrospectionError::InternalError),
+            Err(_) => Ok(None),
+        }
+    }
+
+    async fn introspect_access_token(
+        &self,
+        token: &str,
+    ) -> Result<Option<Introspection>, IntrospectionError> {
+        match self.oauth_service.validate_access_token(token).await {
+            Ok(access_token) => Ok(Some(Introspection {
+                user: access_token.user,
+                client_id: Some(access_token.client_id),
+                scopes: access_token.scopes,
+                issued_at: access_token.issued_at,
+                expires_at: access_token.expires_at,
+                impersonator_id: None,
+            })),
+            Err(AccessTokenError::InternalError) => Err(IntrospectionError::InternalError),
+            Err(AccessTokenError::InvalidToken) => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    db::InviteCodeRepository,
+    domain::invite_code::InviteCode,
+    utils::token::{generate_token, hash_token},
+};
+
+pub struct InviteService {
+    invite_code_repository: Arc<InviteCodeRepository>,
+}
+
+pub enum InviteError {
+    InvalidMaxUses,
+    ExpiryInPast,
+    /// Unknown, expired or used up
+    InvalidCode,
+    InviteDoesNotExist,
+    InternalError,
+}
+
+impl InviteService {
+    pub fn new(invite_code_repository: Arc<InviteCodeRepository>) -> InviteService {
+        InviteService {
+            invite_code_repository,
+        }
+    }
+
+    /// Creates a code valid for `max_uses` registrations. The plain code is returned only here,
+    /// just its hash is stored.
+    pub async fn create(
+        &self,
+        created_by: Uuid,
+        max_uses: i32,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(InviteCode, String), InviteError> {
+        if max_uses < 1 {
+            return Err(InviteError::InvalidMaxUses);
+        }
+
+        let now = Utc::now();
+        if expires_at.is_some_and(|t| t <= now) {
+            return Err(InviteError::ExpiryInPast);
+        }
+
+        let code = generate_token();
+        let invite_code = InviteCode {
+            id: Uuid::new_v4(),
+            code_hash: hash_token(&code),
+            max_uses,
+            uses: 0,
+            created_by: Some(created_by),
+            created_at: now,
+            expires_at,
+        };
+
+        self.invite_code_repository
+            .insert(invite_code.clone())
+            .await
+            .map_err(|_| InviteError::InternalError)?;
+
+        Ok((invite_code, code))
+    }
+
+    pub async fn list(&self) -> Result<Vec<InviteCode>, InviteError> {
+        self.invite_code_repository
+            .get_all()
+            .await
+            .map_err(|_| InviteError::InternalError)
+    }
+
+    pub async fn delete(&self, id: Uuid) -> Result<(), InviteError> {
+        self.invite_code_repository
+            .delete(id)
+            .await
+            .map_err(|_| InviteError::InternalError)?
+            .ok_or(InviteError::InviteDoesNotExist)?;
+
+        Ok(())
+    }
+
+    /// Takes one use of the code, returns the id of the invite
+    pub async fn redeem(&self, code: &str) -> Result<Uuid, InviteError> {
+        self.invite_code_repository
+            .redeem(&hash_token(code), Utc::now())
+            .await
+            .map_err(|_| InviteError::InternalError)?
+            .ok_or(InviteError::InvalidCode)
+    }
+
+    pub async fn release(&self, id: Uuid) -> Result<(), InviteError> {
+        self.invite_code_repository
+            .release(id)
+            .await
+            .map_err(|_| InviteError::InternalError)
+    }
+}
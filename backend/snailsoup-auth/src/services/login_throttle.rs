@@ -0,0 +1,210 @@
+use chrono::{DateTime, Duration, Utc};
+use std::{net::IpAddr, sync::Arc};
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    db::{AppUserRepository, LoginFailureRepository},
+    domain::{app_user::AppUser, login_failure::LoginFailure},
+    services::credential_policy::normalize_login,
+};
+
+const USER: &str = "user";
+const UNKNOWN_LOGIN: &str = "unknown_login";
+const IP: &str = "ip";
+/// Upper bound of the delay between two failed attempts for the same account
+const MAX_DELAY_SECONDS: i64 = 60;
+
+/// Subject of the per-account counter. Accounts are counted by id so that the username, the
+/// email address and every spelling of them share one counter. Logins without an account get
+/// a counter of their own and are throttled alike, which keeps unknown names indistinguishable.
+pub enum AccountKey {
+    User(Uuid),
+    UnknownLogin(String),
+}
+
+impl AccountKey {
+    pub fn for_login(user: Option<&AppUser>, login: &str) -> AccountKey {
+        match user {
+            Some(user) => AccountKey::User(user.id),
+            None => AccountKey::UnknownLogin(normalize_login(login)),
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            AccountKey::User(_) => USER,
+            AccountKey::UnknownLogin(_) => UNKNOWN_LOGIN,
+        }
+    }
+
+    fn subject(&self) -> String {
+        match self {
+            AccountKey::User(id) => id.to_string(),
+            AccountKey::UnknownLogin(login) => login.clone(),
+        }
+    }
+}
+
+pub struct LoginThrottleService {
+    login_failure_repository: Arc<LoginFailureRepository>,
+    user_repository: Arc<AppUserRepository>,
+    config: Config,
+}
+
+pub enum ThrottleError {
+    /// Attempt came too early, retry after the given number of seconds
+    TooManyAttempts(i64),
+    /// Account is locked for the given number of seconds
+    Locked(i64),
+    InternalError,
+}
+
+pub enum UnlockError {
+    UserDoesNotExist,
+    InternalError,
+}
+
+impl LoginThrottleService {
+    pub fn new(
+        login_failure_repository: Arc<LoginFailureRepository>,
+        user_repository: Arc<AppUserRepository>,
+        config: Config,
+    ) -> LoginThrottleService {
+        LoginThrottleService {
+            login_failure_repository,
+            user_repository,
+            config,
+        }
+    }
+
+    /// Rejects the attempt while the account or the client address is locked out,
+    /// or when it comes before the delay following the last failure has passed.
+    pub async fn check(&self, account: &AccountKey, ip: IpAddr) -> Result<(), ThrottleError> {
+        let now = Utc::now();
+
+        let by_ip = self
+            .login_failure_repository
+            .get(IP, &ip.to_string())
+            .await
+            .map_err(|_| ThrottleError::InternalError)?;
+
+        if let Some(locked_until) = by_ip.and_then(|f| f.locked_until).filter(|t| *t > now) {
+            return Err(ThrottleError::TooManyAttempts(seconds_until(
+                now,
+                locked_until,
+            )));
+        }
+
+        let by_account = self
+            .login_failure_repository
+            .get(account.kind(), &account.subject())
+            .await
+            .map_err(|_| ThrottleError::InternalError)?;
+
+        let by_account = match by_account {
+            Some(failure) => failure,
+            None => return Ok(()),
+        };
+
+        if let Some(locked_until) = by_account.locked_until.filter(|t| *t > now) {
+            return Err(ThrottleError::Locked(seconds_until(now, locked_until)));
+        }
+
+        let retry_at = by_account.last_failed_at + self.delay_after(&by_account);
+        if retry_at > now {
+            return Err(ThrottleError::TooManyAttempts(seconds_until(now, retry_at)));
+        }
+
+        Ok(())
+    }
+
+    /// Counts a failed attempt and locks the account or address once its threshold is reached.
+    pub async fn record_failure(
+        &self,
+        account: &AccountKey,
+        ip: IpAddr,
+    ) -> Result<(), ThrottleError> {
+        let (kind, subject) = (account.kind(), account.subject());
+        let now = Utc::now();
+        let window_start = now - self.lockout();
+
+        self.login_failure_repository
+            .delete_stale(window_start)
+            .await
+            .map_err(|_| ThrottleError::InternalError)?;
+
+        let by_account = self
+            .login_failure_repository
+            .record(kind, &subject, now, window_start)
+            .await
+            .map_err(|_| ThrottleError::InternalError)?;
+
+        if by_account.failed_attempts >= self.config.login_max_attempts as i32 {
+            println!(
+                "Too many failed logins for {} {}, locking account",
+                kind, subject
+            );
+            self.login_failure_repository
+                .lock(kind, &subject, now + self.lockout())
+                .await
+                .map_err(|_| ThrottleError::InternalError)?;
+        }
+
+        let ip = ip.to_string();
+        let by_ip = self
+            .login_failure_repository
+            .record(IP, &ip, now, window_start)
+            .await
+            .map_err(|_| ThrottleError::InternalError)?;
+
+        if by_ip.failed_attempts >= self.config.login_max_attempts_per_ip as i32 {
+            println!("Too many failed logins from {}, blocking address", ip);
+            self.login_failure_repository
+                .lock(IP, &ip, now + self.lockout())
+                .await
+                .map_err(|_| ThrottleError::InternalError)?;
+        }
+
+        Ok(())
+    }
+
+    /// Resets the account counter. The address counter is kept so that logging in to one
+    /// account does not allow guessing more passwords of others.
+    pub async fn record_success(&self, user_id: Uuid) -> Result<(), ThrottleError> {
+        self.login_failure_repository
+            .clear(USER, &user_id.to_string())
+            .await
+            .map_err(|_| ThrottleError::InternalError)
+    }
+
+    pub async fn unlock(&self, user_id: Uuid) -> Result<(), UnlockError> {
+        let user = self
+            .user_repository
+            .get(user_id)
+            .await
+            .map_err(|_| UnlockError::InternalError)?
+            .ok_or(UnlockError::UserDoesNotExist)?;
+
+        self.login_failure_repository
+            .clear(USER, &user.id.to_string())
+            .await
+            .map_err(|_| UnlockError::InternalError)
+    }
+
+    fn lockout(&self) -> Duration {
+        Duration::minutes(self.config.login_lockout_minutes.into())
+    }
+
+    /// The delay doubles with every failure.
+    fn delay_after(&self, failure: &LoginFailure) -> Duration {
+        let exponent = (failure.failed_attempts - 1).clamp(0, 16) as u32;
+        let delay = i64::from(self.config.login_delay_seconds) * 2i64.pow(exponent);
+        Duration::seconds(delay.min(MAX_DELAY_SECONDS))
+    }
+}
+
+fn seconds_until(now: DateTime<Utc>, until: DateTime<Utc>) -> i64 {
+    // Rounded up so clients never retry a moment too early
+    (until - now).num_milliseconds().div_euclid(1000) + 1
+}
@@ -0,0 +1,136 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use lettre::{
+    message::Mailbox, transport::smtp::authentication::Credentials, AsyncSmtpTransport,
+    AsyncTransport, Message, Tokio1Executor,
+};
+use std::{path::PathBuf, sync::Arc};
+use uuid::Uuid;
+
+use crate::config::Config;
+
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+pub enum MailError {
+    InvalidAddress,
+    Transport(String),
+}
+
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, email: Email) -> Result<(), MailError>;
+}
+
+pub fn mailer_from_config(config: &Config) -> Result<Arc<dyn Mailer>, String> {
+    match config.mail_transport.as_str() {
+        "smtp" => Ok(Arc::new(SmtpMailer::new(config)?)),
+        "file" => Ok(Arc::new(OutboxMailer::new(Some(PathBuf::from(
+            config
+                .mail_outbox_dir
+                .as_ref()
+                .ok_or("MAIL_OUTBOX_DIR must be set for file mail transport")?,
+        ))))),
+        "stdout" => Ok(Arc::new(OutboxMailer::new(None))),
+        other => Err(format!("Unknown MAIL_TRANSPORT {}", other)),
+    }
+}
+
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn new(config: &Config) -> Result<SmtpMailer, String> {
+        let host = config
+            .smtp_host
+            .as_ref()
+            .ok_or("SMTP_HOST must be set for smtp mail transport")?;
+
+        let mut builder = match config.smtp_tls.as_str() {
+            "tls" => {
+                AsyncSmtpTransport::<Tokio1Executor>::relay(host).map_err(|e| e.to_string())?
+            }
+            "starttls" => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+                .map_err(|e| e.to_string())?,
+            "none" => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+            other => Err(format!("Unknown SMTP_TLS {}", other))?,
+        };
+
+        if let Some(port) = config.smtp_port {
+            builder = builder.port(port);
+        }
+
+        if let (Some(username), Some(password)) = (&config.smtp_username, &config.smtp_password) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+
+        Ok(SmtpMailer {
+            transport: builder.build(),
+            from: config
+                .mail_from
+                .parse()
+                .map_err(|_| "MAIL_FROM must be a valid address")?,
+        })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: Email) -> Result<(), MailError> {
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(email.to.parse().map_err(|_| MailError::InvalidAddress)?)
+            .subject(email.subject)
+            .body(email.body)
+            .map_err(|e| MailError::Transport(e.to_string()))?;
+
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| MailError::Transport(e.to_string()))?;
+
+        Ok(())
+    }
+}
+
+/// Development mailer: writes messages to a directory, or prints them when no directory is given.
+pub struct OutboxMailer {
+    dir: Option<PathBuf>,
+}
+
+impl OutboxMailer {
+    pub fn new(dir: Option<PathBuf>) -> OutboxMailer {
+        OutboxMailer { dir }
+    }
+}
+
+#[async_trait]
+impl Mailer for OutboxMailer {
+    async fn send(&self, email: Email) -> Result<(), MailError> {
+        let content = format!(
+            "To: {}\nSubject: {}\n\n{}\n",
+            email.to, email.subject, email.body
+        );
+
+        match &self.dir {
+            Some(dir) => {
+                tokio::fs::create_dir_all(dir)
+                    .await
+                    .map_err(|e| MailError::Transport(e.to_string()))?;
+                let file_name = format!("{}-{}.eml", Utc::now().timestamp_millis(), Uuid::new_v4());
+                tokio::fs::write(dir.join(file_name), content)
+                    .await
+                    .map_err(|e| MailError::Transport(e.to_string()))
+            }
+            None => {
+                println!("{}", content);
+                Ok(())
+            }
+        }
+    }
+}
@@ -1,2 +1,18 @@
+pub mod account;
+pub mod api_token;
+pub mod audit;
 pub mod auth;
+pub mod credential_policy;
+pub mod email_verification;
+pub mod expense;
+pub mod introspection;
+pub mod invite;
+pub mod login_throttle;
+pub mod mail;
+pub mod oauth;
+pub mod password_reset;
+pub mod permission;
+pub mod registration;
+pub mod session;
+pub mod two_factor;
 pub mod user;
@@ -0,0 +1,703 @@
+mod claims;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{decode, decode_header, encode, Validation};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    db::{AppUserRepository, OAuthRepository, RevocationRepository},
+    domain::{
+        app_user::AppUser,
+        oauth::{
+            AuthorizationCode, OAuthClient, GRANT_AUTHORIZATION_CODE, GRANT_CLIENT_CREDENTIALS,
+            OPENID_SCOPE,
+        },
+    },
+    services::auth::JwtKeys,
+    utils::token::{generate_token, hash_token},
+};
+
+pub use self::claims::UserClaims;
+use self::claims::{AccessTokenClaims, IdTokenClaims};
+
+const AUTHORIZATION_CODE_MAXAGE_MINUTES: i64 = 5;
+/// `typ` header of access tokens issued to clients, see RFC 9068
+pub const ACCESS_TOKEN_TYPE: &str = "at+jwt";
+
+pub struct OAuthService {
+    oauth_repository: Arc<OAuthRepository>,
+    user_repository: Arc<AppUserRepository>,
+    revocation_repository: Arc<RevocationRepository>,
+    keys: Arc<JwtKeys>,
+    config: Config,
+}
+
+pub struct NewClient {
+    pub name: String,
+    pub redirect_uris: Vec<String>,
+    pub grant_types: Vec<String>,
+    pub scopes: Vec<String>,
+    /// Confidential clients get a secret, public clients authenticate with PKCE only
+    pub confidential: bool,
+}
+
+pub struct AuthorizationRequest {
+    pub response_type: String,
+    pub client_id: String,
+    pub redirect_uri: Option<String>,
+    pub scope: Option<String>,
+    pub state: Option<String>,
+    pub code_challenge: Option<String>,
+    pub code_challenge_method: Option<String>,
+    pub nonce: Option<String>,
+}
+
+/// Client authentication sent to the token endpoint
+pub struct ClientCredentials {
+    pub client_id: String,
+    pub client_secret: Option<String>,
+}
+
+pub struct OAuthTokens {
+    pub access_token: String,
+    pub id_token: Option<String>,
+    pub expires_in: i64,
+    pub scope: String,
+}
+
+struct ValidAuthorization {
+    client: OAuthClient,
+    redirect_uri: String,
+    scopes: Vec<String>,
+    code_challenge: String,
+}
+
+pub enum ClientError {
+    MissingName,
+    MissingGrantTypes,
+    MissingRedirectUris,
+    InvalidRedirectUri(String),
+    UnsupportedGrantType(String),
+    InvalidScope(String),
+    /// The client credentials grant needs a client secret
+    PublicClientCredentials,
+    ClientDoesNotExist,
+    InternalError,
+}
+
+pub enum AuthorizeError {
+    /// Unknown client, the user must not be redirected anywhere
+    InvalidClient,
+    /// Redirect URI is not registered for the client, the user must not be redirected to it
+    InvalidRedirectUri,
+    /// Request is invalid, contains the redirect back to the client with the OAuth error
+    Rejected(String),
+    InternalError,
+}
+
+/// Error codes of RFC 6749 section 5.2
+pub enum TokenError {
+    InvalidRequest(&'static str),
+    InvalidClient,
+    InvalidGrant,
+    UnauthorizedClient,
+    UnsupportedGrantType,
+    InvalidScope,
+    InternalError,
+}
+
+pub enum UserInfoError {
+    InvalidToken,
+    InsufficientScope,
+    InternalError,
+}
+
+pub enum AccessTokenError {
+    InvalidToken,
+    InternalError,
+}
+
+/// Access token issued to a client that passed validation.
+pub struct ValidAccessToken {
+    pub client_id: Uuid,
+    /// `None` for tokens of the client credentials grant
+    pub user: Option<AppUser>,
+    pub scopes: Vec<String>,
+    pub issued_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl OAuthService {
+    pub fn new(
+        oauth_repository: Arc<OAuthRepository>,
+        user_repository: Arc<AppUserRepository>,
+        revocation_repository: Arc<RevocationRepository>,
+        keys: Arc<JwtKeys>,
+        config: Config,
+    ) -> OAuthService {
+        OAuthService {
+            oauth_repository,
+            user_repository,
+            revocation_repository,
+            keys,
+            config,
+        }
+    }
+
+    /// Registers a client. The plain secret of confidential clients is returned only here.
+    pub async fn create_client(
+        &self,
+        new_client: NewClient,
+    ) -> Result<(OAuthClient, Option<String>), ClientError> {
+        let name = new_client.name.trim();
+        if name.is_empty() {
+            return Err(ClientError::MissingName);
+        }
+
+        let mut grant_types = new_client.grant_types;
+        grant_types.sort();
+        grant_types.dedup();
+        if grant_types.is_empty() {
+            return Err(ClientError::MissingGrantTypes);
+        }
+        if let Some(grant_type) = grant_types
+            .iter()
+            .find(|g| *g != GRANT_AUTHORIZATION_CODE && *g != GRANT_CLIENT_CREDENTIALS)
+        {
+            return Err(ClientError::UnsupportedGrantType(grant_type.clone()));
+        }
+
+        if grant_types.iter().any(|g| g == GRANT_CLIENT_CREDENTIALS) && !new_client.confidential {
+            return Err(ClientError::PublicClientCredentials);
+        }
+
+        if grant_types.iter().any(|g| g == GRANT_AUTHORIZATION_CODE)
+            && new_client.redirect_uris.is_empty()
+        {
+            return Err(ClientError::MissingRedirectUris);
+        }
+
+        if let Some(uri) = new_client
+            .redirect_uris
+            .iter()
+            .find(|uri| Url::parse(uri).map_or(true, |url| url.fragment().is_some()))
+        {
+            return Err(ClientError::InvalidRedirectUri(uri.clone()));
+        }
+
+        let mut scopes = new_client.scopes;
+        scopes.sort();
+        scopes.dedup();
+        if let Some(scope) = scopes
+            .iter()
+            .find(|s| s.is_empty() || s.contains(char::is_whitespace))
+        {
+            return Err(ClientError::InvalidScope(scope.clone()));
+        }
+
+        let secret = new_client.confidential.then(generate_token);
+        let client = OAuthClient {
+            id: Uuid::new_v4(),
+            name: name.to_owned(),
+            secret_hash: secret.as_deref().map(hash_token),
+            redirect_uris: new_client.redirect_uris,
+            grant_types,
+            scopes,
+            created_at: Utc::now(),
+        };
+
+        self.oauth_repository
+            .insert_client(client.clone())
+            .await
+            .map_err(|_| ClientError::InternalError)?;
+
+        Ok((client, secret))
+    }
+
+    pub async fn list_clients(&self) -> Result<Vec<OAuthClient>, ClientError> {
+        self.oauth_repository
+            .get_all_clients()
+            .await
+            .map_err(|_| ClientError::InternalError)
+    }
+
+    pub async fn delete_client(&self, id: Uuid) -> Result<(), ClientError> {
+        self.oauth_repository
+            .delete_client(id)
+            .await
+            .map_err(|_| ClientError::InternalError)?
+            .ok_or(ClientError::ClientDoesNotExist)?;
+
+        Ok(())
+    }
+
+    /// Checks an authorization request before the user is asked to approve it.
+    pub async fn check_authorization(
+        &self,
+        request: &AuthorizationRequest,
+    ) -> Result<(), AuthorizeError> {
+        self.validate_authorization(request).await.map(|_| ())
+    }
+
+    /// Issues an authorization code for the approved request and returns the redirect
+    /// back to the client carrying it.
+    pub async fn authorize(
+        &self,
+        user: &AppUser,
+        request: &AuthorizationRequest,
+    ) -> Result<String, AuthorizeError> {
+        let authorization = self.validate_authorization(request).await?;
+
+        let code = generate_token();
+        let now = Utc::now();
+
+        self.oauth_repository
+            .insert_code(AuthorizationCode {
+                code_hash: hash_token(&code),
+                client_id: authorization.client.id,
+                user_id: user.id,
+                redirect_uri: authorization.redirect_uri.clone(),
+                redirect_uri_sent: request.redirect_uri.is_some(),
+                scopes: authorization.scopes,
+                nonce: request.nonce.clone(),
+                code_challenge: authorization.code_challenge,
+                created_at: now,
+                expires_at: now + Duration::minutes(AUTHORIZATION_CODE_MAXAGE_MINUTES),
+                used_at: None,
+                access_token_jti: None,
+            })
+            .await
+            .map_err(|_| AuthorizeError::InternalError)?;
+
+        Ok(redirect_url(
+            &authorization.redirect_uri,
+            &[
+                ("code", Some(code.as_str())),
+                ("state", request.state.as_deref()),
+            ],
+        ))
+    }
+
+    pub async fn exchange_code(
+        &self,
+        credentials: ClientCredentials,
+        code: Option<&str>,
+        redirect_uri: Option<&str>,
+        code_verifier: Option<&str>,
+    ) -> Result<OAuthTokens, TokenError> {
+        let client = self.authenticate_client(credentials).await?;
+        if !client.allows_grant(GRANT_AUTHORIZATION_CODE) {
+            return Err(TokenError::UnauthorizedClient);
+        }
+
+        let code = code.ok_or(TokenError::InvalidRequest("code is required"))?;
+        let code_verifier =
+            code_verifier.ok_or(TokenError::InvalidRequest("code_verifier is required"))?;
+
+        let now = Utc::now();
+        self.oauth_repository
+            .delete_expired_codes(now, now - self.code_token_lifetime())
+            .await
+            .map_err(|_| TokenError::InternalError)?;
+
+        let code_hash = hash_token(code);
+        let access_token_jti = Uuid::new_v4();
+        let stored_code = match self
+            .oauth_repository
+            .use_code(&code_hash, now, access_token_jti)
+            .await
+            .map_err(|_| TokenError::InternalError)?
+        {
+            Some(stored_code) => stored_code,
+            None => {
+                self.revoke_replayed_code(&code_hash).await?;
+                return Err(TokenError::InvalidGrant);
+            }
+        };
+
+        if stored_code.client_id != client.id || stored_code.expires_at < now {
+            return Err(TokenError::InvalidGrant);
+        }
+
+        match redirect_uri {
+            Some(uri) if uri != stored_code.redirect_uri => return Err(TokenError::InvalidGrant),
+            None if stored_code.redirect_uri_sent => {
+                return Err(TokenError::InvalidRequest("redirect_uri is required"))
+            }
+            _ => {}
+        }
+
+        let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()));
+        if challenge != stored_code.code_challenge {
+            return Err(TokenError::InvalidGrant);
+        }
+
+        let user = self
+            .user_repository
+            .get(stored_code.user_id)
+            .await
+            .map_err(|_| TokenError::InternalError)?
+            .filter(|user| user.disabled_at.is_none())
+            .ok_or(TokenError::InvalidGrant)?;
+
+        self.issue_tokens(
+            &client,
+            Some(&user),
+            stored_code.scopes,
+            stored_code.nonce,
+            access_token_jti,
+        )
+        .map_err(|_| TokenError::InternalError)
+    }
+
+    /// Authenticates a confidential client acting on its own behalf, e.g. a resource server.
+    pub async fn authenticate_confidential_client(
+        &self,
+        credentials: ClientCredentials,
+    ) -> Result<OAuthClient, TokenError> {
+        let client = self.authenticate_client(credentials).await?;
+        if !client.is_confidential() {
+            return Err(TokenError::InvalidClient);
+        }
+
+        Ok(client)
+    }
+
+    pub async fn client_credentials(
+        &self,
+        credentials: ClientCredentials,
+        scope: Option<&str>,
+    ) -> Result<OAuthTokens, TokenError> {
+        let client = self.authenticate_client(credentials).await?;
+        if !client.is_confidential() || !client.allows_grant(GRANT_CLIENT_CREDENTIALS) {
+            return Err(TokenError::UnauthorizedClient);
+        }
+
+        let scopes = granted_scopes(&client, scope).ok_or(TokenError::InvalidScope)?;
+
+        self.issue_tokens(&client, None, scopes, None, Uuid::new_v4())
+            .map_err(|_| TokenError::InternalError)
+    }
+
+    /// Returns the subject and the claims released to the client the access token was issued to.
+    pub async fn userinfo(&self, access_token: &str) -> Result<(Uuid, UserClaims), UserInfoError> {
+        let access_token = self
+            .validate_access_token(access_token)
+            .await
+            .map_err(|e| match e {
+                AccessTokenError::InvalidToken => UserInfoError::InvalidToken,
+                AccessTokenError::InternalError => UserInfoError::InternalError,
+            })?;
+
+        // Client credentials tokens have no user behind them
+        let user = access_token.user.ok_or(UserInfoError::InvalidToken)?;
+
+        if !access_token.scopes.iter().any(|s| s == OPENID_SCOPE) {
+            return Err(UserInfoError::InsufficientScope);
+        }
+
+        Ok((user.id, UserClaims::for_scopes(&user, &access_token.scopes)))
+    }
+
+    /// Checks an access token issued to a client like the userinfo endpoint does: signature,
+    /// issuer, revocation, the client still being registered and, unless it was issued with
+    /// the client credentials grant, the user being active.
+    pub async fn validate_access_token(
+        &self,
+        access_token: &str,
+    ) -> Result<ValidAccessToken, AccessTokenError> {
+        let header = decode_header(access_token).map_err(|_| AccessTokenError::InvalidToken)?;
+        if header.typ.as_deref() != Some(ACCESS_TOKEN_TYPE) {
+            return Err(AccessTokenError::InvalidToken);
+        }
+
+        let (algorithm, decoding_key) = self
+            .keys
+            .decoding_key(header.kid.as_deref())
+            .ok_or(AccessTokenError::InvalidToken)?;
+
+        let mut validation = Validation::new(algorithm);
+        validation.set_issuer(&[&self.config.oauth_issuer]);
+        // Access tokens are meant for the client, any audience is fine here
+        validation.validate_aud = false;
+
+        let claims = decode::<AccessTokenClaims>(access_token, decoding_key, &validation)
+            .map_err(|_| AccessTokenError::InvalidToken)?
+            .claims;
+
+        let jti = Uuid::parse_str(&claims.jti).map_err(|_| AccessTokenError::InvalidToken)?;
+        if self
+            .revocation_repository
+            .is_token_revoked(jti)
+            .await
+            .map_err(|_| AccessTokenError::InternalError)?
+        {
+            return Err(AccessTokenError::InvalidToken);
+        }
+
+        let client_id =
+            Uuid::parse_str(&claims.client_id).map_err(|_| AccessTokenError::InvalidToken)?;
+        self.oauth_repository
+            .get_client(client_id)
+            .await
+            .map_err(|_| AccessTokenError::InternalError)?
+            .ok_or(AccessTokenError::InvalidToken)?;
+
+        let user = if claims.sub == claims.client_id {
+            None
+        } else {
+            Some(self.active_user(&claims).await?)
+        };
+
+        Ok(ValidAccessToken {
+            client_id,
+            user,
+            scopes: claims.scope.split_whitespace().map(str::to_owned).collect(),
+            issued_at: DateTime::from_timestamp(claims.iat, 0),
+            expires_at: DateTime::from_timestamp(claims.exp, 0),
+        })
+    }
+
+    async fn active_user(&self, claims: &AccessTokenClaims) -> Result<AppUser, AccessTokenError> {
+        let user_id = Uuid::parse_str(&claims.sub).map_err(|_| AccessTokenError::InvalidToken)?;
+
+        let revoked_before = self
+            .revocation_repository
+            .get_revoked_before(user_id)
+            .await
+            .map_err(|_| AccessTokenError::InternalError)?;
+        if revoked_before.is_some_and(|t| claims.iat < t.timestamp()) {
+            return Err(AccessTokenError::InvalidToken);
+        }
+
+        self.user_repository
+            .get(user_id)
+            .await
+            .map_err(|_| AccessTokenError::InternalError)?
+            .filter(|user| user.disabled_at.is_none())
+            .ok_or(AccessTokenError::InvalidToken)
+    }
+
+    async fn validate_authorization(
+        &self,
+        request: &AuthorizationRequest,
+    ) -> Result<ValidAuthorization, AuthorizeError> {
+        let client_id =
+            Uuid::parse_str(&request.client_id).map_err(|_| AuthorizeError::InvalidClient)?;
+        let client = self
+            .oauth_repository
+            .get_client(client_id)
+            .await
+            .map_err(|_| AuthorizeError::InternalError)?
+            .ok_or(AuthorizeError::InvalidClient)?;
+
+        let redirect_uri = match (&request.redirect_uri, client.redirect_uris.as_slice()) {
+            (Some(uri), registered) if registered.contains(uri) => uri.clone(),
+            (None, [only]) => only.clone(),
+            _ => return Err(AuthorizeError::InvalidRedirectUri),
+        };
+
+        let reject = |error: &str, description: &str| {
+            AuthorizeError::Rejected(redirect_url(
+                &redirect_uri,
+                &[
+                    ("error", Some(error)),
+                    ("error_description", Some(description)),
+                    ("state", request.state.as_deref()),
+                ],
+            ))
+        };
+
+        if request.response_type != "code" {
+            return Err(reject(
+                "unsupported_response_type",
+                "Only the code response type is supported",
+            ));
+        }
+
+        if !client.allows_grant(GRANT_AUTHORIZATION_CODE) {
+            return Err(reject(
+                "unauthorized_client",
+                "Client may not use the authorization code grant",
+            ));
+        }
+
+        let code_challenge = match (
+            &request.code_challenge,
+            request.code_challenge_method.as_deref(),
+        ) {
+            (Some(challenge), Some("S256")) if (43..=128).contains(&challenge.len()) => {
+                challenge.clone()
+            }
+            _ => {
+                return Err(reject(
+                    "invalid_request",
+                    "A S256 code_challenge (PKCE) is required",
+                ))
+            }
+        };
+
+        let scopes = match granted_scopes(&client, request.scope.as_deref()) {
+            Some(scopes) => scopes,
+            None => {
+                return Err(reject(
+                    "invalid_scope",
+                    "Scope is not allowed for the client",
+                ))
+            }
+        };
+
+        Ok(ValidAuthorization {
+            client,
+            redirect_uri,
+            scopes,
+            code_challenge,
+        })
+    }
+
+    async fn authenticate_client(
+        &self,
+        credentials: ClientCredentials,
+    ) -> Result<OAuthClient, TokenError> {
+        let client_id =
+            Uuid::parse_str(&credentials.client_id).map_err(|_| TokenError::InvalidClient)?;
+        let client = self
+            .oauth_repository
+            .get_client(client_id)
+            .await
+            .map_err(|_| TokenError::InternalError)?
+            .ok_or(TokenError::InvalidClient)?;
+
+        match (&client.secret_hash, credentials.client_secret.as_deref()) {
+            (Some(secret_hash), Some(secret)) if hash_token(secret) == *secret_hash => Ok(client),
+            (None, None) => Ok(client),
+            _ => Err(TokenError::InvalidClient),
+        }
+    }
+
+    /// How long the access token issued for a code may stay valid after the code was used,
+    /// with a margin for the time between using the code and issuing the token
+    fn code_token_lifetime(&self) -> Duration {
+        Duration::minutes(i64::from(self.config.jwt_maxage) + 1)
+    }
+
+    /// A code presented a second time was probably intercepted, so the access token already
+    /// issued for it is revoked (RFC 6749 section 4.1.2).
+    async fn revoke_replayed_code(&self, code_hash: &str) -> Result<(), TokenError> {
+        let replayed = self
+            .oauth_repository
+            .get_code(code_hash)
+            .await
+            .map_err(|_| TokenError::InternalError)?;
+
+        if let Some(AuthorizationCode {
+            user_id,
+            access_token_jti: Some(jti),
+            ..
+        }) = replayed
+        {
+            println!(
+                "Authorization code used again, revoking access token {}",
+                jti
+            );
+            self.revocation_repository
+                .revoke_token(jti, user_id, Utc::now() + self.code_token_lifetime())
+                .await
+                .map_err(|_| TokenError::InternalError)?;
+        }
+
+        Ok(())
+    }
+
+    fn issue_tokens(
+        &self,
+        client: &OAuthClient,
+        user: Option<&AppUser>,
+        scopes: Vec<String>,
+        nonce: Option<String>,
+        jti: Uuid,
+    ) -> Result<OAuthTokens, jsonwebtoken::errors::Error> {
+        let now = Utc::now();
+        let expires_in = i64::from(self.config.jwt_maxage) * 60;
+        let scope = scopes.join(" ");
+
+        let access_claims = AccessTokenClaims {
+            iss: self.config.oauth_issuer.clone(),
+            sub: user.map_or(client.id, |user| user.id).to_string(),
+            aud: client.id.to_string(),
+            client_id: client.id.to_string(),
+            scope: scope.clone(),
+            jti: jti.to_string(),
+            iat: now.timestamp(),
+            exp: now.timestamp() + expires_in,
+        };
+
+        let mut access_header = self.keys.header().clone();
+        access_header.typ = Some(ACCESS_TOKEN_TYPE.to_owned());
+        let access_token = encode(&access_header, &access_claims, self.keys.encoding_key())?;
+
+        let id_token = match user {
+            Some(user) if scopes.iter().any(|s| s == OPENID_SCOPE) => {
+                let id_claims = IdTokenClaims {
+                    iss: self.config.oauth_issuer.clone(),
+                    sub: user.id.to_string(),
+                    aud: client.id.to_string(),
+                    iat: now.timestamp(),
+                    exp: now.timestamp() + expires_in,
+                    nonce,
+                    user: UserClaims::for_scopes(user, &scopes),
+                };
+                Some(encode(
+                    self.keys.header(),
+                    &id_claims,
+                    self.keys.encoding_key(),
+                )?)
+            }
+            _ => None,
+        };
+
+        Ok(OAuthTokens {
+            access_token,
+            id_token,
+            expires_in,
+            scope,
+        })
+    }
+}
+
+/// Requested scopes if the client may use all of them, every allowed scope when none are requested.
+fn granted_scopes(client: &OAuthClient, requested: Option<&str>) -> Option<Vec<String>> {
+    let requested = match requested {
+        None => return Some(client.scopes.clone()),
+        Some(requested) => requested,
+    };
+
+    let mut scopes: Vec<String> = requested.split_whitespace().map(str::to_owned).collect();
+    scopes.sort();
+    scopes.dedup();
+
+    scopes
+        .iter()
+        .all(|scope| client.scopes.contains(scope))
+        .then_some(scopes)
+}
+
+fn redirect_url(redirect_uri: &str, params: &[(&str, Option<&str>)]) -> String {
+    match Url::parse(redirect_uri) {
+        Ok(mut url) => {
+            for (name, value) in params {
+                if let Some(value) = value {
+                    url.query_pairs_mut().append_pair(name, value);
+                }
+            }
+            url.to_string()
+        }
+        // Registered redirect URIs are validated, this only guards against manual database edits
+        Err(_) => redirect_uri.to_owned(),
+    }
+}
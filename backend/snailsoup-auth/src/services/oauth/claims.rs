@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::app_user::AppUser;
+
+/// Claims of access tokens issued to OAuth clients (RFC 9068). Unlike `TokenClaims`
+/// they are not accepted by the snail-soup API itself.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessTokenClaims {
+    pub iss: String,
+    /// User id, or the client id for the client credentials grant
+    pub sub: String,
+    pub aud: String,
+    pub client_id: String,
+    /// Space separated granted scopes
+    pub scope: String,
+    pub jti: String,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdTokenClaims {
+    pub iss: String,
+    pub sub: String,
+    pub aud: String,
+    pub iat: i64,
+    pub exp: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+    #[serde(flatten)]
+    pub user: UserClaims,
+}
+
+/// Claims about the user released according to the granted scopes,
+/// shared by ID tokens and the userinfo endpoint.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UserClaims {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preferred_username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+}
+
+impl UserClaims {
+    pub fn for_scopes(user: &AppUser, scopes: &[String]) -> UserClaims {
+        let granted = |scope: &str| scopes.iter().any(|s| s == scope);
+        let mut claims = UserClaims::default();
+
+        if granted("profile") {
+            claims.preferred_username = Some(user.username.clone());
+            claims.role = Some(user.account_role.to_string());
+        }
+
+        if granted("email") {
+            claims.email = user.email.clone();
+        }
+
+        claims
+    }
+}
@@ -0,0 +1,154 @@
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    db::{AppUserRepository, PasswordResetRepository},
+    domain::password_reset::PasswordResetToken,
+    services::{
+        auth::{AuthService, PasswordChangeError},
+        credential_policy::PolicyViolation,
+        mail::{Email, MailError, Mailer},
+    },
+    utils::token::{generate_token, hash_token},
+};
+
+pub enum ResetRequestError {
+    InternalError,
+}
+
+pub enum ResetError {
+    InvalidToken,
+    ExpiredToken,
+    PolicyViolation(Vec<PolicyViolation>),
+    InternalError,
+}
+
+pub struct PasswordResetService {
+    user_repository: Arc<AppUserRepository>,
+    reset_repository: Arc<PasswordResetRepository>,
+    auth_service: Arc<AuthService>,
+    mailer: Arc<dyn Mailer>,
+    config: Config,
+}
+
+impl PasswordResetService {
+    pub fn new(
+        user_repository: Arc<AppUserRepository>,
+        reset_repository: Arc<PasswordResetRepository>,
+        auth_service: Arc<AuthService>,
+        mailer: Arc<dyn Mailer>,
+        config: Config,
+    ) -> PasswordResetService {
+        PasswordResetService {
+            user_repository,
+            reset_repository,
+            auth_service,
+            mailer,
+            config,
+        }
+    }
+
+    /// Mails a one-time reset link to the address of the account with the given username or email.
+    /// Unknown users and users without an email are ignored so callers cannot tell them apart.
+    pub async fn request_reset(&self, username: &str) -> Result<(), ResetRequestError> {
+        let user = self
+            .user_repository
+            .get_by_login(username)
+            .await
+            .map_err(|_| ResetRequestError::InternalError)?;
+
+        let (user, email) = match user {
+            Some(user) => match user.email.clone() {
+                Some(email) => (user, email),
+                None => return Ok(()),
+            },
+            None => return Ok(()),
+        };
+
+        let token = generate_token();
+        let now = Utc::now();
+
+        self.reset_repository
+            .replace_for_user(PasswordResetToken {
+                id: Uuid::new_v4(),
+                user_id: user.id,
+                token_hash: hash_token(&token),
+                created_at: now,
+                expires_at: now + Duration::minutes(self.config.password_reset_token_maxage.into()),
+                used_at: None,
+            })
+            .await
+            .map_err(|_| ResetRequestError::InternalError)?;
+
+        let sent = self
+            .mailer
+            .send(Email {
+                to: email,
+                subject: "Reset your snail-soup password".to_owned(),
+                body: format!(
+                    "Hi {},\n\nuse the link below to set a new password. It expires in {} minutes.\n\n{}?token={}\n\nIf you did not ask for a reset, ignore this message.",
+                    user.username,
+                    self.config.password_reset_token_maxage,
+                    self.config.password_reset_url,
+                    token
+                ),
+            })
+            .await;
+
+        if let Err(e) = sent {
+            match e {
+                MailError::InvalidAddress => {
+                    println!("{} has an invalid email address", user.username)
+                }
+                MailError::Transport(e) => println!("Cannot send password reset email: {}", e),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Consumes a reset token, sets the new password and revokes the user's existing tokens.
+    /// Returns the id of the user whose password was changed.
+    pub async fn reset(&self, token: &str, new_password: &str) -> Result<Uuid, ResetError> {
+        let stored_token = self
+            .reset_repository
+            .get_by_hash(&hash_token(token))
+            .await
+            .map_err(|_| ResetError::InternalError)?
+            .ok_or(ResetError::InvalidToken)?;
+
+        let now = Utc::now();
+
+        if stored_token.expires_at < now {
+            return Err(ResetError::ExpiredToken);
+        }
+
+        // Checked before the token is used up so that the user can pick another password
+        self.auth_service
+            .check_password(new_password)
+            .map_err(ResetError::PolicyViolation)?;
+
+        self.reset_repository
+            .mark_used(stored_token.id, now)
+            .await
+            .map_err(|_| ResetError::InternalError)?
+            .ok_or(ResetError::InvalidToken)?;
+
+        self.auth_service
+            .reset_password(stored_token.user_id, new_password)
+            .await
+            .map_err(|e| match e {
+                PasswordChangeError::UserDoesNotExist => ResetError::InvalidToken,
+                PasswordChangeError::PolicyViolation(violations) => {
+                    ResetError::PolicyViolation(violations)
+                }
+                PasswordChangeError::IncorrectPassword | PasswordChangeError::InternalError => {
+                    ResetError::InternalError
+                }
+            })?;
+
+        Ok(stored_token.user_id)
+    }
+}
@@ -0,0 +1,50 @@
+use std::sync::Arc;
+
+use crate::{db::PermissionRepository, domain::app_user::Role};
+
+pub struct PermissionService {
+    permission_repository: Arc<PermissionRepository>,
+}
+
+pub enum PermissionError {
+    InternalError,
+}
+
+impl PermissionService {
+    pub fn new(permission_repository: Arc<PermissionRepository>) -> PermissionService {
+        PermissionService {
+            permission_repository,
+        }
+    }
+
+    pub async fn has_permission(
+        &self,
+        role: &Role,
+        permission: &str,
+    ) -> Result<bool, PermissionError> {
+        self.permission_repository
+            .has_permission(role.as_str(), permission)
+            .await
+            .map_err(|_| PermissionError::InternalError)
+    }
+
+    /// Roles are ordered by their permissions: a role includes another when it is granted
+    /// everything the other one is, so `Admin` includes `User`.
+    pub async fn role_includes(&self, role: &Role, other: &Role) -> Result<bool, PermissionError> {
+        self.permission_repository
+            .role_includes(role.as_str(), other.as_str())
+            .await
+            .map_err(|_| PermissionError::InternalError)
+    }
+
+    /// Role of the given name, `None` when the `roles` table does not have it.
+    pub async fn find_role(&self, name: &str) -> Result<Option<Role>, PermissionError> {
+        let exists = self
+            .permission_repository
+            .role_exists(name)
+            .await
+            .map_err(|_| PermissionError::InternalError)?;
+
+        Ok(exists.then(|| Role::new(name)))
+    }
+}
@@ -0,0 +1,75 @@
+use std::sync::Arc;
+
+use crate::{
+    config::{Config, RegistrationMode},
+    domain::app_user::AppUser,
+    services::{
+        auth::{AuthService, RegisterError},
+        invite::{InviteError, InviteService},
+    },
+};
+
+/// Applies `REGISTRATION_MODE` to self-service registration
+pub struct RegistrationService {
+    auth_service: Arc<AuthService>,
+    invite_service: Arc<InviteService>,
+    config: Config,
+}
+
+impl RegistrationService {
+    pub fn new(
+        auth_service: Arc<AuthService>,
+        invite_service: Arc<InviteService>,
+        config: Config,
+    ) -> RegistrationService {
+        RegistrationService {
+            auth_service,
+            invite_service,
+            config,
+        }
+    }
+
+    pub async fn register(
+        &self,
+        username: &str,
+        password: &str,
+        email: Option<&str>,
+        invite_code: Option<&str>,
+    ) -> Result<AppUser, RegisterError> {
+        match self.config.registration_mode {
+            RegistrationMode::Open => {
+                self.auth_service
+                    .register(username, password, email, false)
+                    .await
+            }
+            RegistrationMode::Approval => {
+                self.auth_service
+                    .register(username, password, email, true)
+                    .await
+            }
+            RegistrationMode::Invite => {
+                let invite_code = invite_code.ok_or(RegisterError::InviteCodeRequired)?;
+                let invite_id =
+                    self.invite_service
+                        .redeem(invite_code)
+                        .await
+                        .map_err(|e| match e {
+                            InviteError::InvalidCode => RegisterError::InvalidInviteCode,
+                            _ => RegisterError::InternalError,
+                        })?;
+
+                let created_user = self
+                    .auth_service
+                    .register(username, password, email, false)
+                    .await;
+
+                // The use is taken first so that concurrent registrations cannot exceed it
+                if created_user.is_err() && self.invite_service.release(invite_id).await.is_err() {
+                    println!("Cannot release use of invite code {}", invite_id);
+                }
+
+                created_user
+            }
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    db::{RefreshTokenRepository, SessionRepository},
+    domain::session::Session,
+};
+
+/// Lets users see where they are logged in and end single logins.
+pub struct SessionService {
+    session_repository: Arc<SessionRepository>,
+    refresh_token_repository: Arc<RefreshTokenRepository>,
+    config: Config,
+}
+
+pub enum SessionError {
+    SessionDoesNotExist,
+    InternalError,
+}
+
+impl SessionService {
+    pub fn new(
+        session_repository: Arc<SessionRepository>,
+        refresh_token_repository: Arc<RefreshTokenRepository>,
+        config: Config,
+    ) -> SessionService {
+        SessionService {
+            session_repository,
+            refresh_token_repository,
+            config,
+        }
+    }
+
+    /// Sessions that are not revoked and can still be refreshed, most recently used first
+    pub async fn list(&self, user_id: Uuid) -> Result<Vec<Session>, SessionError> {
+        let seen_after = Utc::now() - Duration::minutes(self.config.refresh_token_maxage.into());
+
+        self.session_repository
+            .get_active_for_user(user_id, seen_after)
+            .await
+            .map_err(|_| SessionError::InternalError)
+    }
+
+    /// Ends the session: its access tokens are rejected from now on and its refresh tokens
+    /// cannot be used anymore.
+    pub async fn revoke(&self, user_id: Uuid, session_id: Uuid) -> Result<(), SessionError> {
+        let now = Utc::now();
+
+        self.session_repository
+            .revoke(session_id, user_id, now)
+            .await
+            .map_err(|_| SessionError::InternalError)?
+            .ok_or(SessionError::SessionDoesNotExist)?;
+
+        self.refresh_token_repository
+            .revoke_family(session_id, now)
+            .await
+            .map_err(|_| SessionError::InternalError)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,241 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::Utc;
+use std::sync::Arc;
+use totp_rs::{Algorithm, Secret, TOTP};
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    db::TwoFactorRepository,
+    domain::{app_user::AppUser, two_factor::TotpCredential},
+    utils::token::hash_token,
+};
+
+const TOTP_DIGITS: usize = 6;
+const TOTP_STEP: u64 = 30;
+/// Codes from one step before and after the current one are accepted to tolerate clock drift
+const TOTP_SKEW: i64 = 1;
+const RECOVERY_CODE_COUNT: usize = 10;
+const RECOVERY_CODE_ALPHABET: &[u8] = b"abcdefghijkmnpqrstuvwxyz23456789";
+
+pub struct TwoFactorService {
+    two_factor_repository: Arc<TwoFactorRepository>,
+    config: Config,
+}
+
+pub struct TotpEnrollment {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+pub enum TwoFactorError {
+    NotEnrolled,
+    AlreadyEnabled,
+    InvalidCode,
+    InternalError,
+}
+
+impl TwoFactorService {
+    pub fn new(
+        two_factor_repository: Arc<TwoFactorRepository>,
+        config: Config,
+    ) -> TwoFactorService {
+        TwoFactorService {
+            two_factor_repository,
+            config,
+        }
+    }
+
+    /// Whether the user has a confirmed authenticator and must pass the second login step.
+    pub async fn is_enabled(&self, user_id: Uuid) -> Result<bool, TwoFactorError> {
+        let credential = self
+            .two_factor_repository
+            .get(user_id)
+            .await
+            .map_err(|_| TwoFactorError::InternalError)?;
+
+        Ok(credential.is_some_and(|c| c.confirmed_at.is_some()))
+    }
+
+    /// Generates a new secret. It is not used at login until confirmed with a valid code.
+    pub async fn enroll(&self, user: &AppUser) -> Result<TotpEnrollment, TwoFactorError> {
+        let secret = match Secret::generate_secret().to_encoded() {
+            Secret::Encoded(secret) => secret,
+            Secret::Raw(_) => Err(TwoFactorError::InternalError)?,
+        };
+
+        self.two_factor_repository
+            .upsert_unconfirmed(TotpCredential {
+                user_id: user.id,
+                secret: secret.clone(),
+                created_at: Utc::now(),
+                confirmed_at: None,
+                last_used_step: None,
+            })
+            .await
+            .map_err(|_| TwoFactorError::InternalError)?
+            .ok_or(TwoFactorError::AlreadyEnabled)?;
+
+        let otpauth_uri = self.totp(&secret, &user.username)?.get_url();
+
+        Ok(TotpEnrollment {
+            secret,
+            otpauth_uri,
+        })
+    }
+
+    /// Enables two-factor authentication and returns the recovery codes, which are shown only once.
+    pub async fn confirm(&self, user: &AppUser, code: &str) -> Result<Vec<String>, TwoFactorError> {
+        let credential = self
+            .two_factor_repository
+            .get(user.id)
+            .await
+            .map_err(|_| TwoFactorError::InternalError)?
+            .ok_or(TwoFactorError::NotEnrolled)?;
+
+        if credential.confirmed_at.is_some() {
+            return Err(TwoFactorError::AlreadyEnabled);
+        }
+
+        self.verify_totp(&credential, code).await?;
+
+        let recovery_codes = generate_recovery_codes();
+
+        self.two_factor_repository
+            .confirm(user.id, Utc::now(), &hash_codes(&recovery_codes))
+            .await
+            .map_err(|_| TwoFactorError::InternalError)?;
+
+        Ok(recovery_codes)
+    }
+
+    /// Checks a code from the authenticator app or, failing that, consumes a recovery code.
+    pub async fn verify(&self, user_id: Uuid, code: &str) -> Result<(), TwoFactorError> {
+        let credential = self
+            .two_factor_repository
+            .get(user_id)
+            .await
+            .map_err(|_| TwoFactorError::InternalError)?
+            .filter(|c| c.confirmed_at.is_some())
+            .ok_or(TwoFactorError::NotEnrolled)?;
+
+        match self.verify_totp(&credential, code).await {
+            Err(TwoFactorError::InvalidCode) => self.use_recovery_code(user_id, code).await,
+            result => result,
+        }
+    }
+
+    /// Replaces every recovery code of the user with a new set.
+    pub async fn regenerate_recovery_codes(
+        &self,
+        user_id: Uuid,
+        code: &str,
+    ) -> Result<Vec<String>, TwoFactorError> {
+        self.verify(user_id, code).await?;
+
+        let recovery_codes = generate_recovery_codes();
+
+        self.two_factor_repository
+            .replace_recovery_codes(user_id, &hash_codes(&recovery_codes))
+            .await
+            .map_err(|_| TwoFactorError::InternalError)?;
+
+        Ok(recovery_codes)
+    }
+
+    pub async fn disable(&self, user_id: Uuid, code: &str) -> Result<(), TwoFactorError> {
+        self.verify(user_id, code).await?;
+        self.remove(user_id).await
+    }
+
+    /// Removes the authenticator without asking for a code, e.g. when the user lost their device.
+    pub async fn remove(&self, user_id: Uuid) -> Result<(), TwoFactorError> {
+        self.two_factor_repository
+            .delete(user_id)
+            .await
+            .map_err(|_| TwoFactorError::InternalError)?
+            .ok_or(TwoFactorError::NotEnrolled)?;
+
+        Ok(())
+    }
+
+    async fn verify_totp(
+        &self,
+        credential: &TotpCredential,
+        code: &str,
+    ) -> Result<(), TwoFactorError> {
+        let totp = self.totp(&credential.secret, "")?;
+        let code = code.trim();
+        let current_step = Utc::now().timestamp() / TOTP_STEP as i64;
+
+        let step = (current_step - TOTP_SKEW..=current_step + TOTP_SKEW)
+            .find(|step| totp.generate(*step as u64 * TOTP_STEP) == code)
+            .ok_or(TwoFactorError::InvalidCode)?;
+
+        // Every code is accepted once, a stolen code cannot be replayed within its time window
+        self.two_factor_repository
+            .use_step(credential.user_id, step)
+            .await
+            .map_err(|_| TwoFactorError::InternalError)?
+            .ok_or(TwoFactorError::InvalidCode)?;
+
+        Ok(())
+    }
+
+    async fn use_recovery_code(&self, user_id: Uuid, code: &str) -> Result<(), TwoFactorError> {
+        self.two_factor_repository
+            .use_recovery_code(user_id, &hash_recovery_code(code), Utc::now())
+            .await
+            .map_err(|_| TwoFactorError::InternalError)?
+            .ok_or(TwoFactorError::InvalidCode)?;
+
+        println!("Recovery code used by user {}", user_id);
+
+        Ok(())
+    }
+
+    fn totp(&self, secret: &str, account_name: &str) -> Result<TOTP, TwoFactorError> {
+        let secret = Secret::Encoded(secret.to_owned())
+            .to_bytes()
+            .map_err(|_| TwoFactorError::InternalError)?;
+
+        Ok(TOTP::new_unchecked(
+            Algorithm::SHA1,
+            TOTP_DIGITS,
+            TOTP_SKEW as u8,
+            TOTP_STEP,
+            secret,
+            Some(self.config.totp_issuer.clone()),
+            account_name.to_owned(),
+        ))
+    }
+}
+
+/// Recovery codes look like `k7mqp-2xwhd`, ambiguous characters are left out.
+fn generate_recovery_codes() -> Vec<String> {
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let mut bytes = [0u8; 10];
+            OsRng.fill_bytes(&mut bytes);
+            let chars: String = bytes
+                .iter()
+                .map(|b| RECOVERY_CODE_ALPHABET[*b as usize % RECOVERY_CODE_ALPHABET.len()] as char)
+                .collect();
+            format!("{}-{}", &chars[..5], &chars[5..])
+        })
+        .collect()
+}
+
+fn hash_codes(codes: &[String]) -> Vec<String> {
+    codes.iter().map(|code| hash_recovery_code(code)).collect()
+}
+
+/// Codes are compared without the dash and case-insensitively so they can be typed loosely.
+fn hash_recovery_code(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    hash_token(&normalized)
+}
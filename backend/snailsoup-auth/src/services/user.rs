@@ -1,31 +1,229 @@
+use chrono::Utc;
 use uuid::Uuid;
 
-use crate::{db::AppUserRepository, domain::app_user::AppUser};
+use crate::{
+    db::AppUserRepository,
+    domain::app_user::{AppUser, Role},
+    services::{
+        auth::{AuthService, RegisterError},
+        credential_policy::PolicyViolation,
+        permission::PermissionService,
+    },
+    utils::user_query::{UserCursor, UserQuery},
+};
 
 use std::sync::Arc;
 
 pub struct UserService {
     user_repository: Arc<AppUserRepository>,
+    auth_service: Arc<AuthService>,
+    permission_service: Arc<PermissionService>,
+}
+
+pub struct UserPage {
+    pub users: Vec<AppUser>,
+    /// Number of users matching the filter across all pages
+    pub total: i64,
+    /// Present when there are more users after this page
+    pub next_cursor: Option<UserCursor>,
+}
+
+pub enum UserError {
+    UserDoesNotExist,
+    UsernameInUse,
+    EmailInUse,
+    PolicyViolation(Vec<PolicyViolation>),
+    /// Role name missing from the `roles` table
+    UnknownRole(String),
+    /// Administrators cannot lock themselves out by demoting, disabling or deleting their own account
+    OwnAccount,
+    InternalError,
 }
 
 impl UserService {
-    pub fn new(expense_repository: Arc<AppUserRepository>) -> UserService {
+    pub fn new(
+        user_repository: Arc<AppUserRepository>,
+        auth_service: Arc<AuthService>,
+        permission_service: Arc<PermissionService>,
+    ) -> UserService {
         UserService {
-            user_repository: expense_repository,
+            user_repository,
+            auth_service,
+            permission_service,
         }
     }
 
+    pub async fn find_role(&self, name: &str) -> Result<Role, UserError> {
+        self.permission_service
+            .find_role(name)
+            .await
+            .map_err(|_| UserError::InternalError)?
+            .ok_or_else(|| UserError::UnknownRole(name.to_owned()))
+    }
+
     pub async fn get(&self, id: Uuid) -> Option<AppUser> {
-        match self.user_repository.get(id).await {
-            Ok(expense) => expense,
-            Err(_) => None,
+        self.user_repository.get(id).await.unwrap_or_default()
+    }
+
+    pub async fn list(&self, query: UserQuery) -> Result<UserPage, UserError> {
+        let total = self
+            .user_repository
+            .count(&query.filter)
+            .await
+            .map_err(|_| UserError::InternalError)?;
+
+        // One extra row tells whether another page follows
+        let mut users = self
+            .user_repository
+            .get_page(&UserQuery {
+                limit: query.limit + 1,
+                ..query.clone()
+            })
+            .await
+            .map_err(|_| UserError::InternalError)?;
+
+        let next_cursor = if users.len() as i64 > query.limit {
+            users.truncate(query.limit as usize);
+            users.last().map(|user| UserCursor::after(user, query.sort))
+        } else {
+            None
+        };
+
+        Ok(UserPage {
+            users,
+            total,
+            next_cursor,
+        })
+    }
+
+    pub async fn create(
+        &self,
+        username: &str,
+        password: &str,
+        email: Option<&str>,
+        account_role: &str,
+    ) -> Result<AppUser, UserError> {
+        let account_role = self.find_role(account_role).await?;
+
+        self.auth_service
+            .create_user(username, password, email, account_role)
+            .await
+            .map_err(|e| match e {
+                RegisterError::UsernameInUse => UserError::UsernameInUse,
+                RegisterError::EmailInUse => UserError::EmailInUse,
+                RegisterError::PolicyViolation(violations) => {
+                    UserError::PolicyViolation(violations)
+                }
+                _ => UserError::InternalError,
+            })
+    }
+
+    pub async fn change_role(
+        &self,
+        acting_user: &AppUser,
+        id: Uuid,
+        account_role: &str,
+    ) -> Result<(), UserError> {
+        if acting_user.id == id {
+            return Err(UserError::OwnAccount);
+        }
+
+        let account_role = self.find_role(account_role).await?;
+
+        self.user_repository
+            .update_role(id, account_role.as_str())
+            .await
+            .map_err(|_| UserError::InternalError)?
+            .ok_or(UserError::UserDoesNotExist)?;
+
+        Ok(())
+    }
+
+    /// Disables the account and revokes all of its tokens
+    pub async fn disable(&self, acting_user: &AppUser, id: Uuid) -> Result<(), UserError> {
+        if acting_user.id == id {
+            return Err(UserError::OwnAccount);
         }
+
+        self.user_repository
+            .set_disabled_at(id, Some(Utc::now()))
+            .await
+            .map_err(|_| UserError::InternalError)?
+            .ok_or(UserError::UserDoesNotExist)?;
+
+        println!("User {} disabled by {}", id, acting_user.username);
+
+        self.auth_service
+            .logout_everywhere(id)
+            .await
+            .map_err(|_| UserError::InternalError)
+    }
+
+    pub async fn enable(&self, id: Uuid) -> Result<(), UserError> {
+        self.user_repository
+            .set_disabled_at(id, None)
+            .await
+            .map_err(|_| UserError::InternalError)?
+            .ok_or(UserError::UserDoesNotExist)?;
+
+        Ok(())
+    }
+
+    /// Lets an account registered in approval mode log in
+    pub async fn approve(&self, acting_user: &AppUser, id: Uuid) -> Result<(), UserError> {
+        self.user_repository
+            .approve(id)
+            .await
+            .map_err(|_| UserError::InternalError)?
+            .ok_or(UserError::UserDoesNotExist)?;
+
+        println!("User {} approved by {}", id, acting_user.username);
+
+        Ok(())
     }
 
-    pub async fn get_all(&self) -> Vec<AppUser> {
-        match self.user_repository.get_all().await {
-            Ok(expenses) => expenses,
-            Err(_) => Vec::new(),
+    pub async fn rename(&self, id: Uuid, username: &str) -> Result<(), UserError> {
+        let username = self
+            .auth_service
+            .check_username(username)
+            .map_err(UserError::PolicyViolation)?;
+
+        let existing_user = self
+            .user_repository
+            .get_by_name(&username)
+            .await
+            .map_err(|_| UserError::InternalError)?;
+
+        // Usernames are unique ignoring case, users may still change the case of their own
+        match existing_user {
+            Some(user) if user.id == id => {}
+            Some(_) => return Err(UserError::UsernameInUse),
+            None => {}
+        }
+
+        self.user_repository
+            .update_username(id, &username)
+            .await
+            .map_err(|_| UserError::InternalError)?
+            .ok_or(UserError::UserDoesNotExist)?;
+
+        Ok(())
+    }
+
+    /// Deletes the account together with its expenses, categories, tags and tokens
+    pub async fn delete(&self, acting_user: &AppUser, id: Uuid) -> Result<(), UserError> {
+        if acting_user.id == id {
+            return Err(UserError::OwnAccount);
         }
+
+        self.user_repository
+            .delete(id)
+            .await
+            .map_err(|_| UserError::InternalError)?
+            .ok_or(UserError::UserDoesNotExist)?;
+
+        println!("User {} deleted by {}", id, acting_user.username);
+
+        Ok(())
     }
 }
@@ -0,0 +1,14 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct AuditQuery {
+    /// Events where the user is either the actor or the target
+    pub user_id: Option<Uuid>,
+    pub action: Option<String>,
+    /// Inclusive lower bound of `occurred_at`
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound of `occurred_at`
+    pub to: Option<DateTime<Utc>>,
+    pub limit: i64,
+}
@@ -0,0 +1,5 @@
+pub mod audit_query;
+pub mod period;
+pub mod token;
+pub mod user_agent;
+pub mod user_query;
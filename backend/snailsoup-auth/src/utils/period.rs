@@ -0,0 +1,17 @@
+use chrono::NaiveDate;
+
+/// Half-open date range: `from` is included, `to` is not.
+#[derive(Debug, Clone, Copy)]
+pub struct DatePeriod {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl DatePeriod {
+    pub fn new(from: NaiveDate, to: NaiveDate) -> Option<DatePeriod> {
+        if from > to {
+            return None;
+        }
+        Some(DatePeriod { from, to })
+    }
+}
@@ -0,0 +1,41 @@
+/// Column length of `sessions.device_name`
+const MAX_DEVICE_NAME_LENGTH: usize = 255;
+
+/// Browser and operating system for showing a session to its user, e.g. "Firefox on Linux".
+/// Falls back to the raw User-Agent for other clients such as scripts.
+pub fn device_name(user_agent: Option<&str>) -> String {
+    let Some(user_agent) = user_agent.filter(|agent| !agent.trim().is_empty()) else {
+        return "Unknown device".to_owned();
+    };
+
+    // Order matters, e.g. Edge and Opera also claim to be Chrome and Chrome claims to be Safari
+    let browser = [
+        ("Edg/", "Edge"),
+        ("OPR/", "Opera"),
+        ("Firefox/", "Firefox"),
+        ("Chrome/", "Chrome"),
+        ("Safari/", "Safari"),
+    ]
+    .into_iter()
+    .find(|(token, _)| user_agent.contains(token))
+    .map(|(_, name)| name);
+
+    let os = [
+        ("Android", "Android"),
+        ("iPhone", "iOS"),
+        ("iPad", "iPadOS"),
+        ("Windows", "Windows"),
+        ("Mac OS X", "macOS"),
+        ("CrOS", "ChromeOS"),
+        ("Linux", "Linux"),
+    ]
+    .into_iter()
+    .find(|(token, _)| user_agent.contains(token))
+    .map(|(_, name)| name);
+
+    match (browser, os) {
+        (Some(browser), Some(os)) => format!("{} on {}", browser, os),
+        (Some(browser), None) => browser.to_owned(),
+        _ => user_agent.chars().take(MAX_DEVICE_NAME_LENGTH).collect(),
+    }
+}
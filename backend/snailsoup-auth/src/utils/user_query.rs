@@ -0,0 +1,86 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::domain::app_user::{AppUser, Role};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserSort {
+    Username,
+    CreatedAt,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct UserFilter {
+    pub role: Option<Role>,
+    /// Case-insensitive substring of the username
+    pub username_contains: Option<String>,
+    pub pending_approval: Option<bool>,
+}
+
+/// Sort key of the last user on a page. The next page starts right after it,
+/// so pages stay stable while accounts are added or removed.
+#[derive(Debug, Clone)]
+pub enum UserCursor {
+    Username(String, Uuid),
+    CreatedAt(DateTime<Utc>, Uuid),
+}
+
+#[derive(Debug, Clone)]
+pub struct UserQuery {
+    pub filter: UserFilter,
+    pub sort: UserSort,
+    pub order: SortOrder,
+    pub after: Option<UserCursor>,
+    pub limit: i64,
+}
+
+impl UserCursor {
+    pub fn after(user: &AppUser, sort: UserSort) -> UserCursor {
+        match sort {
+            UserSort::Username => UserCursor::Username(user.username.clone(), user.id),
+            UserSort::CreatedAt => UserCursor::CreatedAt(user.created_at, user.id),
+        }
+    }
+
+    pub fn sort(&self) -> UserSort {
+        match self {
+            UserCursor::Username(_, _) => UserSort::Username,
+            UserCursor::CreatedAt(_, _) => UserSort::CreatedAt,
+        }
+    }
+
+    /// Opaque, url-safe representation handed out to clients
+    pub fn encode(&self) -> String {
+        let raw = match self {
+            UserCursor::Username(username, id) => format!("u.{}.{}", id, username),
+            UserCursor::CreatedAt(created_at, id) => {
+                format!("c.{}.{}", id, created_at.timestamp_micros())
+            }
+        };
+        URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    pub fn decode(value: &str) -> Option<UserCursor> {
+        let raw = String::from_utf8(URL_SAFE_NO_PAD.decode(value).ok()?).ok()?;
+        let mut parts = raw.splitn(3, '.');
+        let kind = parts.next()?;
+        let id = Uuid::parse_str(parts.next()?).ok()?;
+        let key = parts.next()?;
+
+        match kind {
+            "u" => Some(UserCursor::Username(key.to_owned(), id)),
+            "c" => {
+                let created_at = DateTime::from_timestamp_micros(key.parse().ok()?)?;
+                Some(UserCursor::CreatedAt(created_at, id))
+            }
+            _ => None,
+        }
+    }
+}